@@ -0,0 +1,788 @@
+#include "snask_runtime.h"
+
+#include <ctype.h>
+#include <math.h>
+#include <stdarg.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+/* ---------------------------------------------------------------------------
+ * Memória e erros
+ * ------------------------------------------------------------------------- */
+
+static void *sn_alloc(size_t size) {
+    void *ptr = malloc(size ? size : 1);
+    if (!ptr) {
+        fprintf(stderr, "Erro de execução: memória insuficiente\n");
+        exit(1);
+    }
+    return ptr;
+}
+
+static void *sn_realloc(void *ptr, size_t size) {
+    void *new_ptr = realloc(ptr, size ? size : 1);
+    if (!new_ptr) {
+        fprintf(stderr, "Erro de execução: memória insuficiente\n");
+        exit(1);
+    }
+    return new_ptr;
+}
+
+void sn_init(int argc, char **argv) {
+    (void)argc;
+    (void)argv;
+}
+
+void sn_panic(const char *fmt, ...) {
+    va_list args;
+    va_start(args, fmt);
+    fprintf(stderr, "Erro de execução: ");
+    vfprintf(stderr, fmt, args);
+    fprintf(stderr, "\n");
+    va_end(args);
+    exit(1);
+}
+
+static const char *sn_type_name(SnValue v) {
+    switch (v.tag) {
+        case SN_NIL: return "nil";
+        case SN_NUM: return "número";
+        case SN_BOOL: return "booleano";
+        case SN_STR: return "string";
+        case SN_LIST: return "lista";
+        case SN_DICT: return "dicionário";
+        case SN_FUNC: return "função";
+    }
+    return "?";
+}
+
+/* ---------------------------------------------------------------------------
+ * Construtores
+ * ------------------------------------------------------------------------- */
+
+SnValue sn_nil(void) {
+    SnValue v;
+    v.tag = SN_NIL;
+    v.as.num = 0;
+    return v;
+}
+
+SnValue sn_num(double n) {
+    SnValue v;
+    v.tag = SN_NUM;
+    v.as.num = n;
+    return v;
+}
+
+SnValue sn_bool(int b) {
+    SnValue v;
+    v.tag = SN_BOOL;
+    v.as.boolean = b ? 1 : 0;
+    return v;
+}
+
+SnValue sn_str_len(const char *s, size_t len) {
+    SnString *str = sn_alloc(sizeof(SnString));
+    str->len = len;
+    str->data = sn_alloc(len + 1);
+    memcpy(str->data, s, len);
+    str->data[len] = '\0';
+    SnValue v;
+    v.tag = SN_STR;
+    v.as.str = str;
+    return v;
+}
+
+SnValue sn_str(const char *s) {
+    return sn_str_len(s, strlen(s));
+}
+
+static SnList *sn_list_new(size_t cap) {
+    SnList *list = sn_alloc(sizeof(SnList));
+    list->len = 0;
+    list->cap = cap;
+    list->items = sn_alloc(sizeof(SnValue) * cap);
+    return list;
+}
+
+static void sn_list_append(SnList *list, SnValue item) {
+    if (list->len == list->cap) {
+        list->cap = list->cap ? list->cap * 2 : 4;
+        list->items = sn_realloc(list->items, sizeof(SnValue) * list->cap);
+    }
+    list->items[list->len++] = item;
+}
+
+static SnValue sn_list_value(SnList *list) {
+    SnValue v;
+    v.tag = SN_LIST;
+    v.as.list = list;
+    return v;
+}
+
+SnValue sn_list(int count, ...) {
+    SnList *list = sn_list_new((size_t)count);
+    va_list args;
+    va_start(args, count);
+    for (int i = 0; i < count; i++) {
+        sn_list_append(list, va_arg(args, SnValue));
+    }
+    va_end(args);
+    return sn_list_value(list);
+}
+
+static void sn_dict_insert(SnDict *dict, SnValue key, SnValue value) {
+    for (size_t i = 0; i < dict->len; i++) {
+        if (sn_equals(dict->keys[i], key)) {
+            dict->values[i] = value;
+            return;
+        }
+    }
+    if (dict->len == dict->cap) {
+        dict->cap = dict->cap ? dict->cap * 2 : 4;
+        dict->keys = sn_realloc(dict->keys, sizeof(SnValue) * dict->cap);
+        dict->values = sn_realloc(dict->values, sizeof(SnValue) * dict->cap);
+    }
+    dict->keys[dict->len] = key;
+    dict->values[dict->len] = value;
+    dict->len++;
+}
+
+SnValue sn_dict(int pairs, ...) {
+    SnDict *dict = sn_alloc(sizeof(SnDict));
+    dict->len = 0;
+    dict->cap = (size_t)pairs;
+    dict->keys = sn_alloc(sizeof(SnValue) * dict->cap);
+    dict->values = sn_alloc(sizeof(SnValue) * dict->cap);
+    va_list args;
+    va_start(args, pairs);
+    for (int i = 0; i < pairs; i++) {
+        SnValue key = va_arg(args, SnValue);
+        SnValue value = va_arg(args, SnValue);
+        sn_dict_insert(dict, key, value);
+    }
+    va_end(args);
+    SnValue v;
+    v.tag = SN_DICT;
+    v.as.dict = dict;
+    return v;
+}
+
+SnValue sn_func(const char *name, int arity, SnFuncPtr ptr) {
+    SnFunc *func = sn_alloc(sizeof(SnFunc));
+    func->name = name;
+    func->arity = arity;
+    func->ptr = ptr;
+    SnValue v;
+    v.tag = SN_FUNC;
+    v.as.func = func;
+    return v;
+}
+
+/* ---------------------------------------------------------------------------
+ * Conversão para texto (mesmo formato do `Display` de `Value` no interpretador)
+ * ------------------------------------------------------------------------- */
+
+typedef struct {
+    size_t len;
+    size_t cap;
+    char *data;
+} SnBuffer;
+
+static void sn_buf_push(SnBuffer *buf, const char *s, size_t len) {
+    if (buf->len + len + 1 > buf->cap) {
+        while (buf->len + len + 1 > buf->cap) {
+            buf->cap = buf->cap ? buf->cap * 2 : 32;
+        }
+        buf->data = sn_realloc(buf->data, buf->cap);
+    }
+    memcpy(buf->data + buf->len, s, len);
+    buf->len += len;
+    buf->data[buf->len] = '\0';
+}
+
+static void sn_buf_push_str(SnBuffer *buf, const char *s) {
+    sn_buf_push(buf, s, strlen(s));
+}
+
+/* Menor representação decimal que preserva o valor, sem notação científica,
+ * como o `Display` de `f64` do Rust. */
+static void sn_format_number(SnBuffer *buf, double n) {
+    if (isnan(n)) {
+        sn_buf_push_str(buf, "NaN");
+        return;
+    }
+    if (isinf(n)) {
+        sn_buf_push_str(buf, n < 0 ? "-inf" : "inf");
+        return;
+    }
+
+    char sci[64];
+    for (int precision = 1; precision <= 17; precision++) {
+        snprintf(sci, sizeof(sci), "%.*e", precision - 1, n);
+        if (strtod(sci, NULL) == n) {
+            break;
+        }
+    }
+
+    /* sci = [-]d.ddddde[+-]xx */
+    char digits[32];
+    int ndigits = 0;
+    int negative = 0;
+    char *p = sci;
+    if (*p == '-') {
+        negative = 1;
+        p++;
+    }
+    while (*p && *p != 'e') {
+        if (isdigit((unsigned char)*p)) {
+            digits[ndigits++] = *p;
+        }
+        p++;
+    }
+    int exponent = atoi(p + 1);
+    while (ndigits > 1 && digits[ndigits - 1] == '0') {
+        ndigits--;
+    }
+
+    if (negative) {
+        sn_buf_push_str(buf, "-");
+    }
+    int point = exponent + 1; /* posição do ponto decimal em `digits` */
+    if (point <= 0) {
+        sn_buf_push_str(buf, "0.");
+        for (int i = 0; i < -point; i++) {
+            sn_buf_push_str(buf, "0");
+        }
+        sn_buf_push(buf, digits, (size_t)ndigits);
+    } else if (point >= ndigits) {
+        sn_buf_push(buf, digits, (size_t)ndigits);
+        for (int i = ndigits; i < point; i++) {
+            sn_buf_push_str(buf, "0");
+        }
+    } else {
+        sn_buf_push(buf, digits, (size_t)point);
+        sn_buf_push_str(buf, ".");
+        sn_buf_push(buf, digits + point, (size_t)(ndigits - point));
+    }
+}
+
+static void sn_format_value(SnBuffer *buf, SnValue v) {
+    switch (v.tag) {
+        case SN_NIL:
+            sn_buf_push_str(buf, "nil");
+            break;
+        case SN_NUM:
+            sn_format_number(buf, v.as.num);
+            break;
+        case SN_BOOL:
+            sn_buf_push_str(buf, v.as.boolean ? "true" : "false");
+            break;
+        case SN_STR:
+            sn_buf_push(buf, v.as.str->data, v.as.str->len);
+            break;
+        case SN_LIST:
+            sn_buf_push_str(buf, "[");
+            for (size_t i = 0; i < v.as.list->len; i++) {
+                if (i > 0) {
+                    sn_buf_push_str(buf, ", ");
+                }
+                sn_format_value(buf, v.as.list->items[i]);
+            }
+            sn_buf_push_str(buf, "]");
+            break;
+        case SN_DICT:
+            sn_buf_push_str(buf, "{");
+            for (size_t i = 0; i < v.as.dict->len; i++) {
+                if (i > 0) {
+                    sn_buf_push_str(buf, ", ");
+                }
+                sn_format_value(buf, v.as.dict->keys[i]);
+                sn_buf_push_str(buf, ": ");
+                sn_format_value(buf, v.as.dict->values[i]);
+            }
+            sn_buf_push_str(buf, "}");
+            break;
+        case SN_FUNC:
+            sn_buf_push_str(buf, "<fun ");
+            sn_buf_push_str(buf, v.as.func->name);
+            sn_buf_push_str(buf, ">");
+            break;
+    }
+}
+
+SnValue sn_to_string(SnValue v) {
+    if (v.tag == SN_STR) {
+        return v;
+    }
+    SnBuffer buf = {0, 0, NULL};
+    sn_format_value(&buf, v);
+    SnValue result = sn_str_len(buf.data ? buf.data : "", buf.len);
+    free(buf.data);
+    return result;
+}
+
+/* ---------------------------------------------------------------------------
+ * Conversões
+ * ------------------------------------------------------------------------- */
+
+double sn_as_num(SnValue v) {
+    if (v.tag != SN_NUM) {
+        sn_panic("esperado um número, mas encontrado %s", sn_type_name(v));
+    }
+    return v.as.num;
+}
+
+int sn_as_bool(SnValue v) {
+    if (v.tag != SN_BOOL) {
+        sn_panic("condição deve ser booleana, mas encontrado %s", sn_type_name(v));
+    }
+    return v.as.boolean;
+}
+
+/* ---------------------------------------------------------------------------
+ * Operadores
+ * ------------------------------------------------------------------------- */
+
+SnValue sn_add(SnValue a, SnValue b) {
+    if (a.tag == SN_NUM && b.tag == SN_NUM) {
+        return sn_num(a.as.num + b.as.num);
+    }
+    if (a.tag == SN_STR || b.tag == SN_STR) {
+        SnValue left = sn_to_string(a);
+        SnValue right = sn_to_string(b);
+        size_t len = left.as.str->len + right.as.str->len;
+        char *data = sn_alloc(len + 1);
+        memcpy(data, left.as.str->data, left.as.str->len);
+        memcpy(data + left.as.str->len, right.as.str->data, right.as.str->len);
+        SnValue result = sn_str_len(data, len);
+        free(data);
+        return result;
+    }
+    sn_panic("operador '+' não suportado para tipos %s e %s", sn_type_name(a), sn_type_name(b));
+    return sn_nil();
+}
+
+SnValue sn_sub(SnValue a, SnValue b) {
+    if (a.tag != SN_NUM || b.tag != SN_NUM) {
+        sn_panic("operador '-' não suportado para tipos %s e %s", sn_type_name(a), sn_type_name(b));
+    }
+    return sn_num(a.as.num - b.as.num);
+}
+
+SnValue sn_mul(SnValue a, SnValue b) {
+    if (a.tag != SN_NUM || b.tag != SN_NUM) {
+        sn_panic("operador '*' não suportado para tipos %s e %s", sn_type_name(a), sn_type_name(b));
+    }
+    return sn_num(a.as.num * b.as.num);
+}
+
+double sn_div_num(double a, double b) {
+    if (b == 0.0) {
+        sn_panic("divisão por zero");
+    }
+    return a / b;
+}
+
+SnValue sn_div(SnValue a, SnValue b) {
+    if (a.tag != SN_NUM || b.tag != SN_NUM) {
+        sn_panic("operador '/' não suportado para tipos %s e %s", sn_type_name(a), sn_type_name(b));
+    }
+    return sn_num(sn_div_num(a.as.num, b.as.num));
+}
+
+SnValue sn_neg(SnValue a) {
+    if (a.tag != SN_NUM) {
+        sn_panic("operador unário '-' aplicado a tipo não numérico: %s", sn_type_name(a));
+    }
+    return sn_num(-a.as.num);
+}
+
+int sn_equals(SnValue a, SnValue b) {
+    if (a.tag != b.tag) {
+        return 0;
+    }
+    switch (a.tag) {
+        case SN_NIL: return 1;
+        case SN_NUM: return a.as.num == b.as.num;
+        case SN_BOOL: return a.as.boolean == b.as.boolean;
+        case SN_STR:
+            return a.as.str->len == b.as.str->len &&
+                   memcmp(a.as.str->data, b.as.str->data, a.as.str->len) == 0;
+        case SN_LIST:
+            if (a.as.list->len != b.as.list->len) {
+                return 0;
+            }
+            for (size_t i = 0; i < a.as.list->len; i++) {
+                if (!sn_equals(a.as.list->items[i], b.as.list->items[i])) {
+                    return 0;
+                }
+            }
+            return 1;
+        case SN_DICT:
+            if (a.as.dict->len != b.as.dict->len) {
+                return 0;
+            }
+            for (size_t i = 0; i < a.as.dict->len; i++) {
+                SnValue key = a.as.dict->keys[i];
+                int found = 0;
+                for (size_t j = 0; j < b.as.dict->len; j++) {
+                    if (sn_equals(key, b.as.dict->keys[j])) {
+                        if (!sn_equals(a.as.dict->values[i], b.as.dict->values[j])) {
+                            return 0;
+                        }
+                        found = 1;
+                        break;
+                    }
+                }
+                if (!found) {
+                    return 0;
+                }
+            }
+            return 1;
+        case SN_FUNC: return a.as.func->ptr == b.as.func->ptr;
+    }
+    return 0;
+}
+
+static void sn_check_comparable(SnValue a, SnValue b) {
+    if (a.tag != SN_NUM || b.tag != SN_NUM) {
+        sn_panic("operadores de comparação não suportados para tipos %s e %s", sn_type_name(a), sn_type_name(b));
+    }
+}
+
+int sn_lt(SnValue a, SnValue b) { sn_check_comparable(a, b); return a.as.num < b.as.num; }
+int sn_gt(SnValue a, SnValue b) { sn_check_comparable(a, b); return a.as.num > b.as.num; }
+int sn_le(SnValue a, SnValue b) { sn_check_comparable(a, b); return a.as.num <= b.as.num; }
+int sn_ge(SnValue a, SnValue b) { sn_check_comparable(a, b); return a.as.num >= b.as.num; }
+
+/* ---------------------------------------------------------------------------
+ * Coleções
+ * ------------------------------------------------------------------------- */
+
+static int sn_dict_lookup(SnDict *dict, SnValue key, SnValue *out) {
+    for (size_t i = 0; i < dict->len; i++) {
+        if (sn_equals(dict->keys[i], key)) {
+            *out = dict->values[i];
+            return 1;
+        }
+    }
+    return 0;
+}
+
+SnValue sn_index(SnValue target, SnValue index) {
+    if (target.tag == SN_LIST) {
+        if (index.tag != SN_NUM) {
+            sn_panic("índice de lista não numérico: %s", sn_type_name(index));
+        }
+        /* Mesmo comportamento de `idx as usize`: negativos viram 0 */
+        size_t idx = index.as.num > 0 ? (size_t)index.as.num : 0;
+        if (idx >= target.as.list->len) {
+            sn_panic("índice fora dos limites da lista. Tamanho: %zu, Índice: %zu", target.as.list->len, idx);
+        }
+        return target.as.list->items[idx];
+    }
+    if (target.tag == SN_DICT) {
+        SnValue result;
+        if (sn_dict_lookup(target.as.dict, index, &result)) {
+            return result;
+        }
+        SnValue key = sn_to_string(index);
+        sn_panic("chave de dicionário não encontrada: %s", key.as.str->data);
+    }
+    sn_panic("tentativa de indexar valor não indexável: %s", sn_type_name(target));
+    return sn_nil();
+}
+
+SnValue sn_property(SnValue target, const char *name) {
+    if (target.tag != SN_DICT) {
+        sn_panic("tentativa de acessar propriedade '%s' em tipo não-objeto: %s", name, sn_type_name(target));
+    }
+    SnValue result;
+    if (!sn_dict_lookup(target.as.dict, sn_str(name), &result)) {
+        sn_panic("propriedade '%s' não encontrada no objeto", name);
+    }
+    return result;
+}
+
+size_t sn_iter_len(SnValue v) {
+    if (v.tag == SN_LIST) {
+        return v.as.list->len;
+    }
+    if (v.tag == SN_STR) {
+        return v.as.str->len;
+    }
+    sn_panic("'for-in' espera uma lista ou string, mas encontrado %s", sn_type_name(v));
+    return 0;
+}
+
+SnValue sn_iter_get(SnValue v, size_t i) {
+    if (v.tag == SN_LIST) {
+        return v.as.list->items[i];
+    }
+    return sn_str_len(v.as.str->data + i, 1);
+}
+
+SnValue sn_call(SnValue callee, int argc, SnValue *argv) {
+    if (callee.tag != SN_FUNC) {
+        sn_panic("tentativa de chamar um valor não-invocável: %s", sn_type_name(callee));
+    }
+    if (argc != callee.as.func->arity) {
+        sn_panic("número incorreto de argumentos para a função '%s'. Esperado %d, encontrado %d.",
+                 callee.as.func->name, callee.as.func->arity, argc);
+    }
+    return callee.as.func->ptr(argc, argv);
+}
+
+/* ---------------------------------------------------------------------------
+ * Entrada e saída
+ * ------------------------------------------------------------------------- */
+
+void sn_print(int count, ...) {
+    SnBuffer buf = {0, 0, NULL};
+    va_list args;
+    va_start(args, count);
+    for (int i = 0; i < count; i++) {
+        if (i > 0) {
+            sn_buf_push_str(&buf, " ");
+        }
+        sn_format_value(&buf, va_arg(args, SnValue));
+    }
+    va_end(args);
+    if (buf.data) {
+        fwrite(buf.data, 1, buf.len, stdout);
+    }
+    fputc('\n', stdout);
+    free(buf.data);
+}
+
+static char *sn_read_line(void) {
+    static char line[4096];
+    if (!fgets(line, sizeof(line), stdin)) {
+        sn_panic("não foi possível ler a entrada do console.");
+    }
+    /* trim */
+    char *start = line;
+    while (isspace((unsigned char)*start)) {
+        start++;
+    }
+    char *end = start + strlen(start);
+    while (end > start && isspace((unsigned char)end[-1])) {
+        *--end = '\0';
+    }
+    return start;
+}
+
+double sn_input_num(int integer) {
+    char *text = sn_read_line();
+    char *end;
+    double n = strtod(text, &end);
+    if (*text == '\0' || *end != '\0' || (integer && n != floor(n))) {
+        sn_panic("entrada inválida. Esperado um número%s, mas recebeu '%s'.", integer ? " inteiro" : " float", text);
+    }
+    return n;
+}
+
+SnValue sn_input_str(void) {
+    return sn_str(sn_read_line());
+}
+
+int sn_input_bool(void) {
+    char *text = sn_read_line();
+    if (strcmp(text, "true") == 0) {
+        return 1;
+    }
+    if (strcmp(text, "false") == 0) {
+        return 0;
+    }
+    sn_panic("entrada inválida. Esperado 'true' ou 'false', mas recebeu '%s'.", text);
+    return 0;
+}
+
+/* ---------------------------------------------------------------------------
+ * Biblioteca padrão
+ * ------------------------------------------------------------------------- */
+
+static SnString *sn_expect_str(SnValue v, const char *func) {
+    if (v.tag != SN_STR) {
+        sn_panic("%s espera uma string, mas recebeu %s", func, sn_type_name(v));
+    }
+    return v.as.str;
+}
+
+SnValue sn_format(int argc, SnValue *argv) {
+    if (argc < 1) {
+        sn_panic("format espera pelo menos 1 argumento");
+    }
+    SnString *template = sn_expect_str(argv[0], "format");
+    SnValue result = sn_str_len(template->data, template->len);
+    for (int i = 1; i < argc; i++) {
+        char placeholder[32];
+        snprintf(placeholder, sizeof(placeholder), "{%d}", i - 1);
+        SnValue replacement;
+        if (argv[i].tag == SN_LIST) {
+            replacement = sn_str("[lista]");
+        } else if (argv[i].tag == SN_DICT) {
+            replacement = sn_str("{dict}");
+        } else if (argv[i].tag == SN_FUNC) {
+            replacement = sn_str("?");
+        } else {
+            replacement = sn_to_string(argv[i]);
+        }
+
+        SnBuffer buf = {0, 0, NULL};
+        const char *cursor = result.as.str->data;
+        size_t placeholder_len = strlen(placeholder);
+        const char *found;
+        while ((found = strstr(cursor, placeholder)) != NULL) {
+            sn_buf_push(&buf, cursor, (size_t)(found - cursor));
+            sn_buf_push(&buf, replacement.as.str->data, replacement.as.str->len);
+            cursor = found + placeholder_len;
+        }
+        sn_buf_push_str(&buf, cursor);
+        result = sn_str_len(buf.data, buf.len);
+        free(buf.data);
+    }
+    return result;
+}
+
+double sn_len(SnValue v) {
+    switch (v.tag) {
+        case SN_STR: return (double)v.as.str->len;
+        case SN_LIST: return (double)v.as.list->len;
+        case SN_DICT: return (double)v.as.dict->len;
+        default:
+            sn_panic("len não suporta tipo %s", sn_type_name(v));
+    }
+    return 0;
+}
+
+static SnValue sn_map_chars(SnValue v, const char *func, int (*fn)(int)) {
+    SnString *s = sn_expect_str(v, func);
+    SnValue result = sn_str_len(s->data, s->len);
+    for (size_t i = 0; i < s->len; i++) {
+        result.as.str->data[i] = (char)fn((unsigned char)s->data[i]);
+    }
+    return result;
+}
+
+SnValue sn_upper(SnValue v) { return sn_map_chars(v, "string.upper", toupper); }
+SnValue sn_lower(SnValue v) { return sn_map_chars(v, "string.lower", tolower); }
+
+SnValue sn_trim(SnValue v) {
+    SnString *s = sn_expect_str(v, "string.trim");
+    size_t start = 0;
+    size_t end = s->len;
+    while (start < end && isspace((unsigned char)s->data[start])) {
+        start++;
+    }
+    while (end > start && isspace((unsigned char)s->data[end - 1])) {
+        end--;
+    }
+    return sn_str_len(s->data + start, end - start);
+}
+
+int sn_contains(SnValue haystack, SnValue needle) {
+    SnString *h = sn_expect_str(haystack, "string.contains");
+    SnString *n = sn_expect_str(needle, "string.contains");
+    return strstr(h->data, n->data) != NULL;
+}
+
+int sn_starts_with(SnValue s, SnValue prefix) {
+    SnString *str = sn_expect_str(s, "string.starts_with");
+    SnString *pre = sn_expect_str(prefix, "string.starts_with");
+    return pre->len <= str->len && memcmp(str->data, pre->data, pre->len) == 0;
+}
+
+int sn_ends_with(SnValue s, SnValue suffix) {
+    SnString *str = sn_expect_str(s, "string.ends_with");
+    SnString *suf = sn_expect_str(suffix, "string.ends_with");
+    return suf->len <= str->len && memcmp(str->data + str->len - suf->len, suf->data, suf->len) == 0;
+}
+
+SnValue sn_range(int argc, double start, double end, double step) {
+    if (argc == 1) {
+        end = start;
+        start = 0;
+        step = 1;
+    } else if (argc == 2) {
+        step = 1;
+    }
+    if (step == 0.0) {
+        sn_panic("step não pode ser zero");
+    }
+    SnList *list = sn_list_new(0);
+    double current = start;
+    if (step > 0) {
+        while (current < end) {
+            sn_list_append(list, sn_num(current));
+            current += step;
+        }
+    } else {
+        while (current > end) {
+            sn_list_append(list, sn_num(current));
+            current += step;
+        }
+    }
+    return sn_list_value(list);
+}
+
+SnValue sn_push(SnValue list, SnValue item) {
+    if (list.tag != SN_LIST) {
+        sn_panic("o primeiro argumento de push() deve ser uma lista");
+    }
+    SnList *copy = sn_list_new(list.as.list->len + 1);
+    for (size_t i = 0; i < list.as.list->len; i++) {
+        sn_list_append(copy, list.as.list->items[i]);
+    }
+    sn_list_append(copy, item);
+    return sn_list_value(copy);
+}
+
+SnValue sn_get(SnValue dict, SnValue key) {
+    if (dict.tag != SN_DICT) {
+        sn_panic("collections.get espera um dicionário, mas recebeu %s", sn_type_name(dict));
+    }
+    SnValue result;
+    if (sn_dict_lookup(dict.as.dict, key, &result)) {
+        return result;
+    }
+    return sn_nil();
+}
+
+SnValue sn_reverse(SnValue list) {
+    if (list.tag != SN_LIST) {
+        sn_panic("reverse espera uma lista");
+    }
+    SnList *copy = sn_list_new(list.as.list->len);
+    for (size_t i = list.as.list->len; i > 0; i--) {
+        sn_list_append(copy, list.as.list->items[i - 1]);
+    }
+    return sn_list_value(copy);
+}
+
+double sn_mod(double a, double b) {
+    if (b == 0.0) {
+        sn_panic("mod: divisão por zero");
+    }
+    return fmod(a, b);
+}
+
+double sn_sqrt(double n) {
+    if (n < 0) {
+        sn_panic("sqrt não aceita números negativos");
+    }
+    return sqrt(n);
+}
+
+double sn_sign(double n) {
+    return n > 0 ? 1.0 : (n < 0 ? -1.0 : 0.0);
+}
+
+double sn_clamp(double v, double lo, double hi) {
+    if (lo > hi) {
+        sn_panic("clamp: min deve ser menor ou igual a max");
+    }
+    return v < lo ? lo : (v > hi ? hi : v);
+}
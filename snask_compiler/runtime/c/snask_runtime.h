@@ -0,0 +1,131 @@
+/*
+ * Runtime mínimo usado pelo código C gerado por `snask compile --emit c`.
+ *
+ * Valores dinâmicos são representados por `SnValue`. Números e booleanos que o
+ * analisador semântico consegue tipar são gerados diretamente como `double` e
+ * `int`; só o que é dinâmico passa por este runtime.
+ *
+ * Os valores do Snask são imutáveis (operações de lista/dicionário retornam
+ * novas coleções), então o runtime compartilha ponteiros livremente e não
+ * libera memória: é adequado para programas de linha de comando.
+ */
+#ifndef SNASK_RUNTIME_H
+#define SNASK_RUNTIME_H
+
+#include <stddef.h>
+
+typedef enum {
+    SN_NIL,
+    SN_NUM,
+    SN_BOOL,
+    SN_STR,
+    SN_LIST,
+    SN_DICT,
+    SN_FUNC
+} SnTag;
+
+struct SnValue;
+typedef struct SnValue (*SnFuncPtr)(int argc, struct SnValue *argv);
+
+typedef struct SnString {
+    size_t len;
+    char *data;
+} SnString;
+
+typedef struct SnList {
+    size_t len;
+    size_t cap;
+    struct SnValue *items;
+} SnList;
+
+typedef struct SnDict {
+    size_t len;
+    size_t cap;
+    struct SnValue *keys;
+    struct SnValue *values;
+} SnDict;
+
+typedef struct SnFunc {
+    const char *name;
+    int arity;
+    SnFuncPtr ptr;
+} SnFunc;
+
+typedef struct SnValue {
+    SnTag tag;
+    union {
+        double num;
+        int boolean;
+        SnString *str;
+        SnList *list;
+        SnDict *dict;
+        SnFunc *func;
+    } as;
+} SnValue;
+
+/* Inicialização e erros */
+void sn_init(int argc, char **argv);
+void sn_panic(const char *fmt, ...);
+
+/* Construtores */
+SnValue sn_nil(void);
+SnValue sn_num(double n);
+SnValue sn_bool(int b);
+SnValue sn_str(const char *s);
+SnValue sn_str_len(const char *s, size_t len);
+SnValue sn_list(int count, ...);
+SnValue sn_dict(int pairs, ...);
+SnValue sn_func(const char *name, int arity, SnFuncPtr ptr);
+
+/* Conversões entre valores dinâmicos e tipos C */
+double sn_as_num(SnValue v);
+int sn_as_bool(SnValue v);
+SnValue sn_to_string(SnValue v);
+
+/* Operadores */
+SnValue sn_add(SnValue a, SnValue b);
+SnValue sn_sub(SnValue a, SnValue b);
+SnValue sn_mul(SnValue a, SnValue b);
+SnValue sn_div(SnValue a, SnValue b);
+SnValue sn_neg(SnValue a);
+double sn_div_num(double a, double b);
+int sn_equals(SnValue a, SnValue b);
+int sn_lt(SnValue a, SnValue b);
+int sn_gt(SnValue a, SnValue b);
+int sn_le(SnValue a, SnValue b);
+int sn_ge(SnValue a, SnValue b);
+
+/* Acesso a coleções */
+SnValue sn_index(SnValue target, SnValue index);
+SnValue sn_property(SnValue target, const char *name);
+size_t sn_iter_len(SnValue v);
+SnValue sn_iter_get(SnValue v, size_t i);
+
+/* Chamadas dinâmicas */
+SnValue sn_call(SnValue callee, int argc, SnValue *argv);
+
+/* Entrada e saída */
+void sn_print(int count, ...);
+double sn_input_num(int integer);
+SnValue sn_input_str(void);
+int sn_input_bool(void);
+
+/* Biblioteca padrão suportada pelo backend C */
+SnValue sn_format(int argc, SnValue *argv);
+double sn_len(SnValue v);
+SnValue sn_upper(SnValue v);
+SnValue sn_lower(SnValue v);
+SnValue sn_trim(SnValue v);
+int sn_contains(SnValue haystack, SnValue needle);
+int sn_starts_with(SnValue s, SnValue prefix);
+int sn_ends_with(SnValue s, SnValue suffix);
+SnValue sn_range(int argc, double start, double end, double step);
+SnValue sn_push(SnValue list, SnValue item);
+SnValue sn_get(SnValue dict, SnValue key);
+SnValue sn_reverse(SnValue list);
+double sn_mod(double a, double b);
+double sn_sqrt(double n);
+double sn_sign(double n);
+double sn_clamp(double v, double lo, double hi);
+
+#endif
@@ -0,0 +1,70 @@
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::RwLock;
+
+lazy_static! {
+    /// Arquivos embutidos em um executável gerado por `snask build`, indexados
+    /// pelo caminho relativo normalizado (ex: "templates/home.html").
+    static ref EMBEDDED_ASSETS: RwLock<HashMap<String, Vec<u8>>> = RwLock::new(HashMap::new());
+}
+
+/// Registra os arquivos embutidos. Eles passam a ter prioridade sobre o disco.
+pub fn register_embedded(assets: HashMap<String, Vec<u8>>) {
+    let mut embedded = EMBEDDED_ASSETS.write().unwrap();
+    for (path, bytes) in assets {
+        embedded.insert(normalize(&path), bytes);
+    }
+}
+
+/// Normaliza um caminho relativo para a chave usada no mapa de assets
+pub fn normalize(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    path.split('/')
+        .filter(|part| !part.is_empty() && *part != ".")
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Lê um arquivo, procurando primeiro nos assets embutidos e depois no disco
+pub fn read(path: &str) -> io::Result<Vec<u8>> {
+    if let Some(bytes) = EMBEDDED_ASSETS.read().unwrap().get(&normalize(path)) {
+        return Ok(bytes.clone());
+    }
+    fs::read(path)
+}
+
+pub fn read_to_string(path: &str) -> io::Result<String> {
+    let bytes = read(path)?;
+    String::from_utf8(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Equivalente a `Path::is_file`, considerando também os assets embutidos
+pub fn is_file(path: &str) -> bool {
+    EMBEDDED_ASSETS.read().unwrap().contains_key(&normalize(path)) || Path::new(path).is_file()
+}
+
+/// Coleta recursivamente todos os arquivos de um diretório para embutir no executável
+pub fn collect_dir(dir: &str) -> Result<HashMap<String, Vec<u8>>, String> {
+    let mut assets = HashMap::new();
+    collect_dir_into(Path::new(dir), &mut assets)?;
+    Ok(assets)
+}
+
+fn collect_dir_into(dir: &Path, assets: &mut HashMap<String, Vec<u8>>) -> Result<(), String> {
+    let entries = fs::read_dir(dir)
+        .map_err(|e| format!("Não foi possível ler o diretório de assets {}: {}", dir.display(), e))?;
+    for entry in entries {
+        let path = entry.map_err(|e| e.to_string())?.path();
+        if path.is_dir() {
+            collect_dir_into(&path, assets)?;
+        } else {
+            let bytes = fs::read(&path)
+                .map_err(|e| format!("Não foi possível ler o asset {}: {}", path.display(), e))?;
+            assets.insert(normalize(&path.to_string_lossy()), bytes);
+        }
+    }
+    Ok(())
+}
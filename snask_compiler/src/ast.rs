@@ -1,13 +1,15 @@
+use crate::span::Span;
 use crate::types::Type;
+use serde::{Deserialize, Serialize};
 
-// Moved from parser.rs to be a central part of the AST
-#[derive(Debug, PartialEq, Clone)]
+// Posição de um token; os nós do AST guardam um `Span` completo
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct Location {
     pub line: usize,
     pub column: usize,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub enum BinaryOp {
     Add,
     Subtract,
@@ -21,12 +23,12 @@ pub enum BinaryOp {
     LessThanOrEquals,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub enum UnaryOp {
     Negative,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub enum LiteralValue {
     Number(f64),
     String(String),
@@ -36,14 +38,14 @@ pub enum LiteralValue {
     Nil,
 }
 
-// Wrapper struct for Expression, including location info
-#[derive(Debug, PartialEq, Clone)]
+// Wrapper struct for Expression, including the source span
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct Expr {
     pub kind: ExprKind,
-    pub loc: Location,
+    pub span: Span,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub enum ExprKind {
     Literal(LiteralValue),
     Variable(String),
@@ -70,17 +72,21 @@ pub enum ExprKind {
     },
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct VarDecl {
     pub name: String,
     pub var_type: Option<Type>,
+    /// Trecho da anotação de tipo (`: int`), se houver
+    pub type_span: Option<Span>,
     pub value: Expr,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct MutDecl {
     pub name: String,
     pub var_type: Option<Type>,
+    /// Trecho da anotação de tipo (`: int`), se houver
+    pub type_span: Option<Span>,
     pub value: Expr,
 }
 
@@ -89,16 +95,21 @@ impl MutDecl {
         VarDecl {
             name: self.name.clone(),
             var_type: self.var_type.clone(),
+            type_span: self.type_span,
             value: self.value.clone(),
         }
     }
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct ConstDecl {
     pub name: String,
     pub var_type: Option<Type>,
+    /// Trecho da anotação de tipo (`: int`), se houver
+    pub type_span: Option<Span>,
     pub value: Expr,
+    /// Comentário `///` logo antes da declaração
+    pub doc: Option<String>,
 }
 
 impl ConstDecl {
@@ -106,39 +117,54 @@ impl ConstDecl {
         VarDecl {
             name: self.name.clone(),
             var_type: self.var_type.clone(),
+            type_span: self.type_span,
             value: self.value.clone(),
         }
     }
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct VarSet {
     pub name: String,
     pub value: Expr,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Param {
+    pub name: String,
+    /// `Type::Any` quando o parâmetro não é anotado
+    pub param_type: Type,
+    /// Trecho do parâmetro inteiro (`nome: tipo`)
+    pub span: Span,
+    pub type_span: Option<Span>,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct FuncDecl {
     pub name: String,
-    pub params: Vec<(String, Type)>,
+    pub name_span: Span,
+    pub params: Vec<Param>,
     pub return_type: Option<Type>,
+    pub return_type_span: Option<Span>,
     pub body: Vec<Stmt>,
+    /// Comentário `///` logo antes da declaração
+    pub doc: Option<String>,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct IfBlock {
     pub condition: Expr,
     pub body: Vec<Stmt>,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct ConditionalStmt {
     pub if_block: IfBlock,
     pub elif_blocks: Vec<IfBlock>,
     pub else_block: Option<Vec<Stmt>>,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub enum LoopStmt {
     While {
         condition: Expr,
@@ -151,41 +177,45 @@ pub enum LoopStmt {
     },
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct ListDecl {
     pub name: String,
     pub var_type: Option<Type>,
+    /// Trecho da anotação de tipo (`: int`), se houver
+    pub type_span: Option<Span>,
     pub value: Expr,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct ListPush {
     pub name: String,
     pub value: Expr,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct DictDecl {
     pub name: String,
     pub var_type: Option<Type>,
+    /// Trecho da anotação de tipo (`: int`), se houver
+    pub type_span: Option<Span>,
     pub value: Expr,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct DictSet {
     pub name: String,
     pub key: Expr,
     pub value: Expr,
 }
 
-// Wrapper struct for Statement, including location info
-#[derive(Debug, PartialEq, Clone)]
+// Wrapper struct for Statement, including the source span
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct Stmt {
     pub kind: StmtKind,
-    pub loc: Location,
+    pub span: Span,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub enum StmtKind {
     Expression(Expr),
     VarDeclaration(VarDecl),
@@ -196,6 +226,7 @@ pub enum StmtKind {
     Input {
         name: String,
         var_type: Type,
+        type_span: Span,
     },
     FuncDeclaration(FuncDecl),
     FuncCall(Expr),
@@ -207,6 +238,20 @@ pub enum StmtKind {
     DictDeclaration(DictDecl),
     DictSet(DictSet),
     Import(String),
+    /// `type Nome = tipo`
+    TypeAlias {
+        name: String,
+        name_span: Span,
+        target: Type,
+        type_span: Span,
+    },
+    /// `test "descrição" { ... }`, executado só por `snask test`
+    Test {
+        name: String,
+        /// Trecho da descrição, com as aspas
+        name_span: Span,
+        body: Vec<Stmt>,
+    },
 }
 
 pub type Program = Vec<Stmt>;
@@ -0,0 +1,184 @@
+use crate::assets;
+use crate::ast::{LoopStmt, Program, Stmt, StmtKind};
+use crate::interpreter::{InterpretResult, Interpreter};
+use crate::modules;
+use crate::optimizer::{optimize, OptLevel};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+/// Marcador gravado no fim de um executável que carrega um programa embutido
+const BUNDLE_MAGIC: &[u8; 8] = b"SNASKBND";
+
+/// Tamanho do trailer: comprimento do payload (u64 LE) + marcador
+const TRAILER_LEN: u64 = 16;
+
+/// Tudo o que um executável standalone precisa para rodar sem o toolchain:
+/// o programa principal, os módulos importados e os diretórios de assets.
+#[derive(Serialize, Deserialize)]
+pub struct Bundle {
+    pub compiler_version: String,
+    pub entry: String,
+    pub opt_level: u8,
+    pub program: Program,
+    pub modules: HashMap<String, Program>,
+    pub assets: HashMap<String, Vec<u8>>,
+}
+
+impl Bundle {
+    /// Monta o bundle a partir do programa já parseado e analisado
+    pub fn new(entry: &str, program: Program, asset_dirs: &[String], opt_level: u8) -> Result<Self, String> {
+        let mut modules = HashMap::new();
+        collect_modules(&program, &mut modules)?;
+
+        let mut assets = HashMap::new();
+        for dir in asset_dirs {
+            assets.extend(assets::collect_dir(dir)?);
+        }
+
+        Ok(Bundle {
+            compiler_version: env!("CARGO_PKG_VERSION").to_string(),
+            entry: entry.to_string(),
+            opt_level,
+            program,
+            modules,
+            assets,
+        })
+    }
+
+    /// Grava um executável em `output`: uma cópia do interpretador atual com o
+    /// bundle serializado anexado ao final.
+    pub fn write_executable(&self, output: &str) -> Result<(), String> {
+        let current_exe = std::env::current_exe()
+            .map_err(|e| format!("Não foi possível localizar o executável do Snask: {}", e))?;
+        let runtime = read_runtime(&current_exe)?;
+        let payload = bincode::serialize(self)
+            .map_err(|e| format!("Falha ao serializar o programa: {}", e))?;
+
+        let mut file = File::create(output)
+            .map_err(|e| format!("Não foi possível criar {}: {}", output, e))?;
+        file.write_all(&runtime)
+            .and_then(|_| file.write_all(&payload))
+            .and_then(|_| file.write_all(&(payload.len() as u64).to_le_bytes()))
+            .and_then(|_| file.write_all(BUNDLE_MAGIC))
+            .map_err(|e| format!("Falha ao escrever {}: {}", output, e))?;
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(output, fs::Permissions::from_mode(0o755))
+                .map_err(|e| format!("Não foi possível tornar {} executável: {}", output, e))?;
+        }
+
+        Ok(())
+    }
+
+    /// Executa o programa embutido. Retorna o código de saída do processo.
+    pub fn run(self) -> i32 {
+        modules::register_embedded_modules(self.modules);
+        assets::register_embedded(self.assets);
+
+        let opt_level = OptLevel::from_level(self.opt_level);
+        let (program, _) = optimize(self.program, opt_level);
+
+        let mut interpreter = Interpreter::new();
+        interpreter.set_opt_level(opt_level);
+        interpreter.set_file(&self.entry);
+        match interpreter.interpret(program) {
+            InterpretResult::Ok => 0,
+            InterpretResult::RuntimeError(e) => {
+                eprintln!("Erro de execução: {}", e);
+                1
+            }
+        }
+    }
+}
+
+/// Procura um bundle anexado ao executável em execução
+pub fn load_embedded() -> Option<Bundle> {
+    let current_exe = std::env::current_exe().ok()?;
+    let mut file = File::open(current_exe).ok()?;
+    let payload_len = read_trailer(&mut file)?;
+
+    file.seek(SeekFrom::End(-((TRAILER_LEN + payload_len) as i64))).ok()?;
+    let mut payload = vec![0u8; payload_len as usize];
+    file.read_exact(&mut payload).ok()?;
+    bincode::deserialize(&payload).ok()
+}
+
+/// Lê o trailer do arquivo e retorna o tamanho do payload, se houver um bundle
+fn read_trailer(file: &mut File) -> Option<u64> {
+    let file_len = file.metadata().ok()?.len();
+    if file_len < TRAILER_LEN {
+        return None;
+    }
+    file.seek(SeekFrom::End(-(TRAILER_LEN as i64))).ok()?;
+    let mut trailer = [0u8; TRAILER_LEN as usize];
+    file.read_exact(&mut trailer).ok()?;
+    if &trailer[8..] != BUNDLE_MAGIC {
+        return None;
+    }
+    let mut len_bytes = [0u8; 8];
+    len_bytes.copy_from_slice(&trailer[..8]);
+    let payload_len = u64::from_le_bytes(len_bytes);
+    if payload_len + TRAILER_LEN > file_len {
+        return None;
+    }
+    Some(payload_len)
+}
+
+/// Bytes do interpretador sem nenhum bundle anexado. Permite rodar
+/// `snask build` a partir de um executável que já é standalone.
+fn read_runtime(exe: &Path) -> Result<Vec<u8>, String> {
+    let mut file = File::open(exe)
+        .map_err(|e| format!("Não foi possível ler {}: {}", exe.display(), e))?;
+    let runtime_len = match read_trailer(&mut file) {
+        Some(payload_len) => file.metadata().map_err(|e| e.to_string())?.len() - payload_len - TRAILER_LEN,
+        None => file.metadata().map_err(|e| e.to_string())?.len(),
+    };
+    file.seek(SeekFrom::Start(0)).map_err(|e| e.to_string())?;
+    let mut runtime = vec![0u8; runtime_len as usize];
+    file.read_exact(&mut runtime)
+        .map_err(|e| format!("Não foi possível ler {}: {}", exe.display(), e))?;
+    Ok(runtime)
+}
+
+/// Resolve recursivamente todos os `import` de arquivos `.snask` do programa
+fn collect_modules(program: &Program, modules: &mut HashMap<String, Program>) -> Result<(), String> {
+    let mut imports = Vec::new();
+    collect_imports(program, &mut imports);
+
+    for path in imports {
+        if modules::is_native_module(&path) || modules.contains_key(&path) {
+            continue;
+        }
+        let module_program = modules::load_module(&path)?;
+        modules.insert(path, module_program.clone());
+        collect_modules(&module_program, modules)?;
+    }
+    Ok(())
+}
+
+fn collect_imports(statements: &[Stmt], imports: &mut Vec<String>) {
+    for stmt in statements {
+        match &stmt.kind {
+            StmtKind::Import(path) => imports.push(path.clone()),
+            StmtKind::FuncDeclaration(func) => collect_imports(&func.body, imports),
+            StmtKind::Conditional(cond) => {
+                collect_imports(&cond.if_block.body, imports);
+                for elif in &cond.elif_blocks {
+                    collect_imports(&elif.body, imports);
+                }
+                if let Some(else_body) = &cond.else_block {
+                    collect_imports(else_body, imports);
+                }
+            }
+            StmtKind::Loop(LoopStmt::While { body, .. }) | StmtKind::Loop(LoopStmt::For { body, .. }) => {
+                collect_imports(body, imports)
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use crate::ast::Program;
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Extensão dos arquivos de cache de módulos pré-compilados
+pub const CACHE_EXTENSION: &str = "snaskc";
+
+/// Versão do compilador gravada em cada arquivo de cache
+const COMPILER_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Versão do formato do AST. O bincode não descreve a estrutura gravada, então
+/// qualquer mudança nos nós do AST precisa incrementar este número.
+const AST_FORMAT_VERSION: u32 = 7;
+
+static CACHE_ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// Conteúdo de um arquivo `.snaskc`
+#[derive(Serialize, Deserialize)]
+struct CachedModule {
+    compiler_version: String,
+    ast_format_version: u32,
+    source_hash: u64,
+    program: Program,
+}
+
+/// Liga ou desliga o cache (ex: `snask interpret --no-cache`)
+pub fn set_enabled(enabled: bool) {
+    CACHE_ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    CACHE_ENABLED.load(Ordering::Relaxed)
+}
+
+pub fn get_cache_dir() -> Option<PathBuf> {
+    if let Some(proj_dirs) = ProjectDirs::from("com", "Snask", "Snask") {
+        let dir = proj_dirs.data_dir().join("cache");
+        if !dir.exists() {
+            let _ = fs::create_dir_all(&dir);
+        }
+        Some(dir)
+    } else {
+        None
+    }
+}
+
+/// Hash FNV-1a de 64 bits. Usamos um hash próprio (e não o `DefaultHasher`)
+/// porque o valor precisa ser estável entre execuções e versões do Rust.
+pub fn source_hash(source: &str) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in source.as_bytes() {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+/// Caminho do arquivo de cache para um arquivo fonte.
+/// O nome é derivado do caminho absoluto do fonte, para que módulos com o
+/// mesmo nome em diretórios diferentes não colidam.
+fn cache_path_for(source_path: &Path) -> Option<PathBuf> {
+    let cache_dir = get_cache_dir()?;
+    let absolute = fs::canonicalize(source_path).unwrap_or_else(|_| source_path.to_path_buf());
+    let stem = source_path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "module".to_string());
+    let name = format!(
+        "{}-{:016x}.{}",
+        stem,
+        source_hash(&absolute.to_string_lossy()),
+        CACHE_EXTENSION
+    );
+    Some(cache_dir.join(name))
+}
+
+/// Carrega o AST do cache se ele existir e ainda for válido para `source`
+pub fn load(source_path: &Path, source: &str) -> Option<Program> {
+    if !is_enabled() {
+        return None;
+    }
+    let cache_path = cache_path_for(source_path)?;
+    let bytes = fs::read(cache_path).ok()?;
+    let cached: CachedModule = bincode::deserialize(&bytes).ok()?;
+
+    if cached.compiler_version != COMPILER_VERSION
+        || cached.ast_format_version != AST_FORMAT_VERSION
+        || cached.source_hash != source_hash(source)
+    {
+        return None;
+    }
+    Some(cached.program)
+}
+
+/// Grava o AST de `source_path` no cache. Falhas são ignoradas: o cache é só
+/// uma otimização e nunca deve impedir a execução do programa.
+pub fn store(source_path: &Path, source: &str, program: &Program) {
+    if !is_enabled() {
+        return;
+    }
+    let cache_path = match cache_path_for(source_path) {
+        Some(p) => p,
+        None => return,
+    };
+    let cached = CachedModule {
+        compiler_version: COMPILER_VERSION.to_string(),
+        ast_format_version: AST_FORMAT_VERSION,
+        source_hash: source_hash(source),
+        program: program.clone(),
+    };
+    if let Ok(bytes) = bincode::serialize(&cached) {
+        // Escreve em um arquivo temporário e renomeia, para que execuções
+        // concorrentes nunca leiam um cache pela metade.
+        let tmp_path = cache_path.with_extension(format!("{}.tmp", CACHE_EXTENSION));
+        if fs::write(&tmp_path, bytes).is_ok() {
+            let _ = fs::rename(&tmp_path, &cache_path);
+        }
+    }
+}
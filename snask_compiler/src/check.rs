@@ -0,0 +1,301 @@
+//! `snask check`: análise léxica, sintática e semântica sem executar o programa,
+//! com saída em texto, JSON ou SARIF para editores e hooks de pre-commit.
+
+use crate::ast::Program;
+use crate::diagnostics::{Annotation, Diagnostic, DiagnosticBag, Severity};
+use crate::lint;
+use crate::parser::parse_program;
+use crate::semantic_analyzer::SemanticAnalyzer;
+use crate::span::Span;
+use clap::ValueEnum;
+use serde_json::{json, Value as JsonValue};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// Diagnósticos da análise semântica e, se ela não encontrou erros, dos lints
+pub fn diagnose(program: &Program, source: &str) -> Vec<Diagnostic> {
+    analyze(program, source).1
+}
+
+/// Como `diagnose`, devolvendo também o analisador, com os tipos e nomes que ele encontrou
+pub fn analyze(program: &Program, source: &str) -> (SemanticAnalyzer, Vec<Diagnostic>) {
+    let mut analyzer = SemanticAnalyzer::new();
+    analyzer.analyze(program);
+
+    let mut diagnostics: Vec<Diagnostic> = analyzer
+        .errors
+        .iter()
+        .map(|error| error.to_diagnostic())
+        .chain(analyzer.warnings.iter().map(|warning| warning.to_diagnostic()))
+        .collect();
+    if !diagnostics.iter().any(|d| d.severity == Severity::Error) {
+        diagnostics.extend(lint::lint(program, source).iter().map(|lint| lint.to_diagnostic()));
+    }
+    (analyzer, diagnostics)
+}
+
+/// Resultado da verificação de um arquivo
+#[derive(Debug)]
+pub struct FileReport {
+    pub path: String,
+    pub source: String,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl FileReport {
+    pub fn check(path: &Path) -> Self {
+        let display = path.to_string_lossy().into_owned();
+        let source = match fs::read_to_string(path) {
+            Ok(source) => source,
+            Err(e) => {
+                let diagnostic = Diagnostic::error(format!("não foi possível ler o arquivo: {}", e));
+                return FileReport { path: display, source: String::new(), diagnostics: vec![diagnostic] };
+            }
+        };
+        let diagnostics = match parse_program(&source) {
+            Ok(program) => diagnose(&program, &source),
+            Err(diagnostics) => diagnostics,
+        };
+        FileReport { path: display, source, diagnostics }
+    }
+
+    pub fn error_count(&self) -> usize {
+        self.diagnostics.iter().filter(|d| d.severity == Severity::Error).count()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CheckFormat {
+    /// Human-readable diagnostics with source snippets
+    Text,
+    /// A JSON object with every diagnostic and its spans
+    Json,
+    /// SARIF 2.1.0, for code scanning tools
+    Sarif,
+}
+
+/// Arquivos `.snask` dos caminhos dados; diretórios são percorridos
+/// recursivamente, ignorando os ocultos (`.git`, ...)
+pub fn collect_files(paths: &[String]) -> Result<Vec<PathBuf>, String> {
+    let mut files = Vec::new();
+    for path in paths {
+        let path = Path::new(path);
+        if path.is_dir() {
+            collect_dir(path, &mut files).map_err(|e| format!("Não foi possível ler o diretório {}: {}", path.display(), e))?;
+        } else if path.exists() {
+            files.push(path.to_path_buf());
+        } else {
+            return Err(format!("O caminho {} não existe", path.display()));
+        }
+    }
+    Ok(files)
+}
+
+fn collect_dir(dir: &Path, files: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    let mut entries: Vec<PathBuf> = fs::read_dir(dir)?.map(|entry| entry.map(|e| e.path())).collect::<Result<_, _>>()?;
+    entries.sort();
+    for path in entries {
+        if path.file_name().is_some_and(|name| name.to_string_lossy().starts_with('.')) {
+            continue;
+        }
+        if path.is_dir() {
+            collect_dir(&path, files)?;
+        } else if path.extension().is_some_and(|ext| ext == "snask") {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Verifica os arquivos e escreve o resultado em stdout. Retorna o código de
+/// saída: 1 se algum arquivo tem erros, 0 caso contrário.
+pub fn run(paths: &[String], format: CheckFormat) -> Result<i32, String> {
+    let files = collect_files(paths)?;
+    let reports: Vec<FileReport> = files.iter().map(|file| FileReport::check(file)).collect();
+
+    match format {
+        CheckFormat::Text => print!("{}", render_text(&reports)),
+        CheckFormat::Json => println!("{}", serde_json::to_string_pretty(&to_json(&reports)).unwrap()),
+        CheckFormat::Sarif => println!("{}", serde_json::to_string_pretty(&to_sarif(&reports)).unwrap()),
+    }
+
+    let has_errors = reports.iter().any(|report| report.error_count() > 0);
+    Ok(if has_errors { 1 } else { 0 })
+}
+
+fn render_text(reports: &[FileReport]) -> String {
+    let mut output = String::new();
+    let (mut errors, mut warnings) = (0, 0);
+    for report in reports {
+        let bag = DiagnosticBag::from(report.diagnostics.clone());
+        errors += bag.error_count();
+        warnings += bag.warning_count();
+        for diagnostic in bag.diagnostics() {
+            output.push_str(&diagnostic.render(&report.path, &report.source));
+            output.push('\n');
+        }
+    }
+    output.push_str(&format!(
+        "{} arquivo{} verificado{}: {} erro{}, {} aviso{}\n",
+        reports.len(),
+        if reports.len() == 1 { "" } else { "s" },
+        if reports.len() == 1 { "" } else { "s" },
+        errors,
+        if errors == 1 { "" } else { "s" },
+        warnings,
+        if warnings == 1 { "" } else { "s" },
+    ));
+    output
+}
+
+fn severity_name(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Info => "info",
+        Severity::Hint => "hint",
+    }
+}
+
+/// Anotação que aponta o problema: a primeira com a severidade do diagnóstico,
+/// ou a primeira de todas
+pub fn primary_annotation(diagnostic: &Diagnostic) -> Option<&Annotation> {
+    diagnostic
+        .annotations
+        .iter()
+        .find(|annotation| annotation.severity == diagnostic.severity)
+        .or_else(|| diagnostic.annotations.first())
+}
+
+fn span_json(span: &Span) -> JsonValue {
+    json!({
+        "start": { "line": span.start.line, "column": span.start.column, "offset": span.start.offset },
+        "end": { "line": span.end.line, "column": span.end.column, "offset": span.end.offset },
+    })
+}
+
+fn to_json(reports: &[FileReport]) -> JsonValue {
+    let mut diagnostics = Vec::new();
+    for report in reports {
+        for diagnostic in &report.diagnostics {
+            let primary = primary_annotation(diagnostic);
+            let labels: Vec<JsonValue> = diagnostic
+                .annotations
+                .iter()
+                .map(|annotation| {
+                    json!({
+                        "span": span_json(&annotation.span),
+                        "message": annotation.message,
+                        "primary": primary.is_some_and(|p| std::ptr::eq(p, annotation)),
+                    })
+                })
+                .collect();
+            diagnostics.push(json!({
+                "file": report.path,
+                "severity": severity_name(diagnostic.severity),
+                "code": diagnostic.code,
+                "message": diagnostic.message,
+                "span": primary.map(|annotation| span_json(&annotation.span)),
+                "labels": labels,
+                "notes": diagnostic.notes,
+                "help": diagnostic.help,
+            }));
+        }
+    }
+    let count = |severity: Severity| {
+        reports.iter().flat_map(|r| &r.diagnostics).filter(|d| d.severity == severity).count()
+    };
+    json!({
+        "files": reports.iter().map(|r| r.path.clone()).collect::<Vec<_>>(),
+        "errors": count(Severity::Error),
+        "warnings": count(Severity::Warning),
+        "diagnostics": diagnostics,
+    })
+}
+
+fn sarif_level(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Info | Severity::Hint => "note",
+    }
+}
+
+fn sarif_location(path: &str, span: &Span, message: Option<&String>) -> JsonValue {
+    let mut location = json!({
+        "physicalLocation": {
+            "artifactLocation": { "uri": path.replace('\\', "/") },
+            "region": {
+                "startLine": span.start.line,
+                "startColumn": span.start.column,
+                "endLine": span.end.line,
+                "endColumn": span.end.column,
+            },
+        },
+    });
+    if let Some(message) = message {
+        location["message"] = json!({ "text": message });
+    }
+    location
+}
+
+fn to_sarif(reports: &[FileReport]) -> JsonValue {
+    let mut rules: Vec<String> = Vec::new();
+    let mut results = Vec::new();
+    for report in reports {
+        for diagnostic in &report.diagnostics {
+            let primary = primary_annotation(diagnostic);
+            let mut text = diagnostic.message.clone();
+            for note in &diagnostic.notes {
+                text.push_str(&format!("\nnota: {}", note));
+            }
+            if let Some(help) = &diagnostic.help {
+                text.push_str(&format!("\najuda: {}", help));
+            }
+            let location = match primary {
+                Some(annotation) => sarif_location(&report.path, &annotation.span, None),
+                None => json!({ "physicalLocation": { "artifactLocation": { "uri": report.path.replace('\\', "/") } } }),
+            };
+            let related: Vec<JsonValue> = diagnostic
+                .annotations
+                .iter()
+                .filter(|annotation| !primary.is_some_and(|p| std::ptr::eq(p, *annotation)))
+                .map(|annotation| sarif_location(&report.path, &annotation.span, annotation.message.as_ref()))
+                .collect();
+
+            let mut result = json!({
+                "level": sarif_level(diagnostic.severity),
+                "message": { "text": text },
+                "locations": [location],
+            });
+            if let Some(code) = &diagnostic.code {
+                let index = rules.iter().position(|rule| rule == code).unwrap_or_else(|| {
+                    rules.push(code.clone());
+                    rules.len() - 1
+                });
+                result["ruleId"] = json!(code);
+                result["ruleIndex"] = json!(index);
+            }
+            if !related.is_empty() {
+                result["relatedLocations"] = json!(related);
+            }
+            results.push(result);
+        }
+    }
+    json!({
+        "$schema": SARIF_SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "snask",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules.iter().map(|id| json!({ "id": id })).collect::<Vec<_>>(),
+                },
+            },
+            "results": results,
+        }],
+    })
+}
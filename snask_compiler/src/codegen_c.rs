@@ -0,0 +1,814 @@
+use crate::ast::{
+    BinaryOp, ConditionalStmt, Expr, ExprKind, FuncDecl, LiteralValue, LoopStmt, Program, Stmt,
+    StmtKind, UnaryOp,
+};
+use crate::modules;
+use crate::semantic_analyzer::SemanticAnalyzer;
+use crate::span::Span;
+use crate::types::Type;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// Cabeçalho do runtime C, gravado ao lado do código gerado
+pub const RUNTIME_HEADER: &str = include_str!("../runtime/c/snask_runtime.h");
+/// Implementação do runtime C, compilada junto com o código gerado
+pub const RUNTIME_SOURCE: &str = include_str!("../runtime/c/snask_runtime.c");
+
+/// Representação C de um valor. Números e booleanos com tipo estático
+/// conhecido são gerados sem boxing; o resto passa por `SnValue`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CType {
+    Num,
+    Bool,
+    Value,
+}
+
+impl CType {
+    fn from_type(t: &Type) -> CType {
+        match t {
+            Type::Int | Type::Float => CType::Num,
+            Type::Bool => CType::Bool,
+            _ => CType::Value,
+        }
+    }
+
+    fn c_name(&self) -> &'static str {
+        match self {
+            CType::Num => "double",
+            CType::Bool => "int",
+            CType::Value => "SnValue",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct CVar {
+    c_name: String,
+    ctype: CType,
+}
+
+#[derive(Debug, Clone)]
+struct CFunction {
+    c_name: String,
+    wrapper_name: String,
+    params: Vec<CType>,
+    return_type: CType,
+}
+
+/// Uma chamada de função da stdlib que o runtime C implementa
+struct Builtin {
+    c_name: &'static str,
+    params: &'static [CType],
+    return_type: CType,
+    /// `true` quando o número de argumentos é passado como primeiro parâmetro (ex: `range`)
+    pass_argc: bool,
+}
+
+fn builtin(module: &str, member: &str) -> Option<Builtin> {
+    use CType::{Bool, Num, Value};
+    let (c_name, params, return_type, pass_argc): (&'static str, &'static [CType], CType, bool) = match (module, member) {
+        ("math", "abs") => ("fabs", &[Num], Num, false),
+        ("math", "floor") => ("floor", &[Num], Num, false),
+        ("math", "ceil") => ("ceil", &[Num], Num, false),
+        ("math", "round") => ("round", &[Num], Num, false),
+        ("math", "sqrt") => ("sn_sqrt", &[Num], Num, false),
+        ("math", "sin") => ("sin", &[Num], Num, false),
+        ("math", "cos") => ("cos", &[Num], Num, false),
+        ("math", "tan") => ("tan", &[Num], Num, false),
+        ("math", "pow") => ("pow", &[Num, Num], Num, false),
+        ("math", "mod") => ("sn_mod", &[Num, Num], Num, false),
+        ("math", "sign") => ("sn_sign", &[Num], Num, false),
+        ("math", "clamp") => ("sn_clamp", &[Num, Num, Num], Num, false),
+        ("string", "len") | ("collections", "len") => ("sn_len", &[Value], Num, false),
+        ("string", "upper") => ("sn_upper", &[Value], Value, false),
+        ("string", "lower") => ("sn_lower", &[Value], Value, false),
+        ("string", "trim") => ("sn_trim", &[Value], Value, false),
+        ("string", "contains") => ("sn_contains", &[Value, Value], Bool, false),
+        ("string", "starts_with") => ("sn_starts_with", &[Value, Value], Bool, false),
+        ("string", "ends_with") => ("sn_ends_with", &[Value, Value], Bool, false),
+        ("collections", "range") => ("sn_range", &[Num, Num, Num], Value, true),
+        ("collections", "push") => ("sn_push", &[Value, Value], Value, false),
+        ("collections", "get") => ("sn_get", &[Value, Value], Value, false),
+        ("collections", "reverse") => ("sn_reverse", &[Value], Value, false),
+        _ => return None,
+    };
+    Some(Builtin { c_name, params, return_type, pass_argc })
+}
+
+fn module_constant(module: &str, member: &str) -> Option<f64> {
+    match (module, member) {
+        ("math", "PI") => Some(std::f64::consts::PI),
+        ("math", "E") => Some(std::f64::consts::E),
+        ("math", "TAU") => Some(std::f64::consts::TAU),
+        _ => None,
+    }
+}
+
+const NATIVE_MODULES: &[&str] = &["math", "string", "collections"];
+
+/// Tradutor de um programa Snask para C
+pub struct CCodegen {
+    functions: HashMap<String, CFunction>,
+    /// Tipos que a análise semântica resolveu ou inferiu para cada declaração
+    /// (variáveis pelo trecho do statement, funções pelo trecho do nome)
+    declared_types: HashMap<Span, Type>,
+    scopes: Vec<HashMap<String, CVar>>,
+    global_decls: Vec<String>,
+    current_return: Option<CType>,
+    indent: usize,
+    tmp_counter: usize,
+}
+
+/// Traduz um programa (já analisado semanticamente) para um arquivo C completo
+pub fn generate(program: &Program) -> Result<String, String> {
+    let mut codegen = CCodegen::new();
+    codegen.generate_program(program)
+}
+
+impl CCodegen {
+    pub fn new() -> Self {
+        CCodegen {
+            functions: HashMap::new(),
+            declared_types: HashMap::new(),
+            scopes: vec![HashMap::new()],
+            global_decls: Vec::new(),
+            current_return: None,
+            indent: 0,
+            tmp_counter: 0,
+        }
+    }
+
+    fn generate_program(&mut self, program: &Program) -> Result<String, String> {
+        let mut statements = Vec::new();
+        flatten_imports(program, &mut statements, &mut Vec::new())?;
+        self.declared_types = infer_types(&mut statements);
+
+        // 1. Assinaturas de todas as funções de nível superior
+        for stmt in &statements {
+            if let StmtKind::FuncDeclaration(func) = &stmt.kind {
+                self.declare_function(func);
+            }
+        }
+
+        // 2. Variáveis globais, na ordem em que são declaradas
+        for stmt in &statements {
+            if let Some((name, var_type, value)) = declaration_parts(&stmt.kind) {
+                let ctype = match var_type {
+                    Some(t) => CType::from_type(t),
+                    None => self.inferred_ctype(stmt.span).unwrap_or(self.gen_expr(value)?.1),
+                };
+                self.declare_global(name, ctype);
+            } else if let StmtKind::Input { name, var_type, .. } = &stmt.kind {
+                self.declare_global(name, input_ctype(var_type, stmt)?);
+            }
+        }
+
+        // 3. Corpos das funções
+        let mut functions_code = String::new();
+        for stmt in &statements {
+            if let StmtKind::FuncDeclaration(func) = &stmt.kind {
+                functions_code.push_str(&self.gen_function(func)?);
+            }
+        }
+
+        // 4. Statements de nível superior dentro de `main`
+        self.indent = 1;
+        let mut main_code = String::new();
+        for stmt in &statements {
+            if !matches!(stmt.kind, StmtKind::FuncDeclaration(_)) {
+                main_code.push_str(&self.gen_statement(stmt)?);
+            }
+        }
+
+        let mut out = String::new();
+        out.push_str("/* Gerado por `snask compile --emit c`. Não edite manualmente. */\n");
+        out.push_str("#include <math.h>\n#include <stddef.h>\n#include \"snask_runtime.h\"\n\n");
+
+        for decl in &self.global_decls {
+            out.push_str(decl);
+            out.push('\n');
+        }
+        if !self.global_decls.is_empty() {
+            out.push('\n');
+        }
+
+        let mut prototypes: Vec<&CFunction> = self.functions.values().collect();
+        prototypes.sort_by(|a, b| a.c_name.cmp(&b.c_name));
+        for func in &prototypes {
+            let params: Vec<&str> = func.params.iter().map(|p| p.c_name()).collect();
+            let params = if params.is_empty() { "void".to_string() } else { params.join(", ") };
+            let _ = writeln!(out, "static {} {}({});", func.return_type.c_name(), func.c_name, params);
+            let _ = writeln!(out, "SnValue {}(int argc, SnValue *argv);", func.wrapper_name);
+        }
+        if !prototypes.is_empty() {
+            out.push('\n');
+        }
+
+        out.push_str(&functions_code);
+        out.push_str("int main(int argc, char **argv) {\n    sn_init(argc, argv);\n");
+        out.push_str(&main_code);
+        out.push_str("    return 0;\n}\n");
+        Ok(out)
+    }
+
+    fn declare_function(&mut self, func: &FuncDecl) {
+        let c_name = format!("fn_{}", mangle(&func.name));
+        let signature = CFunction {
+            wrapper_name: format!("fnw_{}", mangle(&func.name)),
+            c_name,
+            params: func.params.iter().map(|p| CType::from_type(&p.param_type)).collect(),
+            return_type: match &func.return_type {
+                Some(t) => CType::from_type(t),
+                None => match self.declared_types.get(&func.name_span) {
+                    Some(Type::Function(_, return_type)) => CType::from_type(return_type),
+                    _ => CType::Value,
+                },
+            },
+        };
+        self.functions.insert(func.name.clone(), signature);
+    }
+
+    /// Representação sem boxing do tipo que a análise semântica provou para uma
+    /// declaração sem anotação
+    fn inferred_ctype(&self, span: Span) -> Option<CType> {
+        self.declared_types.get(&span).map(CType::from_type).filter(|ctype| *ctype != CType::Value)
+    }
+
+    fn declare_global(&mut self, name: &str, ctype: CType) {
+        let c_name = format!("g_{}", mangle(name));
+        let init = match ctype {
+            CType::Value => "",
+            _ => " = 0",
+        };
+        self.global_decls.push(format!("static {} {}{};", ctype.c_name(), c_name, init));
+        self.scopes[0].insert(name.to_string(), CVar { c_name, ctype });
+    }
+
+    fn lookup(&self, name: &str) -> Option<&CVar> {
+        self.scopes.iter().rev().find_map(|scope| scope.get(name))
+    }
+
+    fn is_shadowed(&self, name: &str) -> bool {
+        self.lookup(name).is_some()
+    }
+
+    fn fresh(&mut self, prefix: &str) -> String {
+        self.tmp_counter += 1;
+        format!("_{}{}", prefix, self.tmp_counter)
+    }
+
+    fn line(&self, code: &str) -> String {
+        format!("{}{}\n", "    ".repeat(self.indent), code)
+    }
+
+    fn gen_function(&mut self, func: &FuncDecl) -> Result<String, String> {
+        let signature = self.functions[&func.name].clone();
+        let saved_indent = self.indent;
+        let saved_return = self.current_return.replace(signature.return_type);
+
+        self.scopes.push(HashMap::new());
+        let mut params = Vec::new();
+        for (param, ctype) in func.params.iter().zip(&signature.params) {
+            let c_name = format!("v_{}", mangle(&param.name));
+            params.push(format!("{} {}", ctype.c_name(), c_name));
+            self.scopes.last_mut().unwrap().insert(param.name.clone(), CVar { c_name, ctype: *ctype });
+        }
+        let params_code = if params.is_empty() { "void".to_string() } else { params.join(", ") };
+
+        let mut out = format!(
+            "static {} {}({}) {{\n",
+            signature.return_type.c_name(),
+            signature.c_name,
+            params_code
+        );
+        self.indent = 1;
+        for stmt in &func.body {
+            out.push_str(&self.gen_statement(stmt)?);
+        }
+        match signature.return_type {
+            CType::Value => out.push_str("    return sn_nil();\n"),
+            _ => {
+                let _ = writeln!(
+                    out,
+                    "    sn_panic(\"a função '{}' terminou sem retornar um valor\");\n    return 0;",
+                    c_escape(&func.name)
+                );
+            }
+        }
+        out.push_str("}\n\n");
+        self.scopes.pop();
+
+        // Wrapper com a convenção de chamada dinâmica, usado quando a função é passada como valor
+        let _ = writeln!(out, "SnValue {}(int argc, SnValue *argv) {{", signature.wrapper_name);
+        out.push_str("    (void)argc;\n    (void)argv;\n");
+        let args: Vec<String> = signature
+            .params
+            .iter()
+            .enumerate()
+            .map(|(i, ctype)| coerce(&format!("argv[{}]", i), CType::Value, *ctype))
+            .collect();
+        let call = format!("{}({})", signature.c_name, args.join(", "));
+        let _ = writeln!(out, "    return {};", coerce(&call, signature.return_type, CType::Value));
+        out.push_str("}\n\n");
+
+        self.indent = saved_indent;
+        self.current_return = saved_return;
+        Ok(out)
+    }
+
+    fn gen_block(&mut self, statements: &[Stmt]) -> Result<String, String> {
+        self.scopes.push(HashMap::new());
+        self.indent += 1;
+        let mut out = String::new();
+        for stmt in statements {
+            match self.gen_statement(stmt) {
+                Ok(code) => out.push_str(&code),
+                Err(e) => {
+                    self.indent -= 1;
+                    self.scopes.pop();
+                    return Err(e);
+                }
+            }
+        }
+        self.indent -= 1;
+        self.scopes.pop();
+        Ok(out)
+    }
+
+    fn gen_statement(&mut self, stmt: &Stmt) -> Result<String, String> {
+        if let Some((name, var_type, value)) = declaration_parts(&stmt.kind) {
+            let (code, expr_type) = self.gen_expr(value)?;
+            let ctype = match var_type {
+                Some(t) => CType::from_type(t),
+                None => self.inferred_ctype(stmt.span).unwrap_or(expr_type),
+            };
+            return Ok(self.gen_declaration(name, ctype, &code, expr_type));
+        }
+
+        match &stmt.kind {
+            StmtKind::VarAssignment(set) => {
+                let var = self
+                    .lookup(&set.name)
+                    .cloned()
+                    .ok_or_else(|| unsupported(stmt, &format!("variável '{}' não encontrada", set.name)))?;
+                let (code, expr_type) = self.gen_expr(&set.value)?;
+                Ok(self.line(&format!("{} = {};", var.c_name, coerce(&code, expr_type, var.ctype))))
+            }
+            StmtKind::Print(exprs) => {
+                let mut args = Vec::new();
+                for expr in exprs {
+                    let (code, ctype) = self.gen_expr(expr)?;
+                    args.push(coerce(&code, ctype, CType::Value));
+                }
+                let mut call = format!("sn_print({}", args.len());
+                for arg in args {
+                    call.push_str(", ");
+                    call.push_str(&arg);
+                }
+                call.push_str(");");
+                Ok(self.line(&call))
+            }
+            StmtKind::Input { name, var_type, .. } => {
+                let ctype = input_ctype(var_type, stmt)?;
+                let read = match var_type {
+                    Type::Int => "sn_input_num(1)",
+                    Type::Float => "sn_input_num(0)",
+                    Type::Bool => "sn_input_bool()",
+                    _ => "sn_input_str()",
+                };
+                Ok(self.gen_declaration(name, ctype, read, ctype))
+            }
+            StmtKind::Expression(expr) | StmtKind::FuncCall(expr) => {
+                let (code, _) = self.gen_expr(expr)?;
+                Ok(self.line(&format!("(void)({});", code)))
+            }
+            StmtKind::Return(expr) => {
+                let return_type = self
+                    .current_return
+                    .ok_or_else(|| unsupported(stmt, "'return' fora de uma função"))?;
+                let (code, ctype) = self.gen_expr(expr)?;
+                Ok(self.line(&format!("return {};", coerce(&code, ctype, return_type))))
+            }
+            StmtKind::Conditional(cond) => self.gen_conditional(cond),
+            StmtKind::Loop(LoopStmt::While { condition, body }) => {
+                let (code, ctype) = self.gen_expr(condition)?;
+                let mut out = self.line(&format!("while ({}) {{", coerce(&code, ctype, CType::Bool)));
+                out.push_str(&self.gen_block(body)?);
+                out.push_str(&self.line("}"));
+                Ok(out)
+            }
+            StmtKind::Loop(LoopStmt::For { iterator, iterable, body }) => {
+                let (code, ctype) = self.gen_expr(iterable)?;
+                let items = self.fresh("items");
+                let len = self.fresh("len");
+                let index = self.fresh("i");
+                let mut out = self.line("{");
+                self.indent += 1;
+                out.push_str(&self.line(&format!("SnValue {} = {};", items, coerce(&code, ctype, CType::Value))));
+                out.push_str(&self.line(&format!("size_t {} = sn_iter_len({});", len, items)));
+                out.push_str(&self.line(&format!("for (size_t {i} = 0; {i} < {}; {i}++) {{", len, i = index)));
+                self.scopes.push(HashMap::new());
+                let c_name = format!("v_{}", mangle(iterator));
+                self.scopes.last_mut().unwrap().insert(iterator.clone(), CVar { c_name: c_name.clone(), ctype: CType::Value });
+                self.indent += 1;
+                out.push_str(&self.line(&format!("SnValue {} = sn_iter_get({}, {});", c_name, items, index)));
+                self.indent -= 1;
+                let body_code = self.gen_block(body);
+                self.scopes.pop();
+                out.push_str(&body_code?);
+                out.push_str(&self.line("}"));
+                self.indent -= 1;
+                out.push_str(&self.line("}"));
+                Ok(out)
+            }
+            StmtKind::FuncDeclaration(func) => Err(unsupported(
+                stmt,
+                &format!("função '{}' declarada dentro de outro bloco", func.name),
+            )),
+            StmtKind::Import(path) if modules::is_native_module(path) => Ok(String::new()),
+            StmtKind::Import(path) => Err(unsupported(stmt, &format!("'import \"{}\"' fora do nível superior", path))),
+            StmtKind::ListPush(_) | StmtKind::DictSet(_) => Err(unsupported(stmt, "mutação de coleções")),
+            // Aliases de tipo só existem na análise; no C esses valores são `SnValue`
+            StmtKind::TypeAlias { .. } => Ok(String::new()),
+            // Testes só rodam com `snask test`; o executável não os inclui
+            StmtKind::Test { .. } => Ok(String::new()),
+            _ => unreachable!("declarações são tratadas acima"),
+        }
+    }
+
+    fn gen_declaration(&mut self, name: &str, ctype: CType, code: &str, expr_type: CType) -> String {
+        let value = coerce(code, expr_type, ctype);
+        if self.scopes.len() == 1 {
+            // Nível superior: a variável global já foi declarada no pré-passo
+            let c_name = self.scopes[0][name].c_name.clone();
+            self.line(&format!("{} = {};", c_name, value))
+        } else {
+            let c_name = format!("v_{}", mangle(name));
+            self.scopes.last_mut().unwrap().insert(name.to_string(), CVar { c_name: c_name.clone(), ctype });
+            self.line(&format!("{} {} = {};", ctype.c_name(), c_name, value))
+        }
+    }
+
+    fn gen_conditional(&mut self, cond: &ConditionalStmt) -> Result<String, String> {
+        let (code, ctype) = self.gen_expr(&cond.if_block.condition)?;
+        let mut out = self.line(&format!("if ({}) {{", coerce(&code, ctype, CType::Bool)));
+        out.push_str(&self.gen_block(&cond.if_block.body)?);
+        for elif in &cond.elif_blocks {
+            let (code, ctype) = self.gen_expr(&elif.condition)?;
+            out.push_str(&self.line(&format!("}} else if ({}) {{", coerce(&code, ctype, CType::Bool))));
+            out.push_str(&self.gen_block(&elif.body)?);
+        }
+        if let Some(else_body) = &cond.else_block {
+            out.push_str(&self.line("} else {"));
+            out.push_str(&self.gen_block(else_body)?);
+        }
+        out.push_str(&self.line("}"));
+        Ok(out)
+    }
+
+    fn gen_expr(&mut self, expr: &Expr) -> Result<(String, CType), String> {
+        match &expr.kind {
+            ExprKind::Literal(lit) => self.gen_literal(lit),
+            ExprKind::Variable(name) => {
+                if let Some(var) = self.lookup(name) {
+                    return Ok((var.c_name.clone(), var.ctype));
+                }
+                if let Some(func) = self.functions.get(name) {
+                    return Ok((
+                        format!("sn_func(\"{}\", {}, {})", c_escape(name), func.params.len(), func.wrapper_name),
+                        CType::Value,
+                    ));
+                }
+                Err(unsupported_expr(expr, &format!("uso de '{}' como valor", name)))
+            }
+            ExprKind::Unary { op: UnaryOp::Negative, expr: inner } => {
+                let (code, ctype) = self.gen_expr(inner)?;
+                match ctype {
+                    CType::Num => Ok((format!("(-{})", code), CType::Num)),
+                    _ => Ok((format!("sn_neg({})", coerce(&code, ctype, CType::Value)), CType::Value)),
+                }
+            }
+            ExprKind::Binary { op, left, right } => {
+                let (l, lt) = self.gen_expr(left)?;
+                let (r, rt) = self.gen_expr(right)?;
+                Ok(gen_binary(op, &l, lt, &r, rt))
+            }
+            ExprKind::FunctionCall { callee, args } => self.gen_call(expr, callee, args),
+            ExprKind::PropertyAccess { target, property } => {
+                if let ExprKind::Variable(module) = &target.kind {
+                    if NATIVE_MODULES.contains(&module.as_str()) && !self.is_shadowed(module) {
+                        return match module_constant(module, property) {
+                            Some(n) => Ok((format!("{:?}", n), CType::Num)),
+                            None => Err(unsupported_expr(expr, &format!("'{}.{}' como valor", module, property))),
+                        };
+                    }
+                }
+                let (code, ctype) = self.gen_expr(target)?;
+                Ok((
+                    format!("sn_property({}, \"{}\")", coerce(&code, ctype, CType::Value), c_escape(property)),
+                    CType::Value,
+                ))
+            }
+            ExprKind::IndexAccess { target, index } => {
+                let (t, tt) = self.gen_expr(target)?;
+                let (i, it) = self.gen_expr(index)?;
+                Ok((
+                    format!("sn_index({}, {})", coerce(&t, tt, CType::Value), coerce(&i, it, CType::Value)),
+                    CType::Value,
+                ))
+            }
+        }
+    }
+
+    fn gen_literal(&mut self, lit: &LiteralValue) -> Result<(String, CType), String> {
+        match lit {
+            LiteralValue::Number(n) => Ok((format!("{:?}", n), CType::Num)),
+            LiteralValue::Boolean(b) => Ok((if *b { "1" } else { "0" }.to_string(), CType::Bool)),
+            LiteralValue::Nil => Ok(("sn_nil()".to_string(), CType::Value)),
+            LiteralValue::String(s) => Ok((
+                format!("sn_str_len(\"{}\", {})", c_escape(s), s.len()),
+                CType::Value,
+            )),
+            LiteralValue::List(items) => {
+                let mut code = format!("sn_list({}", items.len());
+                for item in items {
+                    let (c, t) = self.gen_expr(item)?;
+                    code.push_str(", ");
+                    code.push_str(&coerce(&c, t, CType::Value));
+                }
+                code.push(')');
+                Ok((code, CType::Value))
+            }
+            LiteralValue::Dict(pairs) => {
+                let mut code = format!("sn_dict({}", pairs.len());
+                for (key, value) in pairs {
+                    let (k, kt) = self.gen_expr(key)?;
+                    let (v, vt) = self.gen_expr(value)?;
+                    let _ = write!(code, ", {}, {}", coerce(&k, kt, CType::Value), coerce(&v, vt, CType::Value));
+                }
+                code.push(')');
+                Ok((code, CType::Value))
+            }
+        }
+    }
+
+    fn gen_args(&mut self, args: &[Expr], params: &[CType]) -> Result<Vec<String>, String> {
+        let mut result = Vec::new();
+        for (arg, ctype) in args.iter().zip(params) {
+            let (code, arg_type) = self.gen_expr(arg)?;
+            result.push(coerce(&code, arg_type, *ctype));
+        }
+        Ok(result)
+    }
+
+    fn gen_boxed_args(&mut self, args: &[Expr]) -> Result<String, String> {
+        if args.is_empty() {
+            return Ok("0, NULL".to_string());
+        }
+        let boxed = self.gen_args(args, &vec![CType::Value; args.len()])?;
+        Ok(format!("{}, (SnValue[]){{{}}}", args.len(), boxed.join(", ")))
+    }
+
+    fn gen_call(&mut self, call: &Expr, callee: &Expr, args: &[Expr]) -> Result<(String, CType), String> {
+        match &callee.kind {
+            ExprKind::Variable(name) if !self.is_shadowed(name) => {
+                if let Some(func) = self.functions.get(name).cloned() {
+                    if args.len() != func.params.len() {
+                        return Err(unsupported_expr(
+                            call,
+                            &format!("número incorreto de argumentos para '{}': esperado {}, encontrado {}", name, func.params.len(), args.len()),
+                        ));
+                    }
+                    let args = self.gen_args(args, &func.params)?;
+                    return Ok((format!("{}({})", func.c_name, args.join(", ")), func.return_type));
+                }
+                if name == "format" {
+                    return Ok((format!("sn_format({})", self.gen_boxed_args(args)?), CType::Value));
+                }
+            }
+            ExprKind::PropertyAccess { target, property } => {
+                if let ExprKind::Variable(module) = &target.kind {
+                    if NATIVE_MODULES.contains(&module.as_str()) && !self.is_shadowed(module) {
+                        let b = builtin(module, property).ok_or_else(|| {
+                            unsupported_expr(call, &format!("'{}.{}' não está disponível no runtime C", module, property))
+                        })?;
+                        let valid_arity = if b.pass_argc {
+                            !args.is_empty() && args.len() <= b.params.len()
+                        } else {
+                            args.len() == b.params.len()
+                        };
+                        if !valid_arity {
+                            return Err(unsupported_expr(call, &format!("número incorreto de argumentos para '{}.{}'", module, property)));
+                        }
+                        let mut c_args = self.gen_args(args, b.params)?;
+                        if b.pass_argc {
+                            while c_args.len() < b.params.len() {
+                                c_args.push("0".to_string());
+                            }
+                            c_args.insert(0, args.len().to_string());
+                        }
+                        return Ok((format!("{}({})", b.c_name, c_args.join(", ")), b.return_type));
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        let (callee_code, callee_type) = self.gen_expr(callee)?;
+        let args = self.gen_boxed_args(args)?;
+        Ok((
+            format!("sn_call({}, {})", coerce(&callee_code, callee_type, CType::Value), args),
+            CType::Value,
+        ))
+    }
+}
+
+impl Default for CCodegen {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn gen_binary(op: &BinaryOp, l: &str, lt: CType, r: &str, rt: CType) -> (String, CType) {
+    let both_num = lt == CType::Num && rt == CType::Num;
+    let boxed = |code: &str, t: CType| coerce(code, t, CType::Value);
+    match op {
+        BinaryOp::Add if both_num => (format!("({} + {})", l, r), CType::Num),
+        BinaryOp::Subtract if both_num => (format!("({} - {})", l, r), CType::Num),
+        BinaryOp::Multiply if both_num => (format!("({} * {})", l, r), CType::Num),
+        BinaryOp::Divide if both_num => (format!("sn_div_num({}, {})", l, r), CType::Num),
+        BinaryOp::Add => (format!("sn_add({}, {})", boxed(l, lt), boxed(r, rt)), CType::Value),
+        BinaryOp::Subtract => (format!("sn_sub({}, {})", boxed(l, lt), boxed(r, rt)), CType::Value),
+        BinaryOp::Multiply => (format!("sn_mul({}, {})", boxed(l, lt), boxed(r, rt)), CType::Value),
+        BinaryOp::Divide => (format!("sn_div({}, {})", boxed(l, lt), boxed(r, rt)), CType::Value),
+        BinaryOp::Equals | BinaryOp::NotEquals => {
+            let negate = if *op == BinaryOp::NotEquals { "!" } else { "" };
+            if lt == rt && lt != CType::Value {
+                (format!("{}({} == {})", negate, l, r), CType::Bool)
+            } else {
+                (format!("{}sn_equals({}, {})", negate, boxed(l, lt), boxed(r, rt)), CType::Bool)
+            }
+        }
+        BinaryOp::LessThan | BinaryOp::GreaterThan | BinaryOp::LessThanOrEquals | BinaryOp::GreaterThanOrEquals => {
+            let (c_op, func) = match op {
+                BinaryOp::LessThan => ("<", "sn_lt"),
+                BinaryOp::GreaterThan => (">", "sn_gt"),
+                BinaryOp::LessThanOrEquals => ("<=", "sn_le"),
+                _ => (">=", "sn_ge"),
+            };
+            if both_num {
+                (format!("({} {} {})", l, c_op, r), CType::Bool)
+            } else {
+                (format!("{}({}, {})", func, boxed(l, lt), boxed(r, rt)), CType::Bool)
+            }
+        }
+    }
+}
+
+/// Converte código C de uma representação para outra
+fn coerce(code: &str, from: CType, to: CType) -> String {
+    match (from, to) {
+        (a, b) if a == b => code.to_string(),
+        (CType::Num, CType::Value) => format!("sn_num({})", code),
+        (CType::Bool, CType::Value) => format!("sn_bool({})", code),
+        (CType::Value, CType::Num) => format!("sn_as_num({})", code),
+        (CType::Value, CType::Bool) => format!("sn_as_bool({})", code),
+        // Número usado onde se espera booleano (ou vice-versa): passa pelo runtime
+        // para produzir o mesmo erro de tipo que o interpretador.
+        (from, to) => coerce(&coerce(code, from, CType::Value), CType::Value, to),
+    }
+}
+
+fn declaration_parts(kind: &StmtKind) -> Option<(&str, Option<&Type>, &Expr)> {
+    match kind {
+        StmtKind::VarDeclaration(d) => Some((&d.name, d.var_type.as_ref(), &d.value)),
+        StmtKind::MutDeclaration(d) => Some((&d.name, d.var_type.as_ref(), &d.value)),
+        StmtKind::ConstDeclaration(d) => Some((&d.name, d.var_type.as_ref(), &d.value)),
+        StmtKind::ListDeclaration(d) => Some((&d.name, d.var_type.as_ref(), &d.value)),
+        StmtKind::DictDeclaration(d) => Some((&d.name, d.var_type.as_ref(), &d.value)),
+        _ => None,
+    }
+}
+
+fn input_ctype(var_type: &Type, stmt: &Stmt) -> Result<CType, String> {
+    match var_type {
+        Type::Int | Type::Float | Type::Bool | Type::String => Ok(CType::from_type(var_type)),
+        _ => Err(unsupported(stmt, &format!("'input' do tipo {:?}", var_type))),
+    }
+}
+
+/// Roda a análise semântica sobre o programa já sem imports e devolve os tipos de
+/// cada declaração. Parâmetros sem anotação de funções que só são chamadas
+/// diretamente, e sempre com números, passam a ter o tipo numérico dos argumentos
+/// (o que também deixa o retorno inferido numérico); se isso criar um erro de
+/// tipo, a última rodada é descartada.
+fn infer_types(statements: &mut Vec<Stmt>) -> HashMap<Span, Type> {
+    let mut analyzer = SemanticAnalyzer::new();
+    analyzer.analyze(statements);
+    loop {
+        let mut attempt = statements.clone();
+        let mut changed = false;
+        for stmt in attempt.iter_mut() {
+            let StmtKind::FuncDeclaration(func) = &mut stmt.kind else { continue };
+            let Some(calls) = analyzer.calls.get(&func.name_span) else { continue };
+            // Usada como valor: o wrapper dinâmico pode receber qualquer coisa
+            let uses = analyzer
+                .names
+                .iter()
+                .filter(|info| info.declared_at == Some(func.name_span) && info.span != func.name_span)
+                .count();
+            if uses != calls.len() {
+                continue;
+            }
+            for (i, param) in func.params.iter_mut().enumerate() {
+                if param.param_type != Type::Any || param.type_span.is_some() {
+                    continue;
+                }
+                let joined = calls
+                    .iter()
+                    .map(|args| args.get(i).cloned().unwrap_or(Type::Any))
+                    .reduce(|a, b| Type::join(&a, &b));
+                if let Some(numeric @ (Type::Int | Type::Float)) = joined {
+                    param.param_type = numeric;
+                    changed = true;
+                }
+            }
+        }
+        if !changed {
+            break;
+        }
+        let mut next = SemanticAnalyzer::new();
+        next.analyze(&attempt);
+        if !next.errors.is_empty() {
+            break;
+        }
+        *statements = attempt;
+        analyzer = next;
+    }
+    analyzer.names.into_iter().filter(|info| info.declared_at == Some(info.span)).map(|info| (info.span, info.symbol_type)).collect()
+}
+
+/// Substitui os `import` de arquivos `.snask` de nível superior pelo conteúdo dos módulos
+fn flatten_imports(program: &[Stmt], out: &mut Vec<Stmt>, seen: &mut Vec<String>) -> Result<(), String> {
+    for stmt in program {
+        match &stmt.kind {
+            StmtKind::Import(path) if !modules::is_native_module(path) => {
+                if seen.contains(path) {
+                    continue;
+                }
+                seen.push(path.clone());
+                let module = modules::load_module(path)?;
+                flatten_imports(&module, out, seen)?;
+            }
+            _ => out.push(stmt.clone()),
+        }
+    }
+    Ok(())
+}
+
+/// Nome C válido para um identificador Snask (que pode conter letras não-ASCII)
+fn mangle(name: &str) -> String {
+    let mut out = String::new();
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() || c == '_' {
+            out.push(c);
+        } else {
+            let _ = write!(out, "_u{:x}_", c as u32);
+        }
+    }
+    out
+}
+
+/// Escapa uma string para um literal C, byte a byte
+fn c_escape(s: &str) -> String {
+    let mut out = String::new();
+    for byte in s.bytes() {
+        match byte {
+            b'"' => out.push_str("\\\""),
+            b'\\' => out.push_str("\\\\"),
+            b'\n' => out.push_str("\\n"),
+            b'\r' => out.push_str("\\r"),
+            b'\t' => out.push_str("\\t"),
+            0x20..=0x7e => out.push(byte as char),
+            // Escapes octais têm tamanho fixo, então não "engolem" o caractere seguinte
+            _ => {
+                let _ = write!(out, "\\{:03o}", byte);
+            }
+        }
+    }
+    out
+}
+
+fn unsupported(stmt: &Stmt, what: &str) -> String {
+    format!(
+        "Backend C: {} não é suportado (linha {}, coluna {})",
+        what, stmt.span.start.line, stmt.span.start.column
+    )
+}
+
+fn unsupported_expr(expr: &Expr, what: &str) -> String {
+    format!(
+        "Backend C: {} não é suportado (linha {}, coluna {})",
+        what, expr.span.start.line, expr.span.start.column
+    )
+}
@@ -0,0 +1,244 @@
+//! `snask run --coverage`: quais statements foram executados, por linha, no
+//! programa e nos módulos `.snask` que ele importa. O resultado sai como um
+//! resumo por arquivo no terminal e, opcionalmente, em formato lcov.
+//!
+//! Em `snask test --coverage` entram só os módulos importados pelos arquivos
+//! de teste, não os testes em si.
+
+use crate::ast::{LoopStmt, Program, Stmt, StmtKind};
+use crate::interpreter::{Interpreter, Tracer};
+use crate::modules::{is_native_module, load_module, resolve_module_path};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+
+#[derive(Debug, Clone, Default)]
+pub struct CoverageOptions {
+    /// Arquivo onde gravar o relatório lcov
+    pub lcov: Option<String>,
+    /// Porcentagem mínima de linhas cobertas; abaixo dela `check` falha
+    pub fail_under: Option<f64>,
+}
+
+/// Linhas executáveis e funções de um arquivo
+#[derive(Debug, Default)]
+struct FileLines {
+    lines: BTreeSet<usize>,
+    /// Nome e linha de cada função declarada
+    functions: Vec<(String, usize)>,
+}
+
+#[derive(Default)]
+pub struct Coverage {
+    options: CoverageOptions,
+    /// Arquivos como o interpretador os chama (`current_file`), em ordem
+    files: BTreeMap<String, FileLines>,
+    hits: HashMap<(String, usize), u64>,
+    function_hits: HashMap<(String, String), u64>,
+}
+
+impl Coverage {
+    /// Registra as linhas executáveis do programa e dos módulos que ele importa
+    pub fn new(file: &str, program: &Program, options: CoverageOptions) -> Self {
+        let mut coverage = Coverage::with_options(options);
+        coverage.add_file(file, program);
+        coverage
+    }
+
+    /// Cobertura ainda sem arquivos
+    pub fn with_options(options: CoverageOptions) -> Self {
+        Coverage { options, ..Default::default() }
+    }
+
+    /// Registra só os módulos `.snask` que `program` importa
+    pub fn add_imports(&mut self, program: &Program) {
+        let mut imports = Vec::new();
+        collect_lines(program, &mut FileLines::default(), &mut imports);
+        self.add_modules(imports);
+    }
+
+    fn add_file(&mut self, file: &str, program: &Program) {
+        if self.files.contains_key(file) {
+            return;
+        }
+        let mut lines = FileLines::default();
+        let mut imports = Vec::new();
+        collect_lines(program, &mut lines, &mut imports);
+        self.files.insert(file.to_string(), lines);
+        self.add_modules(imports);
+    }
+
+    fn add_modules(&mut self, imports: Vec<String>) {
+        for path in imports {
+            if !is_native_module(&path) {
+                if let Ok(module) = load_module(&path) {
+                    self.add_file(&path, &module);
+                }
+            }
+        }
+    }
+
+    fn file_hits(&self, file: &str, lines: &FileLines) -> (usize, Vec<usize>) {
+        let missed: Vec<usize> = lines
+            .lines
+            .iter()
+            .copied()
+            .filter(|&line| !self.hits.contains_key(&(file.to_string(), line)))
+            .collect();
+        (lines.lines.len() - missed.len(), missed)
+    }
+
+    /// Porcentagem de linhas cobertas em todos os arquivos
+    pub fn percent(&self) -> f64 {
+        let (mut total, mut covered) = (0, 0);
+        for (file, lines) in &self.files {
+            total += lines.lines.len();
+            covered += self.file_hits(file, lines).0;
+        }
+        percent(covered, total)
+    }
+
+    /// Tabela por arquivo com as linhas que não foram executadas
+    pub fn summary(&self) -> String {
+        let width = self.files.keys().map(|file| file.chars().count()).max().unwrap_or(0).max(7);
+        let mut out = format!("Cobertura de linhas\n\n{:<width$}  {:>7}  {:>8}  {:>7}  não executadas\n", "arquivo", "linhas", "cobertas", "%", width = width);
+        let (mut total, mut covered) = (0, 0);
+        for (file, lines) in &self.files {
+            let (hit, missed) = self.file_hits(file, lines);
+            total += lines.lines.len();
+            covered += hit;
+            out.push_str(&format!(
+                "{:<width$}  {:>7}  {:>8}  {:>6.1}%  {}\n",
+                file,
+                lines.lines.len(),
+                hit,
+                percent(hit, lines.lines.len()),
+                line_ranges(&missed),
+                width = width
+            ));
+        }
+        out.push_str(&format!(
+            "{:<width$}  {:>7}  {:>8}  {:>6.1}%\n",
+            "total",
+            total,
+            covered,
+            percent(covered, total),
+            width = width
+        ));
+        out
+    }
+
+    /// Relatório no formato lcov (`genhtml`, Codecov, ...)
+    pub fn lcov(&self) -> String {
+        let mut out = String::new();
+        for (file, lines) in &self.files {
+            out.push_str("TN:\n");
+            out.push_str(&format!("SF:{}\n", source_path(file)));
+            for (name, line) in &lines.functions {
+                out.push_str(&format!("FN:{},{}\n", line, name));
+            }
+            let mut functions_hit = 0;
+            for (name, _) in &lines.functions {
+                let count = self.function_hits.get(&(file.clone(), name.clone())).copied().unwrap_or(0);
+                functions_hit += usize::from(count > 0);
+                out.push_str(&format!("FNDA:{},{}\n", count, name));
+            }
+            out.push_str(&format!("FNF:{}\nFNH:{}\n", lines.functions.len(), functions_hit));
+            for &line in &lines.lines {
+                let count = self.hits.get(&(file.clone(), line)).copied().unwrap_or(0);
+                out.push_str(&format!("DA:{},{}\n", line, count));
+            }
+            let (hit, _) = self.file_hits(file, lines);
+            out.push_str(&format!("LF:{}\nLH:{}\nend_of_record\n", lines.lines.len(), hit));
+        }
+        out
+    }
+
+    /// Escreve o resumo em stderr, grava o lcov e verifica o mínimo exigido
+    pub fn write_report(&self) -> Result<(), String> {
+        eprint!("\n{}", self.summary());
+        if let Some(path) = &self.options.lcov {
+            fs::write(path, self.lcov()).map_err(|e| format!("Não foi possível gravar {}: {}", path, e))?;
+            eprintln!("\nRelatório lcov gravado em {}", path);
+        }
+        match self.options.fail_under {
+            Some(minimum) if self.percent() < minimum => Err(format!(
+                "A cobertura de linhas ({:.1}%) está abaixo do mínimo exigido ({:.1}%)",
+                self.percent(),
+                minimum
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Tracer for Coverage {
+    fn statement(&mut self, interpreter: &mut Interpreter, statement: &Stmt) {
+        let key = (interpreter.current_file().to_string(), statement.span.start.line);
+        *self.hits.entry(key).or_default() += 1;
+    }
+
+    fn enter_function(&mut self, interpreter: &mut Interpreter, function: &crate::ast::FuncDecl) {
+        let key = (interpreter.current_file().to_string(), function.name.clone());
+        *self.function_hits.entry(key).or_default() += 1;
+    }
+}
+
+/// Linhas dos statements que executam algo (declarar funções e tipos não conta)
+fn collect_lines(block: &[Stmt], lines: &mut FileLines, imports: &mut Vec<String>) {
+    for stmt in block {
+        match &stmt.kind {
+            StmtKind::FuncDeclaration(decl) => {
+                lines.functions.push((decl.name.clone(), decl.name_span.start.line));
+                collect_lines(&decl.body, lines, imports);
+                continue;
+            }
+            StmtKind::TypeAlias { .. } => continue,
+            StmtKind::Import(path) => imports.push(path.clone()),
+            StmtKind::Conditional(conditional) => {
+                collect_lines(&conditional.if_block.body, lines, imports);
+                for block in &conditional.elif_blocks {
+                    collect_lines(&block.body, lines, imports);
+                }
+                if let Some(body) = &conditional.else_block {
+                    collect_lines(body, lines, imports);
+                }
+            }
+            StmtKind::Loop(LoopStmt::While { body, .. }) | StmtKind::Loop(LoopStmt::For { body, .. }) => {
+                collect_lines(body, lines, imports)
+            }
+            StmtKind::Test { body, .. } => collect_lines(body, lines, imports),
+            _ => {}
+        }
+        lines.lines.insert(stmt.span.start.line);
+    }
+}
+
+fn percent(covered: usize, total: usize) -> f64 {
+    if total == 0 {
+        100.0
+    } else {
+        covered as f64 / total as f64 * 100.0
+    }
+}
+
+/// `[3, 4, 5, 9]` vira `3-5, 9`
+fn line_ranges(lines: &[usize]) -> String {
+    let mut ranges: Vec<String> = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let start = lines[i];
+        while i + 1 < lines.len() && lines[i + 1] == lines[i] + 1 {
+            i += 1;
+        }
+        ranges.push(if lines[i] == start { start.to_string() } else { format!("{}-{}", start, lines[i]) });
+        i += 1;
+    }
+    ranges.join(", ")
+}
+
+/// Caminho absoluto do arquivo, para as ferramentas que leem o lcov
+fn source_path(file: &str) -> String {
+    let path = if fs::metadata(file).is_ok() { Ok(file.into()) } else { resolve_module_path(file) };
+    path.and_then(|path| fs::canonicalize(path).map_err(|e| e.to_string()))
+        .map_or_else(|_| file.to_string(), |path| path.to_string_lossy().into_owned())
+}
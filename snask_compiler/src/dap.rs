@@ -0,0 +1,347 @@
+//! `snask debug --dap`: o depurador exposto pelo Debug Adapter Protocol em
+//! stdin/stdout, para editores como o VS Code.
+//!
+//! Uma thread lê as mensagens do editor e as entrega por um canal. Até o
+//! `configurationDone` elas são atendidas aqui; depois o programa roda com o
+//! mesmo `Debugger` de `snask debug`, e o `Dap` atende os pedidos entre os
+//! statements (breakpoints novos, pausa) e enquanto o programa está parado.
+
+use crate::debugger::{absolute_path, describe_value, Breakpoint, DebugState, Debugger, Frontend, Resume, Session, StopReason};
+use crate::interpreter::{InterpretResult, Interpreter};
+use serde_json::{json, Value as JsonValue};
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::{Arc, Mutex};
+
+/// O interpretador só tem uma thread
+const THREAD_ID: i64 = 1;
+
+/// Atende um editor até ele se desconectar
+pub fn run(max_call_depth: usize) -> Result<(), String> {
+    let (sender, requests) = mpsc::channel();
+    std::thread::spawn(move || {
+        let mut stdin = io::stdin().lock();
+        while let Some(message) = read_message(&mut stdin) {
+            if sender.send(message).is_err() {
+                break;
+            }
+        }
+    });
+
+    let dap = Dap { requests, output: Output::default() };
+    let mut breakpoints = Vec::new();
+    let mut launch: Option<(String, crate::ast::Program, bool)> = None;
+
+    // Configuração: o editor inicializa, lança o programa e envia os breakpoints
+    loop {
+        let Some(request) = dap.next_request() else { return Ok(()) };
+        let arguments = &request["arguments"];
+        match command(&request) {
+            "initialize" => {
+                dap.output.respond(
+                    &request,
+                    json!({
+                        "supportsConfigurationDoneRequest": true,
+                        "supportsEvaluateForHovers": true,
+                        "supportsTerminateRequest": true,
+                    }),
+                );
+                dap.output.event("initialized", json!({}));
+            }
+            "launch" => {
+                let Some(file) = arguments["program"].as_str() else {
+                    dap.output.fail(&request, "o campo 'program' é obrigatório");
+                    continue;
+                };
+                if let Some(cwd) = arguments["cwd"].as_str() {
+                    let _ = std::env::set_current_dir(cwd);
+                }
+                match crate::load_program(file) {
+                    Ok(program) => {
+                        let stop_on_entry = arguments["stopOnEntry"].as_bool().unwrap_or(false);
+                        launch = Some((file.to_string(), program, stop_on_entry));
+                        dap.output.respond(&request, JsonValue::Null);
+                    }
+                    Err(e) => dap.output.fail(&request, &e),
+                }
+            }
+            "setBreakpoints" => {
+                let response = set_breakpoints(&mut breakpoints, arguments);
+                dap.output.respond(&request, response);
+            }
+            "configurationDone" => {
+                dap.output.respond(&request, JsonValue::Null);
+                break;
+            }
+            "disconnect" | "terminate" => {
+                dap.output.respond(&request, JsonValue::Null);
+                return Ok(());
+            }
+            _ => dap.answer_anytime(&request),
+        }
+    }
+
+    let Some((file, program, stop_on_entry)) = launch else {
+        dap.output.event("terminated", json!({}));
+        return Err("O editor não enviou 'launch'".to_string());
+    };
+    let output = dap.output.clone();
+    let mut debugger = Debugger::new(&file, dap, stop_on_entry);
+    debugger.state.breakpoints = breakpoints;
+    let debugger = Arc::new(Mutex::new(debugger));
+
+    let mut interpreter = Interpreter::new();
+    interpreter.set_file(&file);
+    interpreter.set_max_call_depth(max_call_depth);
+    interpreter.add_tracer(debugger.clone());
+    let exit_code = match interpreter.interpret(program) {
+        InterpretResult::Ok => 0,
+        InterpretResult::RuntimeError(e) => {
+            output.event("output", json!({ "category": "stderr", "output": format!("{}\n", crate::render_runtime_error(&e)) }));
+            1
+        }
+    };
+    output.event("exited", json!({ "exitCode": exit_code }));
+    output.event("terminated", json!({}));
+
+    // O editor ainda pode mandar pedidos até se desconectar
+    let debugger = debugger.lock().unwrap_or_else(|e| e.into_inner());
+    while let Some(request) = debugger.frontend.next_request() {
+        match command(&request) {
+            "disconnect" | "terminate" => {
+                output.respond(&request, JsonValue::Null);
+                break;
+            }
+            _ => debugger.frontend.answer_anytime(&request),
+        }
+    }
+    Ok(())
+}
+
+fn command(request: &JsonValue) -> &str {
+    request["command"].as_str().unwrap_or("")
+}
+
+/// Substitui os breakpoints de um arquivo pelos do pedido
+fn set_breakpoints(breakpoints: &mut Vec<Breakpoint>, arguments: &JsonValue) -> JsonValue {
+    let path = arguments["source"]["path"].as_str().unwrap_or("").to_string();
+    breakpoints.retain(|breakpoint| breakpoint.file.as_deref() != Some(path.as_str()));
+    let lines: Vec<usize> = arguments["breakpoints"]
+        .as_array()
+        .map(|list| list.iter().filter_map(|b| b["line"].as_u64()).map(|line| line as usize).collect())
+        .unwrap_or_default();
+    for &line in &lines {
+        breakpoints.push(Breakpoint { file: Some(path.clone()), line });
+    }
+    json!({ "breakpoints": lines.iter().map(|line| json!({ "verified": true, "line": line })).collect::<Vec<_>>() })
+}
+
+/// Lê uma mensagem `Content-Length: n\r\n\r\n{...}`; `None` no fim da entrada
+fn read_message(reader: &mut impl BufRead) -> Option<JsonValue> {
+    let mut length = None;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header).ok()? == 0 {
+            return None;
+        }
+        let header = header.trim();
+        if header.is_empty() {
+            break;
+        }
+        if let Some(value) = header.strip_prefix("Content-Length:") {
+            length = value.trim().parse::<usize>().ok();
+        }
+    }
+    let mut body = vec![0; length?];
+    reader.read_exact(&mut body).ok()?;
+    serde_json::from_slice(&body).ok()
+}
+
+/// Escreve as mensagens para o editor, numerando-as
+#[derive(Clone, Default)]
+struct Output {
+    seq: Arc<Mutex<i64>>,
+}
+
+impl Output {
+    fn send(&self, mut message: JsonValue) {
+        let mut seq = self.seq.lock().unwrap_or_else(|e| e.into_inner());
+        *seq += 1;
+        message["seq"] = json!(*seq);
+        let body = message.to_string();
+        let mut stdout = io::stdout().lock();
+        let _ = write!(stdout, "Content-Length: {}\r\n\r\n{}", body.len(), body);
+        let _ = stdout.flush();
+    }
+
+    fn respond(&self, request: &JsonValue, body: JsonValue) {
+        self.send(json!({
+            "type": "response",
+            "request_seq": request["seq"],
+            "command": request["command"],
+            "success": true,
+            "body": body,
+        }));
+    }
+
+    fn fail(&self, request: &JsonValue, message: &str) {
+        self.send(json!({
+            "type": "response",
+            "request_seq": request["seq"],
+            "command": request["command"],
+            "success": false,
+            "message": message,
+        }));
+    }
+
+    fn event(&self, event: &str, body: JsonValue) {
+        self.send(json!({ "type": "event", "event": event, "body": body }));
+    }
+}
+
+struct Dap {
+    requests: Receiver<JsonValue>,
+    output: Output,
+}
+
+impl Dap {
+    /// Próximo pedido do editor; `None` quando ele fecha a conexão
+    fn next_request(&self) -> Option<JsonValue> {
+        self.requests.iter().find(|message| message["type"] == "request")
+    }
+
+    /// Pedidos que não dependem de o programa estar parado
+    fn answer_anytime(&self, request: &JsonValue) {
+        match command(request) {
+            "threads" => self.output.respond(request, json!({ "threads": [{ "id": THREAD_ID, "name": "main" }] })),
+            "stackTrace" | "scopes" | "variables" | "evaluate" => self.output.fail(request, "o programa não está parado"),
+            other => self.output.fail(request, &format!("pedido não suportado: {}", other)),
+        }
+    }
+
+    fn stack_trace(&self, request: &JsonValue, state: &DebugState) {
+        let frames: Vec<JsonValue> = state
+            .frames()
+            .iter()
+            .enumerate()
+            .rev()
+            .map(|(id, frame)| {
+                let name = Path::new(&frame.file).file_name().map_or_else(|| frame.file.clone(), |n| n.to_string_lossy().into_owned());
+                json!({
+                    "id": id,
+                    "name": frame.function,
+                    "source": { "name": name, "path": absolute_path(&frame.file) },
+                    "line": frame.line,
+                    "column": 1,
+                })
+            })
+            .collect();
+        self.output.respond(request, json!({ "stackFrames": frames, "totalFrames": frames.len() }));
+    }
+}
+
+impl Frontend for Dap {
+    fn poll(&mut self, state: &mut DebugState) -> bool {
+        let mut pause = false;
+        loop {
+            let request = match self.requests.try_recv() {
+                Ok(request) => request,
+                Err(TryRecvError::Empty) => return pause,
+                Err(TryRecvError::Disconnected) => std::process::exit(0),
+            };
+            match command(&request) {
+                "setBreakpoints" => {
+                    let response = set_breakpoints(&mut state.breakpoints, &request["arguments"]);
+                    self.output.respond(&request, response);
+                }
+                "pause" => {
+                    self.output.respond(&request, JsonValue::Null);
+                    pause = true;
+                }
+                "disconnect" | "terminate" => {
+                    self.output.respond(&request, JsonValue::Null);
+                    std::process::exit(0);
+                }
+                _ if request["type"] == "request" => self.answer_anytime(&request),
+                _ => {}
+            }
+        }
+    }
+
+    fn paused(&mut self, session: &mut Session, reason: StopReason) -> Resume {
+        let reason = match reason {
+            StopReason::Entry => "entry",
+            StopReason::Breakpoint => "breakpoint",
+            StopReason::Step => "step",
+            StopReason::Pause => "pause",
+        };
+        self.output.event("stopped", json!({ "reason": reason, "threadId": THREAD_ID, "allThreadsStopped": true }));
+
+        loop {
+            let Some(request) = self.next_request() else { std::process::exit(0) };
+            let arguments = &request["arguments"];
+            let resume = match command(&request) {
+                "continue" => Some(Resume::Continue),
+                "next" => Some(Resume::StepOver),
+                "stepIn" => Some(Resume::StepInto),
+                "stepOut" => Some(Resume::StepOut),
+                _ => None,
+            };
+            if let Some(resume) = resume {
+                self.output.respond(&request, json!({ "allThreadsContinued": true }));
+                return resume;
+            }
+            match command(&request) {
+                "stackTrace" => self.stack_trace(&request, session.state),
+                // Um escopo por frame; a referência das variáveis é o id do frame + 1
+                "scopes" => {
+                    let frame = arguments["frameId"].as_u64().unwrap_or(0);
+                    if frame as usize >= session.state.frames().len() {
+                        self.output.fail(&request, &format!("frame {} não existe", frame));
+                    } else {
+                        let scopes = json!([{ "name": "Locais", "variablesReference": frame + 1, "expensive": false }]);
+                        self.output.respond(&request, json!({ "scopes": scopes }));
+                    }
+                }
+                "variables" => {
+                    let frame = arguments["variablesReference"].as_u64().unwrap_or(1).saturating_sub(1) as usize;
+                    let variables: Vec<JsonValue> = session
+                        .variables(frame)
+                        .iter()
+                        .map(|(name, value)| json!({ "name": name, "value": describe_value(value), "variablesReference": 0 }))
+                        .collect();
+                    self.output.respond(&request, json!({ "variables": variables }));
+                }
+                "evaluate" => {
+                    let expression = arguments["expression"].as_str().unwrap_or("");
+                    let result = match arguments["frameId"].as_u64() {
+                        Some(frame) => session.evaluate_in_frame(expression, frame as usize),
+                        None => session.evaluate(expression),
+                    };
+                    match result {
+                        Ok(value) => {
+                            self.output.respond(&request, json!({ "result": describe_value(&value), "variablesReference": 0 }))
+                        }
+                        Err(e) => self.output.fail(&request, &e),
+                    }
+                }
+                "setBreakpoints" => {
+                    let response = set_breakpoints(&mut session.state.breakpoints, arguments);
+                    self.output.respond(&request, response);
+                }
+                "pause" => self.output.respond(&request, JsonValue::Null),
+                "disconnect" | "terminate" => {
+                    self.output.respond(&request, JsonValue::Null);
+                    std::process::exit(0);
+                }
+                _ => self.answer_anytime(&request),
+            }
+        }
+    }
+
+    fn output(&mut self, text: &str) -> bool {
+        self.output.event("output", json!({ "category": "stdout", "output": format!("{}\n", text) }));
+        true
+    }
+}
@@ -0,0 +1,458 @@
+//! `snask debug`: depurador com breakpoints por linha, execução passo a passo,
+//! pilha de chamadas, avaliação de expressões e expressões observadas.
+//!
+//! O `Debugger` é um `Tracer` do interpretador e decide onde parar; o que
+//! acontece quando o programa está parado fica a cargo de um `Frontend`: a
+//! linha de comando (`Cli`) ou o Debug Adapter Protocol (`dap`).
+
+use crate::ast::{Expr, FuncDecl, Stmt, StmtKind};
+use crate::declarations::stdlib_module;
+use crate::interpreter::{InterpretResult, Interpreter, Tracer};
+use crate::parser::parse_program;
+use crate::value::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+/// Uma chamada ativa: o programa principal ou uma função Snask
+#[derive(Debug, Clone)]
+pub struct Frame {
+    pub function: String,
+    pub file: String,
+    /// Linha do statement em execução no frame (a chamada, nos frames externos)
+    pub line: usize,
+    /// Primeiro escopo da tabela de símbolos que pertence ao frame
+    scope_start: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Breakpoint {
+    /// `None`: o arquivo principal
+    pub file: Option<String>,
+    pub line: usize,
+}
+
+/// Como continuar depois de uma parada
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Resume {
+    Continue,
+    /// Próximo statement no mesmo frame ou num frame externo
+    StepOver,
+    /// Próximo statement, entrando em funções
+    StepInto,
+    /// Próximo statement depois que o frame atual retornar
+    StepOut,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StopReason {
+    Entry,
+    Breakpoint,
+    Step,
+    /// Pedido do frontend (`pause` do DAP)
+    Pause,
+}
+
+/// Estado do depurador que os frontends podem ler e alterar
+#[derive(Debug, Default)]
+pub struct DebugState {
+    pub breakpoints: Vec<Breakpoint>,
+    pub watches: Vec<String>,
+    frames: Vec<Frame>,
+    main_file: String,
+}
+
+impl DebugState {
+    /// Frames ativos, do programa principal ao mais interno
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames
+    }
+
+    fn breakpoint_at(&self, file: &str, line: usize) -> bool {
+        self.breakpoints.iter().any(|breakpoint| {
+            breakpoint.line == line && same_file(breakpoint.file.as_deref().unwrap_or(&self.main_file), file)
+        })
+    }
+}
+
+/// Programa parado: acesso ao estado do depurador e ao interpretador
+pub struct Session<'a> {
+    pub state: &'a mut DebugState,
+    interpreter: &'a mut Interpreter,
+}
+
+impl Session<'_> {
+    /// Avalia uma expressão no escopo do frame mais interno
+    pub fn evaluate(&mut self, text: &str) -> Result<Value, String> {
+        let innermost = self.state.frames().len().saturating_sub(1);
+        self.evaluate_in_frame(text, innermost)
+    }
+
+    /// Avalia uma expressão no escopo do frame `index` (0 é o programa principal):
+    /// os escopos dos frames mais internos ficam escondidos durante a avaliação
+    pub fn evaluate_in_frame(&mut self, text: &str, index: usize) -> Result<Value, String> {
+        let frames = self.state.frames();
+        if index >= frames.len() {
+            return Err(format!("frame {} não existe", index));
+        }
+        let end = frames.get(index + 1).map(|next| next.scope_start);
+        let expression = parse_expression(text)?;
+        let hidden = end.map(|end| self.interpreter.get_globals_mut().take_scopes_from(end)).unwrap_or_default();
+        let result = self.interpreter.evaluate(expression).map_err(|e| e.message);
+        self.interpreter.get_globals_mut().restore_scopes(hidden);
+        result
+    }
+
+    /// Variáveis visíveis no frame `index` (0 é o programa principal), sem as
+    /// funções e os módulos nativos
+    pub fn variables(&self, index: usize) -> Vec<(String, Value)> {
+        let frames = self.state.frames();
+        let Some(frame) = frames.get(index) else {
+            return Vec::new();
+        };
+        let scopes = self.interpreter.get_globals().scopes();
+        let end = frames.get(index + 1).map_or(scopes.len(), |next| next.scope_start);
+        let mut variables: Vec<(String, Value)> = Vec::new();
+        // Do escopo mais interno para o externo: um nome redeclarado mostra o valor visível
+        for scope in scopes[frame.scope_start.min(end)..end].iter().rev() {
+            let mut names: Vec<&String> = scope.keys().collect();
+            names.sort();
+            for name in names {
+                let value = &scope[name].value;
+                let hidden = matches!(value, Value::Function(_) | Value::NativeFunction(_))
+                    || (index == 0 && (stdlib_module(name).is_some() || name == "format"));
+                if !hidden && !variables.iter().any(|(existing, _)| existing == name) {
+                    variables.push((name.clone(), value.clone()));
+                }
+            }
+        }
+        variables
+    }
+}
+
+/// Decide o que fazer quando o programa para (e, no DAP, entre statements)
+pub trait Frontend: Send {
+    /// O programa parou; retorna quando o usuário escolhe como continuar
+    fn paused(&mut self, session: &mut Session, reason: StopReason) -> Resume;
+    /// Chamado antes de cada statement; `true` pede uma parada
+    fn poll(&mut self, _state: &mut DebugState) -> bool {
+        false
+    }
+    /// Saída de um `print`; `false` deixa o interpretador escrevê-la
+    fn output(&mut self, _text: &str) -> bool {
+        false
+    }
+}
+
+pub struct Debugger<F: Frontend> {
+    pub state: DebugState,
+    pub frontend: F,
+    /// Passo pendente e a profundidade da pilha quando ele foi pedido
+    step: Option<(Resume, usize)>,
+    /// Onde foi o último statement (arquivo, linha, profundidade): só paramos
+    /// quando isso muda, para não parar duas vezes na mesma linha
+    last_location: Option<(String, usize, usize)>,
+    entry: bool,
+}
+
+impl<F: Frontend> Debugger<F> {
+    /// Com `stop_on_entry`, para antes do primeiro statement
+    pub fn new(main_file: &str, frontend: F, stop_on_entry: bool) -> Self {
+        let main = Frame { function: "<principal>".to_string(), file: main_file.to_string(), line: 0, scope_start: 0 };
+        Debugger {
+            state: DebugState { frames: vec![main], main_file: main_file.to_string(), ..Default::default() },
+            frontend,
+            step: None,
+            last_location: None,
+            entry: stop_on_entry,
+        }
+    }
+
+    fn stop_reason(&mut self, file: &str, line: usize) -> Option<StopReason> {
+        let depth = self.state.frames.len();
+        let location = (file.to_string(), line, depth);
+        if self.last_location.as_ref() == Some(&location) {
+            return None;
+        }
+        self.last_location = Some(location);
+
+        if std::mem::take(&mut self.entry) {
+            return Some(StopReason::Entry);
+        }
+        let stepped = match self.step {
+            Some((Resume::StepInto, _)) => true,
+            Some((Resume::StepOver, from)) => depth <= from,
+            Some((Resume::StepOut, from)) => depth < from,
+            _ => false,
+        };
+        if stepped {
+            Some(StopReason::Step)
+        } else if self.state.breakpoint_at(file, line) {
+            Some(StopReason::Breakpoint)
+        } else {
+            None
+        }
+    }
+}
+
+impl<F: Frontend> Tracer for Debugger<F> {
+    fn statement(&mut self, interpreter: &mut Interpreter, statement: &Stmt) {
+        // Declarar uma função não executa nada que valha uma parada
+        if matches!(statement.kind, StmtKind::FuncDeclaration(_)) {
+            return;
+        }
+        let file = interpreter.current_file().to_string();
+        let line = statement.span.start.line;
+        if let Some(frame) = self.state.frames.last_mut() {
+            frame.file = file.clone();
+            frame.line = line;
+        }
+
+        let paused = self.frontend.poll(&mut self.state);
+        let reason = if paused { Some(StopReason::Pause) } else { self.stop_reason(&file, line) };
+        if let Some(reason) = reason {
+            let depth = self.state.frames.len();
+            let mut session = Session { state: &mut self.state, interpreter };
+            self.step = match self.frontend.paused(&mut session, reason) {
+                Resume::Continue => None,
+                resume => Some((resume, depth)),
+            };
+        }
+    }
+
+    fn enter_function(&mut self, interpreter: &mut Interpreter, function: &FuncDecl) {
+        let scope_start = interpreter.get_globals().scopes().len().saturating_sub(1);
+        self.state.frames.push(Frame {
+            function: function.name.clone(),
+            file: interpreter.current_file().to_string(),
+            line: function.name_span.start.line,
+            scope_start,
+        });
+    }
+
+    fn exit_function(&mut self, _interpreter: &mut Interpreter, _function: &FuncDecl) {
+        if self.state.frames.len() > 1 {
+            self.state.frames.pop();
+        }
+    }
+
+    fn output(&mut self, text: &str) -> bool {
+        self.frontend.output(text)
+    }
+}
+
+/// Lê uma expressão digitada no depurador
+pub fn parse_expression(text: &str) -> Result<Expr, String> {
+    let source = format!("{};", text.trim().trim_end_matches(';'));
+    let program = parse_program(&source)
+        .map_err(|diagnostics| diagnostics.first().map_or_else(String::new, |d| d.message.clone()))?;
+    match program.into_iter().next().map(|stmt| stmt.kind) {
+        Some(StmtKind::Expression(expr)) | Some(StmtKind::FuncCall(expr)) => Ok(expr),
+        _ => Err(format!("'{}' não é uma expressão", text.trim())),
+    }
+}
+
+/// Valor como aparece no depurador: strings entre aspas
+pub fn describe_value(value: &Value) -> String {
+    match value {
+        Value::String(s) => format!("{:?}", s),
+        value => value.to_string(),
+    }
+}
+
+pub fn same_file(a: &str, b: &str) -> bool {
+    a == b
+        || match (fs::canonicalize(a), fs::canonicalize(b)) {
+            (Ok(a), Ok(b)) => a == b,
+            _ => false,
+        }
+}
+
+/// Executa o programa com o depurador de linha de comando, parando no primeiro statement
+pub fn run_cli(file: &str, program: crate::ast::Program, max_call_depth: usize) -> Result<(), String> {
+    println!("Depurando {}. Digite 'help' para ver os comandos.", file);
+    let debugger = Arc::new(Mutex::new(Debugger::new(file, Cli::default(), true)));
+    let mut interpreter = Interpreter::new();
+    interpreter.set_file(file);
+    interpreter.set_max_call_depth(max_call_depth);
+    interpreter.add_tracer(debugger);
+    match interpreter.interpret(program) {
+        InterpretResult::Ok => {
+            println!("O programa terminou.");
+            Ok(())
+        }
+        InterpretResult::RuntimeError(e) => Err(crate::render_runtime_error(&e)),
+    }
+}
+
+/// Frontend de linha de comando, com comandos no estilo do gdb
+#[derive(Default)]
+pub struct Cli {
+    sources: HashMap<String, Vec<String>>,
+}
+
+impl Cli {
+    fn source_line(&mut self, file: &str, line: usize) -> Option<String> {
+        let lines = self
+            .sources
+            .entry(file.to_string())
+            .or_insert_with(|| fs::read_to_string(file).map(|s| s.lines().map(str::to_string).collect()).unwrap_or_default());
+        lines.get(line.checked_sub(1)?).cloned()
+    }
+
+    fn show_location(&mut self, frame: &Frame) {
+        println!("{}:{} em {}", frame.file, frame.line, frame.function);
+        if let Some(text) = self.source_line(&frame.file, frame.line) {
+            println!("{:>5} | {}", frame.line, text);
+        }
+    }
+
+    fn list(&mut self, frame: &Frame) {
+        for line in frame.line.saturating_sub(5).max(1)..=frame.line + 5 {
+            let Some(text) = self.source_line(&frame.file, line) else { break };
+            let marker = if line == frame.line { "->" } else { "  " };
+            println!("{} {:>4} | {}", marker, line, text);
+        }
+    }
+
+    fn show_watches(session: &mut Session) {
+        for (i, watch) in session.state.watches.clone().iter().enumerate() {
+            match session.evaluate(watch) {
+                Ok(value) => println!("  [{}] {} = {}", i + 1, watch, describe_value(&value)),
+                Err(e) => println!("  [{}] {} = <erro: {}>", i + 1, watch, e),
+            }
+        }
+    }
+
+    fn add_breakpoint(state: &mut DebugState, spec: &str) {
+        let (file, line) = match spec.rsplit_once(':') {
+            Some((file, line)) => (Some(file.to_string()), line),
+            None => (None, spec),
+        };
+        match line.trim().parse::<usize>() {
+            Ok(line) if line > 0 => {
+                let breakpoint = Breakpoint { file, line };
+                if !state.breakpoints.contains(&breakpoint) {
+                    state.breakpoints.push(breakpoint);
+                }
+                println!("Breakpoint {} em {}", state.breakpoints.len(), spec.trim());
+            }
+            _ => println!("Uso: break [arquivo:]linha"),
+        }
+    }
+
+    fn help() {
+        println!("Comandos:");
+        println!("  break, b [arquivo:]linha   adiciona um breakpoint");
+        println!("  delete, d [n]              remove o breakpoint n (ou todos)");
+        println!("  breakpoints                lista os breakpoints");
+        println!("  continue, c                continua até o próximo breakpoint");
+        println!("  next, n                    executa a linha, sem entrar em funções");
+        println!("  step, s                    executa a linha, entrando em funções");
+        println!("  finish, out                continua até a função atual retornar");
+        println!("  backtrace, bt              mostra a pilha de chamadas");
+        println!("  print, p <expr>            avalia uma expressão no escopo atual");
+        println!("  locals                     mostra as variáveis do frame atual");
+        println!("  watch, w <expr>            mostra a expressão a cada parada");
+        println!("  unwatch <n>                remove a expressão observada n");
+        println!("  list, l                    mostra o código em volta da linha atual");
+        println!("  quit, q                    encerra o programa");
+    }
+}
+
+impl Frontend for Cli {
+    fn paused(&mut self, session: &mut Session, reason: StopReason) -> Resume {
+        let Some(frame) = session.state.frames().last().cloned() else {
+            return Resume::Continue;
+        };
+        if reason == StopReason::Breakpoint {
+            print!("Breakpoint: ");
+        }
+        self.show_location(&frame);
+        Cli::show_watches(session);
+
+        let stdin = io::stdin();
+        loop {
+            print!("(snask-debug) ");
+            let _ = io::stdout().flush();
+            let mut input = String::new();
+            if stdin.lock().read_line(&mut input).unwrap_or(0) == 0 {
+                // Fim da entrada: o programa segue sem o depurador
+                session.state.breakpoints.clear();
+                return Resume::Continue;
+            }
+            let input = input.trim();
+            let (command, argument) = input.split_once(char::is_whitespace).unwrap_or((input, ""));
+            let argument = argument.trim();
+            match command {
+                "" => {}
+                "c" | "continue" => return Resume::Continue,
+                "n" | "next" => return Resume::StepOver,
+                "s" | "step" => return Resume::StepInto,
+                "out" | "finish" => return Resume::StepOut,
+                "b" | "break" => Cli::add_breakpoint(session.state, argument),
+                "d" | "delete" => {
+                    if argument.is_empty() {
+                        session.state.breakpoints.clear();
+                        println!("Todos os breakpoints foram removidos");
+                    } else {
+                        match argument.parse::<usize>() {
+                            Ok(n) if n >= 1 && n <= session.state.breakpoints.len() => {
+                                session.state.breakpoints.remove(n - 1);
+                            }
+                            _ => println!("Não existe o breakpoint {}", argument),
+                        }
+                    }
+                }
+                "breakpoints" | "info" => {
+                    if session.state.breakpoints.is_empty() {
+                        println!("Nenhum breakpoint");
+                    }
+                    for (i, breakpoint) in session.state.breakpoints.iter().enumerate() {
+                        let file = breakpoint.file.as_deref().unwrap_or(&frame.file);
+                        println!("  {}. {}:{}", i + 1, file, breakpoint.line);
+                    }
+                }
+                "bt" | "backtrace" => {
+                    for (i, frame) in session.state.frames().iter().rev().enumerate() {
+                        println!("  #{} {} ({}:{})", i, frame.function, frame.file, frame.line);
+                    }
+                }
+                "p" | "print" => match session.evaluate(argument) {
+                    Ok(value) => println!("{}", describe_value(&value)),
+                    Err(e) => println!("Erro: {}", e),
+                },
+                "locals" => {
+                    let variables = session.variables(session.state.frames().len() - 1);
+                    if variables.is_empty() {
+                        println!("Nenhuma variável");
+                    }
+                    for (name, value) in variables {
+                        println!("  {} = {}", name, describe_value(&value));
+                    }
+                }
+                "w" | "watch" if !argument.is_empty() => {
+                    session.state.watches.push(argument.to_string());
+                    Cli::show_watches(session);
+                }
+                "unwatch" => match argument.parse::<usize>() {
+                    Ok(n) if n >= 1 && n <= session.state.watches.len() => {
+                        session.state.watches.remove(n - 1);
+                    }
+                    _ => println!("Não existe a expressão observada {}", argument),
+                },
+                "l" | "list" => self.list(&frame),
+                "h" | "help" => Cli::help(),
+                "q" | "quit" => std::process::exit(0),
+                _ => println!("Comando desconhecido '{}'. Digite 'help' para ver os comandos.", command),
+            }
+        }
+    }
+}
+
+/// Caminho absoluto para mostrar ao editor
+pub fn absolute_path(file: &str) -> String {
+    fs::canonicalize(Path::new(file)).map_or_else(|_| file.to_string(), |path| path.to_string_lossy().into_owned())
+}
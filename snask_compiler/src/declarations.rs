@@ -0,0 +1,132 @@
+//! Assinaturas dos módulos nativos (`math`, `string`, `blaze_db`, ...), lidas do
+//! arquivo de declarações `stdlib/stdlib.snaskd` embutido no compilador.
+
+use crate::parser::parse_declarations;
+use crate::types::Type;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::OnceLock;
+
+const STDLIB_DECLARATIONS: &str = include_str!("stdlib/stdlib.snaskd");
+
+/// Um parâmetro de função declarada: `nome: tipo`, `nome?: tipo` ou `nome: tipo...`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParamDecl {
+    pub name: String,
+    pub param_type: Type,
+    /// Pode ser omitido na chamada
+    pub optional: bool,
+    /// Recebe todos os argumentos restantes
+    pub variadic: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionDecl {
+    pub params: Vec<ParamDecl>,
+    pub return_type: Type,
+}
+
+impl FunctionDecl {
+    /// Assinatura em que todos os parâmetros são obrigatórios, como nas funções do usuário
+    pub fn from_types(params: &[Type], return_type: &Type) -> Self {
+        let params = params
+            .iter()
+            .map(|param_type| ParamDecl { name: String::new(), param_type: param_type.clone(), optional: false, variadic: false })
+            .collect();
+        FunctionDecl { params, return_type: return_type.clone() }
+    }
+
+    pub fn min_args(&self) -> usize {
+        self.params.iter().filter(|p| !p.optional && !p.variadic).count()
+    }
+
+    /// `None` quando o último parâmetro é variádico
+    pub fn max_args(&self) -> Option<usize> {
+        if self.params.iter().any(|p| p.variadic) { None } else { Some(self.params.len()) }
+    }
+
+    /// Parâmetro que recebe o argumento na posição `index`
+    pub fn param_for(&self, index: usize) -> Option<&ParamDecl> {
+        self.params.get(index).or_else(|| self.params.last().filter(|p| p.variadic))
+    }
+
+    /// Tipo de função equivalente, usado quando a função é passada como valor
+    pub fn as_type(&self) -> Type {
+        Type::Function(self.params.iter().map(|p| p.param_type.clone()).collect(), Box::new(self.return_type.clone()))
+    }
+}
+
+impl fmt::Display for FunctionDecl {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let params: Vec<String> = self
+            .params
+            .iter()
+            .map(|p| {
+                let marker = if p.optional { "?" } else { "" };
+                let rest = if p.variadic { "..." } else { "" };
+                if p.name.is_empty() {
+                    format!("{}{}", p.param_type, rest)
+                } else {
+                    format!("{}{}: {}{}", p.name, marker, p.param_type, rest)
+                }
+            })
+            .collect();
+        write!(f, "({}): {}", params.join(", "), self.return_type)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MemberDecl {
+    Function(FunctionDecl),
+    Constant(Type),
+}
+
+impl MemberDecl {
+    pub fn member_type(&self) -> Type {
+        match self {
+            MemberDecl::Function(function) => function.as_type(),
+            MemberDecl::Constant(constant_type) => constant_type.clone(),
+        }
+    }
+}
+
+/// `module nome { ... }`: os membros na ordem em que foram declarados
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModuleDecl {
+    pub name: String,
+    pub members: Vec<(String, MemberDecl)>,
+    /// Comentário `///` antes de `module`
+    pub doc: Option<String>,
+    /// Comentários `///` dos membros, pelo nome
+    pub member_docs: HashMap<String, String>,
+}
+
+impl ModuleDecl {
+    pub fn member(&self, name: &str) -> Option<&MemberDecl> {
+        self.members.iter().find(|(member, _)| member == name).map(|(_, decl)| decl)
+    }
+
+    pub fn member_doc(&self, name: &str) -> Option<&str> {
+        self.member_docs.get(name).map(String::as_str)
+    }
+
+    pub fn member_names(&self) -> impl Iterator<Item = &str> {
+        self.members.iter().map(|(name, _)| name.as_str())
+    }
+}
+
+/// Módulos nativos declarados em `stdlib.snaskd`, lidos na primeira chamada
+pub fn stdlib_modules() -> &'static HashMap<String, ModuleDecl> {
+    static MODULES: OnceLock<HashMap<String, ModuleDecl>> = OnceLock::new();
+    MODULES.get_or_init(|| {
+        let modules = parse_declarations(STDLIB_DECLARATIONS).unwrap_or_else(|diagnostics| {
+            let messages: Vec<String> = diagnostics.iter().map(|d| d.message.clone()).collect();
+            panic!("stdlib.snaskd inválido: {}", messages.join("; "))
+        });
+        modules.into_iter().map(|module| (module.name.clone(), module)).collect()
+    })
+}
+
+pub fn stdlib_module(name: &str) -> Option<&'static ModuleDecl> {
+    stdlib_modules().get(name)
+}
@@ -254,6 +254,12 @@ pub struct DiagnosticBag {
     diagnostics: Vec<Diagnostic>,
 }
 
+impl From<Vec<Diagnostic>> for DiagnosticBag {
+    fn from(diagnostics: Vec<Diagnostic>) -> Self {
+        DiagnosticBag { diagnostics }
+    }
+}
+
 impl DiagnosticBag {
     pub fn new() -> Self {
         DiagnosticBag {
@@ -0,0 +1,483 @@
+//! `snask doc`: documentação da API em HTML e Markdown, a partir dos
+//! comentários `///` antes de `fun`, `const` e `module`.
+//!
+//! Cada arquivo `.snask` do projeto, e cada módulo `.snask` que ele importa,
+//! vira uma página com as funções e constantes do nível superior. Com
+//! `--stdlib`, os módulos nativos declarados em `stdlib.snaskd` também ganham
+//! páginas, geradas do mesmo jeito.
+
+use crate::ast::{Program, StmtKind};
+use crate::check::collect_files;
+use crate::declarations::{stdlib_module, stdlib_modules, MemberDecl, ModuleDecl};
+use crate::diagnostics::DiagnosticBag;
+use crate::modules::{is_native_module, resolve_module_path};
+use crate::parser::parse_program;
+use crate::span::Span;
+use clap::ValueEnum;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum DocFormat {
+    /// Static HTML pages with a module index in the sidebar
+    Html,
+    /// One Markdown file per module
+    Markdown,
+    /// Both HTML and Markdown
+    Both,
+}
+
+#[derive(Debug, Clone)]
+pub struct DocOptions {
+    /// Diretório onde as páginas são gravadas
+    pub output: String,
+    pub format: DocFormat,
+    /// Gera também as páginas dos módulos nativos
+    pub stdlib: bool,
+}
+
+/// A página de um módulo
+struct Page {
+    /// `util`, `lib/datas` ou, nos nativos, `blaze`
+    name: String,
+    /// Arquivo de onde a página foi gerada (vazio nos módulos nativos)
+    file: Option<String>,
+    doc: Option<String>,
+    imports: Vec<String>,
+    functions: Vec<Item>,
+    constants: Vec<Item>,
+}
+
+struct Item {
+    name: String,
+    signature: String,
+    doc: Option<String>,
+}
+
+impl Page {
+    fn native(&self) -> bool {
+        self.file.is_none()
+    }
+
+    fn title(&self) -> String {
+        if self.native() {
+            format!("Módulo nativo `{}`", self.name)
+        } else {
+            format!("Módulo `{}`", self.name)
+        }
+    }
+
+    /// Nome do arquivo gerado, sem extensão (`lib/datas` vira `lib.datas`)
+    fn slug(&self) -> String {
+        self.name.replace(['/', '\\'], ".")
+    }
+
+    fn summary(&self) -> String {
+        self.doc.as_deref().map(summary).unwrap_or_default()
+    }
+}
+
+/// Gera a documentação; retorna quantas páginas de módulo foram escritas
+pub fn run(paths: &[String], options: &DocOptions) -> Result<usize, String> {
+    let mut pages = Vec::new();
+    if !paths.is_empty() || !options.stdlib {
+        let paths = if paths.is_empty() { vec![".".to_string()] } else { paths.to_vec() };
+        pages.extend(project_pages(&paths)?);
+    }
+    if options.stdlib {
+        let mut modules: Vec<&ModuleDecl> = stdlib_modules().values().collect();
+        modules.sort_by(|a, b| a.name.cmp(&b.name));
+        pages.extend(modules.into_iter().map(native_page));
+    }
+
+    let output = Path::new(&options.output);
+    fs::create_dir_all(output).map_err(|e| format!("Não foi possível criar o diretório {}: {}", output.display(), e))?;
+    let write = |name: String, contents: String| {
+        let path = output.join(name);
+        fs::write(&path, contents).map_err(|e| format!("Não foi possível gravar {}: {}", path.display(), e))
+    };
+    if options.format != DocFormat::Html {
+        write("index.md".to_string(), markdown_index(&pages))?;
+        for page in &pages {
+            write(format!("{}.md", page.slug()), markdown_page(page, &pages))?;
+        }
+    }
+    if options.format != DocFormat::Markdown {
+        write("index.html".to_string(), html_index(&pages))?;
+        for page in &pages {
+            write(format!("{}.html", page.slug()), html_page(page, &pages))?;
+        }
+    }
+    Ok(pages.len())
+}
+
+/// Páginas dos arquivos dados (menos os de teste) e dos módulos que eles importam
+fn project_pages(paths: &[String]) -> Result<Vec<Page>, String> {
+    let mut queue: Vec<PathBuf> = collect_files(paths)?
+        .into_iter()
+        .filter(|file| !file.to_string_lossy().ends_with("_test.snask"))
+        .collect();
+    queue.reverse();
+    let mut seen = HashSet::new();
+    let mut pages = Vec::new();
+    while let Some(file) = queue.pop() {
+        if !seen.insert(fs::canonicalize(&file).unwrap_or_else(|_| file.clone())) {
+            continue;
+        }
+        let path = file.strip_prefix(".").unwrap_or(&file).to_string_lossy().into_owned();
+        let source = fs::read_to_string(&file).map_err(|e| format!("Não foi possível ler o arquivo {}: {}", path, e))?;
+        let program = parse_program(&source).map_err(|diagnostics| DiagnosticBag::from(diagnostics).render_all(&path, &source))?;
+        let page = file_page(&path, &source, &program);
+        for import in page.imports.iter().rev() {
+            if !is_native(import) {
+                if let Ok(module) = resolve_module_path(import) {
+                    queue.push(module);
+                }
+            }
+        }
+        pages.push(page);
+    }
+    Ok(pages)
+}
+
+fn is_native(import: &str) -> bool {
+    is_native_module(import) || stdlib_module(import).is_some()
+}
+
+fn file_page(path: &str, source: &str, program: &Program) -> Page {
+    let text = |span: Span| source[span.start.offset..span.end.offset].to_string();
+    let mut page = Page {
+        name: path.strip_suffix(".snask").unwrap_or(path).to_string(),
+        file: Some(path.to_string()),
+        doc: None,
+        imports: Vec::new(),
+        functions: Vec::new(),
+        constants: Vec::new(),
+    };
+    for stmt in program {
+        match &stmt.kind {
+            StmtKind::Import(import) => page.imports.push(import.clone()),
+            StmtKind::FuncDeclaration(decl) => {
+                let params: Vec<String> = decl
+                    .params
+                    .iter()
+                    .map(|param| match param.type_span {
+                        Some(span) => format!("{}: {}", param.name, text(span)),
+                        None => param.name.clone(),
+                    })
+                    .collect();
+                let return_type = decl.return_type_span.map(|span| format!(": {}", text(span))).unwrap_or_default();
+                page.functions.push(Item {
+                    name: decl.name.clone(),
+                    signature: format!("fun {}({}){}", decl.name, params.join(", "), return_type),
+                    doc: decl.doc.clone(),
+                });
+            }
+            StmtKind::ConstDeclaration(decl) => {
+                let annotation = decl.type_span.map(|span| format!(": {}", text(span))).unwrap_or_default();
+                // Valores curtos ajudam a entender a constante; os longos poluem a página
+                let value = text(decl.value.span);
+                let value = if value.contains('\n') || value.chars().count() > 60 { String::new() } else { format!(" = {}", value) };
+                page.constants.push(Item {
+                    name: decl.name.clone(),
+                    signature: format!("const {}{}{}", decl.name, annotation, value),
+                    doc: decl.doc.clone(),
+                });
+            }
+            _ => {}
+        }
+    }
+    page
+}
+
+fn native_page(module: &ModuleDecl) -> Page {
+    let mut page = Page {
+        name: module.name.clone(),
+        file: None,
+        doc: module.doc.clone(),
+        imports: Vec::new(),
+        functions: Vec::new(),
+        constants: Vec::new(),
+    };
+    for (name, member) in &module.members {
+        let doc = module.member_doc(name).map(str::to_string);
+        match member {
+            MemberDecl::Function(function) => {
+                page.functions.push(Item { name: name.clone(), signature: format!("fun {}{}", name, function), doc })
+            }
+            MemberDecl::Constant(constant_type) => {
+                page.constants.push(Item { name: name.clone(), signature: format!("const {}: {}", name, constant_type), doc })
+            }
+        }
+    }
+    page
+}
+
+/// Primeiro parágrafo de um comentário, numa linha só
+fn summary(doc: &str) -> String {
+    doc.lines().take_while(|line| !line.trim().is_empty()).map(str::trim).collect::<Vec<_>>().join(" ")
+}
+
+/// Página onde um `import` está documentado, se houver
+fn import_target<'a>(import: &str, pages: &'a [Page]) -> Option<&'a Page> {
+    if is_native(import) {
+        return pages.iter().find(|page| page.native() && page.name == import);
+    }
+    let file = fs::canonicalize(resolve_module_path(import).ok()?).ok()?;
+    pages.iter().find(|page| page.file.as_ref().and_then(|f| fs::canonicalize(f).ok()).as_ref() == Some(&file))
+}
+
+// --- Markdown ---------------------------------------------------------------
+
+fn markdown_index(pages: &[Page]) -> String {
+    let mut out = String::from("# Documentação da API\n");
+    for (title, native) in [("Módulos do projeto", false), ("Biblioteca padrão", true)] {
+        let group: Vec<&Page> = pages.iter().filter(|page| page.native() == native).collect();
+        if group.is_empty() {
+            continue;
+        }
+        out.push_str(&format!("\n## {}\n\n", title));
+        for page in group {
+            let summary = page.summary();
+            let dash = if summary.is_empty() { String::new() } else { format!(" — {}", summary) };
+            out.push_str(&format!("- [`{}`]({}.md){}\n", page.name, page.slug(), dash));
+        }
+    }
+    out
+}
+
+fn markdown_page(page: &Page, pages: &[Page]) -> String {
+    let mut out = format!("# {}\n\n", page.title());
+    if let Some(file) = &page.file {
+        out.push_str(&format!("Arquivo: `{}`\n\n", file));
+    }
+    if let Some(doc) = &page.doc {
+        out.push_str(&format!("{}\n\n", doc));
+    }
+    if !page.imports.is_empty() {
+        let imports: Vec<String> = page
+            .imports
+            .iter()
+            .map(|import| match import_target(import, pages) {
+                Some(target) => format!("[`{}`]({}.md)", import, target.slug()),
+                None => format!("`{}`", import),
+            })
+            .collect();
+        out.push_str(&format!("Importa: {}\n\n", imports.join(", ")));
+    }
+    for (title, items) in [("Funções", &page.functions), ("Constantes", &page.constants)] {
+        if items.is_empty() {
+            continue;
+        }
+        out.push_str(&format!("## {}\n\n", title));
+        for item in items {
+            out.push_str(&format!("### `{}`\n\n```snask\n{}\n```\n\n", item.name, item.signature));
+            if let Some(doc) = &item.doc {
+                out.push_str(&format!("{}\n\n", doc));
+            }
+        }
+    }
+    format!("{}\n", out.trim_end())
+}
+
+// --- HTML -------------------------------------------------------------------
+
+const STYLE: &str = "
+body { margin: 0; font-family: system-ui, sans-serif; line-height: 1.5; color: #222; display: flex; }
+nav { width: 16rem; min-height: 100vh; padding: 1rem; background: #f4f1ea; box-sizing: border-box; flex-shrink: 0; }
+nav h2 { font-size: 0.8rem; text-transform: uppercase; color: #777; margin: 1.2rem 0 0.3rem; }
+nav a { display: block; color: #333; text-decoration: none; padding: 0.1rem 0; }
+nav a.current { font-weight: bold; color: #b5451b; }
+main { padding: 1rem 2.5rem; max-width: 52rem; }
+pre { background: #f6f6f6; border: 1px solid #e3e3e3; padding: 0.7rem; overflow-x: auto; }
+code { font-family: ui-monospace, monospace; font-size: 0.92em; }
+h3 { margin-top: 2rem; border-top: 1px solid #eee; padding-top: 1rem; }
+h3 a { color: inherit; text-decoration: none; }
+dl dt { margin-top: 0.4rem; }
+";
+
+fn html_document(title: &str, nav: String, body: String) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"pt-BR\">\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{}</style>\n</head>\n<body>\n{}\n<main>\n{}</main>\n</body>\n</html>\n",
+        escape(title),
+        STYLE,
+        nav,
+        body
+    )
+}
+
+fn html_nav(pages: &[Page], current: Option<&Page>) -> String {
+    let mut nav = String::from("<nav>\n<a href=\"index.html\"><strong>Documentação</strong></a>\n");
+    for (title, native) in [("Projeto", false), ("Biblioteca padrão", true)] {
+        let group: Vec<&Page> = pages.iter().filter(|page| page.native() == native).collect();
+        if group.is_empty() {
+            continue;
+        }
+        nav.push_str(&format!("<h2>{}</h2>\n", title));
+        for page in group {
+            let class = if current.is_some_and(|current| std::ptr::eq(current, page)) { " class=\"current\"" } else { "" };
+            nav.push_str(&format!("<a href=\"{}.html\"{}>{}</a>\n", page.slug(), class, escape(&page.name)));
+        }
+    }
+    nav.push_str("</nav>");
+    nav
+}
+
+fn html_index(pages: &[Page]) -> String {
+    let mut body = String::from("<h1>Documentação da API</h1>\n");
+    for (title, native) in [("Módulos do projeto", false), ("Biblioteca padrão", true)] {
+        let group: Vec<&Page> = pages.iter().filter(|page| page.native() == native).collect();
+        if group.is_empty() {
+            continue;
+        }
+        body.push_str(&format!("<h2>{}</h2>\n<dl>\n", title));
+        for page in group {
+            body.push_str(&format!(
+                "<dt><a href=\"{}.html\"><code>{}</code></a></dt>\n<dd>{}</dd>\n",
+                page.slug(),
+                escape(&page.name),
+                inline(&page.summary())
+            ));
+        }
+        body.push_str("</dl>\n");
+    }
+    html_document("Documentação da API", html_nav(pages, None), body)
+}
+
+fn html_page(page: &Page, pages: &[Page]) -> String {
+    let mut body = format!("<h1>{}</h1>\n", inline(&page.title()));
+    if let Some(file) = &page.file {
+        body.push_str(&format!("<p>Arquivo: <code>{}</code></p>\n", escape(file)));
+    }
+    if let Some(doc) = &page.doc {
+        body.push_str(&markdown_to_html(doc));
+    }
+    if !page.imports.is_empty() {
+        let imports: Vec<String> = page
+            .imports
+            .iter()
+            .map(|import| match import_target(import, pages) {
+                Some(target) => format!("<a href=\"{}.html\"><code>{}</code></a>", target.slug(), escape(import)),
+                None => format!("<code>{}</code>", escape(import)),
+            })
+            .collect();
+        body.push_str(&format!("<p>Importa: {}</p>\n", imports.join(", ")));
+    }
+    for (title, items) in [("Funções", &page.functions), ("Constantes", &page.constants)] {
+        if items.is_empty() {
+            continue;
+        }
+        body.push_str(&format!("<h2>{}</h2>\n", title));
+        for item in items {
+            body.push_str(&format!(
+                "<h3 id=\"{0}\"><a href=\"#{0}\"><code>{0}</code></a></h3>\n<pre><code class=\"language-snask\">{1}</code></pre>\n",
+                escape(&item.name),
+                escape(&item.signature)
+            ));
+            if let Some(doc) = &item.doc {
+                body.push_str(&markdown_to_html(doc));
+            }
+        }
+    }
+    html_document(&page.title().replace('`', ""), html_nav(pages, Some(page)), body)
+}
+
+/// O Markdown dos comentários: parágrafos, listas, títulos, blocos de código,
+/// `código`, **negrito** e [links](url)
+fn markdown_to_html(text: &str) -> String {
+    let mut out = String::new();
+    // Linhas do parágrafo ou, dentro de uma lista, do item atual
+    let mut block: Vec<&str> = Vec::new();
+    let mut list: Option<&str> = None;
+    let mut lines = text.lines();
+
+    fn flush(out: &mut String, block: &mut Vec<&str>, list: &mut Option<&str>) {
+        match list.take() {
+            Some(tag) => out.push_str(&format!("<li>{}</li>\n</{}>\n", inline(&block.join(" ")), tag)),
+            None if !block.is_empty() => out.push_str(&format!("<p>{}</p>\n", inline(&block.join(" ")))),
+            None => {}
+        }
+        block.clear();
+    }
+
+    while let Some(line) = lines.next() {
+        let trimmed = line.trim();
+        if let Some(language) = trimmed.strip_prefix("```") {
+            flush(&mut out, &mut block, &mut list);
+            let code: Vec<&str> = lines.by_ref().take_while(|line| !line.trim().starts_with("```")).collect();
+            let language = if language.is_empty() { "snask" } else { language };
+            out.push_str(&format!("<pre><code class=\"language-{}\">{}</code></pre>\n", escape(language), escape(&code.join("\n"))));
+        } else if trimmed.is_empty() {
+            flush(&mut out, &mut block, &mut list);
+        } else if let Some(level) = trimmed.find(|c| c != '#').filter(|&level| level > 0 && trimmed[level..].starts_with(' ')) {
+            flush(&mut out, &mut block, &mut list);
+            // Os títulos dos comentários ficam abaixo dos da página
+            let tag = (level + 3).min(6);
+            out.push_str(&format!("<h{0}>{1}</h{0}>\n", tag, inline(trimmed[level..].trim())));
+        } else if let Some((tag, item)) = list_item(trimmed) {
+            if list == Some(tag) {
+                out.push_str(&format!("<li>{}</li>\n", inline(&block.join(" "))));
+                block.clear();
+            } else {
+                flush(&mut out, &mut block, &mut list);
+                out.push_str(&format!("<{}>\n", tag));
+                list = Some(tag);
+            }
+            block.push(item);
+        } else {
+            // Numa lista, uma linha sem marcador continua o item anterior
+            block.push(trimmed);
+        }
+    }
+    flush(&mut out, &mut block, &mut list);
+    out
+}
+
+/// `- item`/`* item` viram `ul`; `1. item`, `ol`
+fn list_item(line: &str) -> Option<(&'static str, &str)> {
+    if let Some(item) = line.strip_prefix("- ").or_else(|| line.strip_prefix("* ")) {
+        return Some(("ul", item));
+    }
+    let digits = line.find(|c: char| !c.is_ascii_digit())?;
+    line[digits..].strip_prefix(". ").filter(|_| digits > 0).map(|item| ("ol", item))
+}
+
+/// Marcação dentro de uma linha
+fn inline(text: &str) -> String {
+    let mut out = String::new();
+    for (index, part) in text.split('`').enumerate() {
+        if index % 2 == 1 {
+            out.push_str(&format!("<code>{}</code>", escape(part)));
+            continue;
+        }
+        let mut rest = part;
+        while !rest.is_empty() {
+            if let Some(after) = rest.strip_prefix("**") {
+                if let Some(end) = after.find("**") {
+                    out.push_str(&format!("<strong>{}</strong>", escape(&after[..end])));
+                    rest = &after[end + 2..];
+                    continue;
+                }
+            }
+            if let Some(after) = rest.strip_prefix('[') {
+                if let Some((label, url, remaining)) = after.split_once("](").and_then(|(label, tail)| {
+                    let (url, remaining) = tail.split_once(')')?;
+                    Some((label, url, remaining))
+                }) {
+                    out.push_str(&format!("<a href=\"{}\">{}</a>", escape(url), escape(label)));
+                    rest = remaining;
+                    continue;
+                }
+            }
+            let next = rest[1..].find(['*', '[']).map_or(rest.len(), |i| i + 1);
+            out.push_str(&escape(&rest[..next]));
+            rest = &rest[next..];
+        }
+    }
+    out
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
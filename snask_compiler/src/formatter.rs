@@ -0,0 +1,683 @@
+//! `snask fmt`: reescreve o código em um estilo único.
+//!
+//! O programa é parseado e impresso de volta a partir do AST: indentação de 4
+//! espaços, `;` no fim dos statements simples, espaços em volta dos operadores,
+//! `{` na mesma linha e `} elif`/`} else` juntos. Chamadas, listas e dicts que
+//! passam de `MAX_WIDTH` colunas são quebrados com um item por linha. Os
+//! comentários vêm do lexer (`parse_with_comments`) e voltam para perto do
+//! código a que pertenciam; linhas em branco entre statements são mantidas (no
+//! máximo uma).
+
+use crate::ast::{BinaryOp, Expr, ExprKind, FuncDecl, LiteralValue, LoopStmt, Stmt, StmtKind, UnaryOp};
+use crate::check::collect_files;
+use crate::diagnostics::{Diagnostic, DiagnosticBag};
+use crate::parser::{parse_with_comments, Comment, Token, Tokenizer};
+use crate::span::Span;
+use std::fs;
+use std::io::Read;
+
+const INDENT: &str = "    ";
+/// Largura máxima de uma linha antes de quebrar chamadas, listas e dicts
+const MAX_WIDTH: usize = 100;
+
+/// Formata um arquivo inteiro. Código com erros de sintaxe não é formatado.
+pub fn format_source(source: &str) -> Result<String, Vec<Diagnostic>> {
+    let (program, comments) = parse_with_comments(source)?;
+    let mut tokenizer = Tokenizer::new(source);
+    let mut tokens = Vec::new();
+    loop {
+        match tokenizer.next_token() {
+            (Token::Eof(_), _) => break,
+            (token, span) => tokens.push((token, span)),
+        }
+    }
+
+    let mut formatter = Formatter { source, tokens, comments, next_comment: 0, out: String::new(), last_line: 0 };
+    formatter.block(&program, 0, 0, source.len() + 1);
+    Ok(formatter.out)
+}
+
+/// Formata os arquivos no lugar, ou a entrada padrão quando não há caminhos (ou
+/// o caminho é `-`). Com `check`, só informa o que mudaria. Retorna o código de
+/// saída: 1 se algum arquivo tem erros de sintaxe ou, com `check`, não está formatado.
+pub fn run(paths: &[String], check: bool) -> Result<i32, String> {
+    if paths.is_empty() || paths == ["-"] {
+        let mut source = String::new();
+        std::io::stdin()
+            .read_to_string(&mut source)
+            .map_err(|e| format!("Não foi possível ler a entrada padrão: {}", e))?;
+        return Ok(match format_source(&source) {
+            Ok(formatted) if check => {
+                if formatted == source {
+                    0
+                } else {
+                    eprintln!("<stdin> não está formatado");
+                    1
+                }
+            }
+            Ok(formatted) => {
+                print!("{}", formatted);
+                0
+            }
+            Err(diagnostics) => {
+                eprint!("{}", DiagnosticBag::from(diagnostics).render_all("<stdin>", &source));
+                1
+            }
+        });
+    }
+
+    let mut failed = false;
+    for file in collect_files(paths)? {
+        let path = file.to_string_lossy();
+        let source = fs::read_to_string(&file).map_err(|e| format!("Não foi possível ler o arquivo {}: {}", path, e))?;
+        let formatted = match format_source(&source) {
+            Ok(formatted) => formatted,
+            Err(diagnostics) => {
+                eprint!("{}", DiagnosticBag::from(diagnostics).render_all(&path, &source));
+                failed = true;
+                continue;
+            }
+        };
+        if formatted == source {
+            continue;
+        }
+        if check {
+            println!("{}", path);
+            failed = true;
+        } else {
+            fs::write(&file, formatted).map_err(|e| format!("Não foi possível escrever {}: {}", path, e))?;
+            println!("formatado: {}", path);
+        }
+    }
+    Ok(if failed { 1 } else { 0 })
+}
+
+/// Precedência usada para decidir onde os parênteses são necessários; segue a do parser
+fn precedence(expr: &Expr) -> u8 {
+    match &expr.kind {
+        ExprKind::Binary { op, .. } => binary_precedence(op),
+        ExprKind::Unary { .. } => 5,
+        ExprKind::FunctionCall { .. } | ExprKind::PropertyAccess { .. } | ExprKind::IndexAccess { .. } => 6,
+        ExprKind::Literal(_) | ExprKind::Variable(_) => 7,
+    }
+}
+
+fn binary_precedence(op: &BinaryOp) -> u8 {
+    match op {
+        BinaryOp::Equals | BinaryOp::NotEquals => 1,
+        BinaryOp::GreaterThan | BinaryOp::LessThan | BinaryOp::GreaterThanOrEquals | BinaryOp::LessThanOrEquals => 2,
+        BinaryOp::Add | BinaryOp::Subtract => 3,
+        BinaryOp::Multiply | BinaryOp::Divide => 4,
+    }
+}
+
+fn binary_symbol(op: &BinaryOp) -> &'static str {
+    match op {
+        BinaryOp::Add => "+",
+        BinaryOp::Subtract => "-",
+        BinaryOp::Multiply => "*",
+        BinaryOp::Divide => "/",
+        BinaryOp::Equals => "==",
+        BinaryOp::NotEquals => "!=",
+        BinaryOp::GreaterThan => ">",
+        BinaryOp::LessThan => "<",
+        BinaryOp::GreaterThanOrEquals => ">=",
+        BinaryOp::LessThanOrEquals => "<=",
+    }
+}
+
+/// Parênteses em volta de cada lado de uma operação binária. O parser agrupa
+/// operadores de mesma precedência pela direita (`a - b - c` é `a - (b - c)`),
+/// então é o lado esquerdo que precisa deles nesse caso.
+fn binary_parens(op: &BinaryOp, left: &Expr, right: &Expr) -> (bool, bool) {
+    let own = binary_precedence(op);
+    (precedence(left) <= own, precedence(right) < own)
+}
+
+/// Alvo de `.`, `()` ou `[]` que precisa de parênteses. Um número também
+/// precisa: `1.x` seria lido como o número `1.` seguido de `x`.
+fn postfix_parens(target: &Expr) -> bool {
+    precedence(target) < 6 || matches!(target.kind, ExprKind::Literal(LiteralValue::Number(_)))
+}
+
+fn width(text: &str) -> usize {
+    text.chars().count()
+}
+
+/// Largura da última linha de um trecho, para saber em que coluna o próximo começa
+fn end_column(start_column: usize, text: &str) -> usize {
+    match text.rfind('\n') {
+        Some(index) => width(&text[index + 1..]),
+        None => start_column + width(text),
+    }
+}
+
+fn wrap(text: String, parens: bool) -> String {
+    if parens { format!("({})", text) } else { text }
+}
+
+/// Um item de uma lista quebrável: argumento, elemento, par de dict ou parâmetro
+enum Item<'e> {
+    Expr(&'e Expr),
+    Pair(&'e Expr, &'e Expr),
+    Text(String, Span),
+}
+
+impl Item<'_> {
+    fn span(&self) -> Span {
+        match self {
+            Item::Expr(expr) => expr.span,
+            Item::Pair(key, value) => Span::new(key.span.start, value.span.end),
+            Item::Text(_, span) => *span,
+        }
+    }
+}
+
+/// Um bloco de `if`/`elif`/`else`: a condição (nenhuma no `else`), o corpo e as chaves
+type ConditionalBlock<'s> = (Option<&'s Expr>, &'s [Stmt], (Span, Span));
+
+struct Formatter<'a> {
+    source: &'a str,
+    /// Todos os tokens do arquivo, para achar as chaves de cada bloco
+    tokens: Vec<(Token, Span)>,
+    comments: Vec<Comment>,
+    /// Primeiro comentário ainda não escrito
+    next_comment: usize,
+    out: String,
+    /// Última linha do código original já escrita, para manter as linhas em branco
+    last_line: usize,
+}
+
+impl<'a> Formatter<'a> {
+    fn line(&mut self, indent: usize, text: &str) {
+        self.out.push_str(&INDENT.repeat(indent));
+        self.out.push_str(text);
+        self.out.push('\n');
+    }
+
+    /// Uma linha em branco se havia uma antes de `line` no original
+    fn blank_line_before(&mut self, line: usize, first: bool) {
+        if !first && line > self.last_line + 1 {
+            self.out.push('\n');
+        }
+    }
+
+    fn peek_comment(&self) -> Option<&Comment> {
+        self.comments.get(self.next_comment)
+    }
+
+    fn take_comment(&mut self) -> Comment {
+        self.next_comment += 1;
+        self.comments[self.next_comment - 1].clone()
+    }
+
+    fn has_comments(&self, span: Span) -> bool {
+        self.comments[self.next_comment..]
+            .iter()
+            .take_while(|comment| comment.span.start.offset < span.end.offset)
+            .any(|comment| comment.span.start.offset >= span.start.offset)
+    }
+
+    /// Escreve, cada um em sua linha, os comentários que começam antes de `before`
+    fn leading_comments(&mut self, before: usize, indent: usize, first: &mut bool) {
+        while self.peek_comment().is_some_and(|comment| comment.span.start.offset < before) {
+            let comment = self.take_comment();
+            self.blank_line_before(comment.span.start.line, *first);
+            self.line(indent, &comment.text);
+            self.last_line = comment.span.end.line;
+            *first = false;
+        }
+    }
+
+    /// Comentário que vem logo depois de `after`, na mesma linha do original
+    /// (separado no máximo por `,` ou `;`), precedido de um espaço
+    fn trailing_comment(&mut self, after: Span) -> String {
+        let Some(comment) = self.peek_comment() else {
+            return String::new();
+        };
+        let start = comment.span.start;
+        let first_token = self.tokens.partition_point(|(_, span)| span.start.offset < after.end.offset);
+        let separated = self.tokens[first_token..]
+            .iter()
+            .take_while(|(_, span)| span.start.offset < start.offset)
+            .any(|(token, _)| !matches!(token, Token::Comma(_) | Token::Semicolon(_)));
+        if start.line == after.end.line && start.offset >= after.end.offset && !separated {
+            format!(" {}", self.take_comment().text)
+        } else {
+            String::new()
+        }
+    }
+
+    /// Chaves `{` e `}` do primeiro bloco que começa depois de `after`
+    fn block_braces(&self, after: usize) -> (Span, Span) {
+        let Some(open) = self
+            .tokens
+            .iter()
+            .position(|(token, span)| matches!(token, Token::LeftBrace(_)) && span.start.offset >= after)
+        else {
+            let end = self.tokens.last().map_or(Span::dummy(), |(_, span)| *span);
+            return (end, end);
+        };
+        let mut depth = 0;
+        for (token, span) in &self.tokens[open..] {
+            match token {
+                Token::LeftBrace(_) => depth += 1,
+                Token::RightBrace(_) => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return (self.tokens[open].1, *span);
+                    }
+                }
+                _ => {}
+            }
+        }
+        let end = self.tokens.last().map_or(Span::dummy(), |(_, span)| *span);
+        (self.tokens[open].1, end)
+    }
+
+    /// Statements de um bloco cuja `{` está em `open`; os comentários que
+    /// começam antes de `until` e sobram depois do último statement ficam no fim do bloco
+    fn block(&mut self, body: &[Stmt], indent: usize, open_line: usize, until: usize) {
+        self.last_line = open_line;
+        let mut first = true;
+        for stmt in body {
+            self.leading_comments(stmt.span.start.offset, indent, &mut first);
+            self.blank_line_before(stmt.span.start.line, first);
+            self.statement(stmt, indent);
+            first = false;
+        }
+        self.leading_comments(until, indent, &mut first);
+    }
+
+    /// Escreve `header {` e o corpo do bloco; o `}` fica para quem chama.
+    /// Um comentário na mesma linha da `{` continua depois dela.
+    fn braced(&mut self, header: String, body: &[Stmt], indent: usize, braces: (Span, Span), until: usize) {
+        let trailing = self.trailing_comment(braces.0);
+        self.line(indent, &format!("{} {{{}", header, trailing));
+        self.block(body, indent + 1, braces.0.start.line, until);
+    }
+
+    fn statement(&mut self, stmt: &Stmt, indent: usize) {
+        let column = indent * INDENT.len();
+        match &stmt.kind {
+            StmtKind::FuncDeclaration(func) => {
+                let braces = self.block_braces(self.function_header_end(func));
+                let header = self.function_header(func, indent);
+                self.braced(header, &func.body, indent, braces, braces.1.start.offset);
+                self.close_block(stmt, indent);
+            }
+            StmtKind::Conditional(cond) => {
+                let mut blocks: Vec<ConditionalBlock> = Vec::new();
+                for block in std::iter::once(&cond.if_block).chain(&cond.elif_blocks) {
+                    let braces = self.block_braces(block.condition.span.end.offset);
+                    blocks.push((Some(&block.condition), &block.body, braces));
+                }
+                if let Some(else_block) = &cond.else_block {
+                    let after = blocks.last().map_or(0, |(_, _, braces)| braces.1.end.offset);
+                    blocks.push((None, else_block, self.block_braces(after)));
+                }
+                for (index, (condition, body, braces)) in blocks.iter().enumerate() {
+                    // O `}` do bloco anterior vai na mesma linha do `elif`/`else`
+                    let keyword = match (index, condition) {
+                        (0, _) => "if",
+                        (_, Some(_)) => "} elif",
+                        (_, None) => "} else",
+                    };
+                    let header = match condition {
+                        Some(condition) => format!("{} {}", keyword, self.expr(condition, indent, column + keyword.len() + 1)),
+                        None => keyword.to_string(),
+                    };
+                    // Comentários entre um `}` e o próximo `elif`/`else` ficam no fim do bloco anterior
+                    let until = blocks.get(index + 1).map_or(braces.1.start.offset, |(_, _, next)| next.0.start.offset);
+                    self.braced(header, body, indent, *braces, until);
+                }
+                self.close_block(stmt, indent);
+            }
+            StmtKind::Loop(LoopStmt::While { condition, body }) => {
+                let header = format!("while {}", self.expr(condition, indent, column + 6));
+                let braces = self.block_braces(condition.span.end.offset);
+                self.braced(header, body, indent, braces, braces.1.start.offset);
+                self.close_block(stmt, indent);
+            }
+            StmtKind::Loop(LoopStmt::For { iterator, iterable, body }) => {
+                let prefix = format!("for {} in ", iterator);
+                let header = format!("{}{}", prefix, self.expr(iterable, indent, column + prefix.len()));
+                let braces = self.block_braces(iterable.span.end.offset);
+                self.braced(header, body, indent, braces, braces.1.start.offset);
+                self.close_block(stmt, indent);
+            }
+            StmtKind::Test { name, name_span, body } => {
+                let braces = self.block_braces(name_span.end.offset);
+                self.braced(format!("test \"{}\"", name), body, indent, braces, braces.1.start.offset);
+                self.close_block(stmt, indent);
+            }
+            _ => {
+                let text = self.simple_statement(stmt, indent);
+                // Comentários no meio de uma expressão que não pôde guardá-los
+                // (ex: entre os operandos de um `+`) vão para depois do statement
+                let mut inner = Vec::new();
+                while self.peek_comment().is_some_and(|comment| comment.span.start.offset < stmt.span.end.offset) {
+                    inner.push(self.take_comment());
+                }
+                let trailing = self.trailing_comment(stmt.span);
+                self.line(indent, &format!("{}{}", text, trailing));
+                for comment in inner {
+                    self.line(indent, &comment.text);
+                }
+                self.last_line = stmt.span.end.line;
+            }
+        }
+    }
+
+    fn close_block(&mut self, stmt: &Stmt, indent: usize) {
+        let trailing = self.trailing_comment(stmt.span);
+        self.line(indent, &format!("}}{}", trailing));
+        self.last_line = stmt.span.end.line;
+    }
+
+    fn function_header_end(&self, func: &FuncDecl) -> usize {
+        func.params
+            .iter()
+            .map(|param| param.span.end.offset)
+            .chain(func.return_type_span.map(|span| span.end.offset))
+            .fold(func.name_span.end.offset, usize::max)
+    }
+
+    fn function_header(&mut self, func: &FuncDecl, indent: usize) -> String {
+        let params: Vec<Item> = func
+            .params
+            .iter()
+            .map(|param| {
+                let text = match param.type_span {
+                    Some(span) => format!("{}: {}", param.name, self.type_text(span)),
+                    None => param.name.clone(),
+                };
+                Item::Text(text, param.span)
+            })
+            .collect();
+        let return_type = match func.return_type_span {
+            Some(span) => format!(": {}", self.type_text(span)),
+            None => String::new(),
+        };
+        let open = format!("fun {}(", func.name);
+        let column = indent * INDENT.len();
+        let end = Span::new(func.name_span.start, func.params.last().map_or(func.name_span.end, |param| param.span.end));
+        let params = self.sequence(&open, ")", &params, indent, column, end, false);
+        format!("{}{}", params, return_type)
+    }
+
+    fn simple_statement(&mut self, stmt: &Stmt, indent: usize) -> String {
+        let column = indent * INDENT.len();
+        let declaration = |this: &mut Self, keyword: &str, name: &str, type_span: Option<Span>, value: &Expr| {
+            let annotation = type_span.map(|span| format!(": {}", this.type_text(span))).unwrap_or_default();
+            let prefix = format!("{} {}{} = ", keyword, name, annotation);
+            let value = this.expr(value, indent, column + width(&prefix));
+            format!("{}{};", prefix, value)
+        };
+        match &stmt.kind {
+            StmtKind::VarDeclaration(decl) => declaration(self, "let", &decl.name, decl.type_span, &decl.value),
+            StmtKind::MutDeclaration(decl) => declaration(self, "mut", &decl.name, decl.type_span, &decl.value),
+            StmtKind::ConstDeclaration(decl) => declaration(self, "const", &decl.name, decl.type_span, &decl.value),
+            // Não são gerados pelo parser; só aparecem em ASTs montados à mão
+            StmtKind::ListDeclaration(decl) => declaration(self, "let", &decl.name, decl.type_span, &decl.value),
+            StmtKind::DictDeclaration(decl) => declaration(self, "let", &decl.name, decl.type_span, &decl.value),
+            StmtKind::ListPush(push) => {
+                let value = self.expr(&push.value, indent, column + push.name.len() + 6);
+                format!("{}.push({});", push.name, value)
+            }
+            StmtKind::DictSet(set) => {
+                let key = self.expr(&set.key, indent, column + set.name.len() + 1);
+                let value = self.expr(&set.value, indent, column + set.name.len() + width(&key) + 5);
+                format!("{}[{}] = {};", set.name, key, value)
+            }
+            StmtKind::VarAssignment(set) => {
+                let prefix = format!("{} = ", set.name);
+                format!("{}{};", prefix, self.expr(&set.value, indent, column + width(&prefix)))
+            }
+            StmtKind::Print(args) => {
+                let items: Vec<Item> = args.iter().map(Item::Expr).collect();
+                format!("{};", self.sequence("print(", ")", &items, indent, column, stmt.span, false))
+            }
+            StmtKind::Input { name, type_span, .. } => format!("input {}: {}", name, self.type_text(*type_span)),
+            StmtKind::Return(expr) => format!("return {};", self.expr(expr, indent, column + 7)),
+            StmtKind::Expression(expr) | StmtKind::FuncCall(expr) => format!("{};", self.expr(expr, indent, column)),
+            StmtKind::Import(path) => format!("import \"{}\";", path),
+            StmtKind::TypeAlias { name, type_span, .. } => format!("type {} = {};", name, self.type_text(*type_span)),
+            StmtKind::FuncDeclaration(_) | StmtKind::Conditional(_) | StmtKind::Loop(_) | StmtKind::Test { .. } => unreachable!("blocos são escritos por `statement`"),
+        }
+    }
+
+    /// Tipo escrito como no original (mantendo aliases e `list` sem parâmetros),
+    /// só com os espaços normalizados: `dict<str, int>`, `{ a: int }`, `int | nil`
+    fn type_text(&self, span: Span) -> String {
+        let original = self.source.get(span.start.offset..span.end.offset).unwrap_or("").trim();
+        let mut tokenizer = Tokenizer::new(original);
+        let mut text = String::new();
+        let mut previous_open_brace = false;
+        loop {
+            let piece = match tokenizer.next_token().0 {
+                Token::Eof(_) => break,
+                Token::Identifier(name, _) => name,
+                Token::List(_) => "list".to_string(),
+                Token::Dict(_) => "dict".to_string(),
+                Token::Nil(_) => "nil".to_string(),
+                Token::Fun(_) => "fun".to_string(),
+                Token::Less(_) => "<".to_string(),
+                Token::Greater(_) => ">".to_string(),
+                Token::LeftParen(_) => "(".to_string(),
+                Token::RightParen(_) => ")".to_string(),
+                Token::Question(_) => "?".to_string(),
+                Token::Comma(_) => ", ".to_string(),
+                Token::Colon(_) => ": ".to_string(),
+                Token::Pipe(_) => " | ".to_string(),
+                Token::LeftBrace(_) => "{ ".to_string(),
+                Token::RightBrace(_) if previous_open_brace => {
+                    text.truncate(text.len() - 1);
+                    "}".to_string()
+                }
+                Token::RightBrace(_) => " }".to_string(),
+                _ => return original.to_string(),
+            };
+            previous_open_brace = piece == "{ ";
+            text.push_str(&piece);
+        }
+        text
+    }
+
+    /// Expressão em uma linha só, sem olhar para a largura
+    fn flat(&self, expr: &Expr) -> String {
+        match &expr.kind {
+            ExprKind::Literal(LiteralValue::Number(n)) => n.to_string(),
+            ExprKind::Literal(LiteralValue::String(s)) => format!("\"{}\"", s),
+            ExprKind::Literal(LiteralValue::Boolean(b)) => b.to_string(),
+            ExprKind::Literal(LiteralValue::Nil) => "nil".to_string(),
+            ExprKind::Literal(LiteralValue::List(items)) => {
+                let items: Vec<String> = items.iter().map(|item| self.flat(item)).collect();
+                format!("[{}]", items.join(", "))
+            }
+            ExprKind::Literal(LiteralValue::Dict(pairs)) => {
+                let pairs: Vec<String> =
+                    pairs.iter().map(|(key, value)| format!("{}: {}", self.flat(key), self.flat(value))).collect();
+                format!("{{{}}}", pairs.join(", "))
+            }
+            ExprKind::Variable(name) => name.clone(),
+            ExprKind::Unary { op: UnaryOp::Negative, expr } => format!("-{}", wrap(self.flat(expr), precedence(expr) < 5)),
+            ExprKind::Binary { op, left, right } => {
+                let (left_parens, right_parens) = binary_parens(op, left, right);
+                format!(
+                    "{} {} {}",
+                    wrap(self.flat(left), left_parens),
+                    binary_symbol(op),
+                    wrap(self.flat(right), right_parens)
+                )
+            }
+            ExprKind::FunctionCall { callee, args } => {
+                let args: Vec<String> = args.iter().map(|arg| self.flat(arg)).collect();
+                format!("{}({})", wrap(self.flat(callee), postfix_parens(callee)), args.join(", "))
+            }
+            ExprKind::PropertyAccess { target, property } => {
+                format!("{}.{}", wrap(self.flat(target), postfix_parens(target)), property)
+            }
+            ExprKind::IndexAccess { target, index } => {
+                format!("{}[{}]", wrap(self.flat(target), postfix_parens(target)), self.flat(index))
+            }
+        }
+    }
+
+    /// Expressão que começa na coluna `column` de uma linha com indentação `indent`
+    fn expr(&mut self, expr: &Expr, indent: usize, column: usize) -> String {
+        match &expr.kind {
+            ExprKind::FunctionCall { callee, args } => {
+                let parens = postfix_parens(callee);
+                let callee = wrap(self.expr(callee, indent, column + usize::from(parens)), parens);
+                let items: Vec<Item> = args.iter().map(Item::Expr).collect();
+                let open = format!("{}(", callee);
+                return self.sequence(&open, ")", &items, indent, column, expr.span, false);
+            }
+            ExprKind::Literal(LiteralValue::List(elements)) => {
+                let items: Vec<Item> = elements.iter().map(Item::Expr).collect();
+                return self.sequence("[", "]", &items, indent, column, expr.span, false);
+            }
+            ExprKind::Literal(LiteralValue::Dict(pairs)) => {
+                let items: Vec<Item> = pairs.iter().map(|(key, value)| Item::Pair(key, value)).collect();
+                return self.sequence("{", "}", &items, indent, column, expr.span, false);
+            }
+            _ => {}
+        }
+
+        let flat = self.flat(expr);
+        if !self.has_comments(expr.span) && column + width(&flat) <= MAX_WIDTH {
+            return flat;
+        }
+        match &expr.kind {
+            ExprKind::Unary { op: UnaryOp::Negative, expr: operand } => {
+                let parens = precedence(operand) < 5;
+                format!("-{}", wrap(self.expr(operand, indent, column + 1 + usize::from(parens)), parens))
+            }
+            ExprKind::Binary { op, left, right } => {
+                let (left_parens, right_parens) = binary_parens(op, left, right);
+                let left = wrap(self.expr(left, indent, column + usize::from(left_parens)), left_parens);
+                let prefix = format!("{} {} ", left, binary_symbol(op));
+                let right_column = end_column(column, &prefix) + usize::from(right_parens);
+                format!("{}{}", prefix, wrap(self.expr(right, indent, right_column), right_parens))
+            }
+            ExprKind::PropertyAccess { target, property } => {
+                let parens = postfix_parens(target);
+                format!("{}.{}", wrap(self.expr(target, indent, column + usize::from(parens)), parens), property)
+            }
+            ExprKind::IndexAccess { target, index } => {
+                let parens = postfix_parens(target);
+                let target = wrap(self.expr(target, indent, column + usize::from(parens)), parens);
+                let index_column = end_column(column, &target) + 1;
+                format!("{}[{}]", target, self.expr(index, indent, index_column))
+            }
+            _ => flat,
+        }
+    }
+
+    fn item(&mut self, item: &Item, indent: usize, column: usize) -> String {
+        match item {
+            Item::Expr(expr) => self.expr(expr, indent, column),
+            Item::Pair(key, value) => {
+                let key = self.expr(key, indent, column);
+                let value_column = end_column(column, &key) + 2;
+                format!("{}: {}", key, self.expr(value, indent, value_column))
+            }
+            Item::Text(text, _) => text.clone(),
+        }
+    }
+
+    fn flat_item(&self, item: &Item) -> String {
+        match item {
+            Item::Expr(expr) => self.flat(expr),
+            Item::Pair(key, value) => format!("{}: {}", self.flat(key), self.flat(value)),
+            Item::Text(text, _) => text.clone(),
+        }
+    }
+
+    /// `open` + itens separados por vírgula + `close`. Se não cabe na linha (ou
+    /// tem comentários dentro), cada item vai para uma linha; um dict ou lista no
+    /// último item fica colado aos delimitadores, como em `f(a, {` ... `})`.
+    #[allow(clippy::too_many_arguments)]
+    fn sequence(&mut self, open: &str, close: &str, items: &[Item], indent: usize, column: usize, span: Span, force: bool) -> String {
+        let flat_items: Vec<String> = items.iter().map(|item| self.flat_item(item)).collect();
+        let flat = format!("{}{}{}", open, flat_items.join(", "), close);
+        if !force && !self.has_comments(span) && end_column(column, &flat) <= MAX_WIDTH {
+            return flat;
+        }
+        if items.is_empty() {
+            return flat;
+        }
+
+        if let Some((Item::Expr(last), rest)) = items.split_last() {
+            let collection = matches!(&last.kind, ExprKind::Literal(LiteralValue::List(items)) if !items.is_empty())
+                || matches!(&last.kind, ExprKind::Literal(LiteralValue::Dict(pairs)) if !pairs.is_empty());
+            let rest_has_comments = self.has_comments(Span::new(span.start, last.span.start))
+                || self.has_comments(Span::new(last.span.end, span.end));
+            let prefix = format!("{}{}", open, flat_items[..rest.len()].iter().map(|item| format!("{}, ", item)).collect::<String>());
+            if collection && !rest_has_comments && end_column(column, &prefix) < MAX_WIDTH {
+                let last_column = end_column(column, &prefix);
+                let last = match &last.kind {
+                    ExprKind::Literal(LiteralValue::List(elements)) => {
+                        let items: Vec<Item> = elements.iter().map(Item::Expr).collect();
+                        self.sequence("[", "]", &items, indent, last_column, last.span, true)
+                    }
+                    ExprKind::Literal(LiteralValue::Dict(pairs)) => {
+                        let items: Vec<Item> = pairs.iter().map(|(key, value)| Item::Pair(key, value)).collect();
+                        self.sequence("{", "}", &items, indent, last_column, last.span, true)
+                    }
+                    _ => unreachable!(),
+                };
+                return format!("{}{}{}", prefix, last, close);
+            }
+        }
+
+        let inner = INDENT.repeat(indent + 1);
+        let inner_column = (indent + 1) * INDENT.len();
+        let mut text = format!("{}\n", open);
+        for (index, item) in items.iter().enumerate() {
+            let item_span = item.span();
+            while self.peek_comment().is_some_and(|comment| comment.span.start.offset < item_span.start.offset) {
+                let comment = self.take_comment();
+                text.push_str(&format!("{}{}\n", inner, comment.text));
+            }
+            let rendered = self.item(item, indent + 1, inner_column);
+            let comma = if index + 1 < items.len() { "," } else { "" };
+            let trailing = self.trailing_comment(item_span);
+            text.push_str(&format!("{}{}{}{}\n", inner, rendered, comma, trailing));
+        }
+        while self.peek_comment().is_some_and(|comment| comment.span.start.offset < span.end.offset) {
+            let comment = self.take_comment();
+            text.push_str(&format!("{}{}\n", inner, comment.text));
+        }
+        text.push_str(&INDENT.repeat(indent));
+        text.push_str(close);
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::format_source;
+
+    fn assert_unchanged(source: &str) {
+        assert_eq!(format_source(source).unwrap(), source);
+    }
+
+    #[test]
+    fn keeps_leading_and_trailing_comments() {
+        assert_unchanged("// topo\nlet a = 1; // um\n\n// antes de b\nlet b = [\n    1, // primeiro\n    2\n];\n");
+    }
+
+    #[test]
+    fn keeps_comment_at_end_of_block() {
+        assert_unchanged("while true {\n    print(1);\n    // fim do laço\n}\n");
+    }
+
+    #[test]
+    fn keeps_comment_after_opening_brace() {
+        assert_unchanged("fun f(a, b) { // nota\n    return a + b;\n}\n");
+        assert_unchanged("if x { // sim\n    print(1);\n} else { // não\n    print(2);\n}\n");
+        assert_eq!(format_source("fun g() {   // nota\nreturn 1;\n}\n").unwrap(), "fun g() { // nota\n    return 1;\n}\n");
+    }
+}
@@ -2,30 +2,78 @@ use crate::ast::{Program, Stmt, StmtKind, Expr, ExprKind, LiteralValue, BinaryOp
 use crate::symbol_table::{SymbolTable, Symbol};
 use crate::types::Type;
 use crate::value::Value;
+use crate::optimizer::{optimize, OptLevel};
+use crate::runtime_error::RuntimeError;
 use std::collections::HashMap;
 use std::io;
+use std::sync::{Arc, Mutex};
 
 pub enum InterpretResult {
     Ok,
-    RuntimeError(String),
+    RuntimeError(RuntimeError),
 }
 
+/// Profundidade máxima de chamadas padrão
+pub const DEFAULT_MAX_CALL_DEPTH: usize = 10_000;
+
+/// Pilha (em bytes) reservada para cada nível de chamada do Snask, com folga
+const STACK_BYTES_PER_CALL: usize = 64 * 1024;
+
+/// Maior pilha pedida ao sistema; acima disso, a profundidade máxima é a que cabe nela
+const MAX_STACK_BYTES: usize = 1024 * 1024 * 1024;
+
 // Internal control flow for the interpreter
 enum ControlFlow {
     Continue,
     Return(Value),
-    Error(String),
+    /// `return f(...)`: a chamada é feita pelo chamador depois de desempilhar a função atual
+    TailCall(FuncDecl, Vec<Value>),
+    Error(RuntimeError),
+}
+
+/// Observa a execução do programa, como o depurador (`snask debug`), o profiler
+/// e a cobertura. Enquanto um destes métodos roda, o interpretador não chama os
+/// tracers de novo, então eles podem avaliar expressões com `Interpreter::evaluate`.
+pub trait Tracer: Send {
+    /// Antes de executar cada statement
+    fn statement(&mut self, _interpreter: &mut Interpreter, _statement: &Stmt) {}
+    /// Ao entrar em uma função Snask, com os parâmetros já definidos
+    fn enter_function(&mut self, _interpreter: &mut Interpreter, _function: &FuncDecl) {}
+    /// Ao sair de uma função Snask, também por erro ou chamada de cauda
+    fn exit_function(&mut self, _interpreter: &mut Interpreter, _function: &FuncDecl) {}
+    /// Antes de chamar uma função nativa (`math.sqrt`, `blaze_db.query`, ...)
+    fn enter_native(&mut self, _name: &str) {}
+    /// Depois que a função nativa retorna
+    fn exit_native(&mut self, _name: &str) {}
+    /// Texto de um `print`; `false` deixa o interpretador escrevê-lo na saída padrão
+    fn output(&mut self, _text: &str) -> bool {
+        false
+    }
 }
 
 #[derive(Clone)]
 pub struct Interpreter {
     globals: SymbolTable,
+    opt_level: OptLevel,
+    /// Arquivo em execução, usado nos frames da pilha de chamadas
+    current_file: String,
+    /// Arquivo onde cada função foi declarada
+    function_files: HashMap<String, String>,
+    call_depth: usize,
+    max_call_depth: usize,
+    tracers: Vec<Arc<Mutex<dyn Tracer>>>,
 }
 
 impl Interpreter {
     pub fn new() -> Self {
         let mut interpreter = Interpreter {
             globals: SymbolTable::new(),
+            opt_level: OptLevel::None,
+            current_file: "<repl>".to_string(),
+            function_files: HashMap::new(),
+            call_depth: 0,
+            max_call_depth: DEFAULT_MAX_CALL_DEPTH,
+            tracers: Vec::new(),
         };
         crate::stdlib::register_stdlib(&mut interpreter.globals);
         interpreter
@@ -35,26 +83,58 @@ impl Interpreter {
         &mut self.globals
     }
 
+    /// Define o nível de otimização aplicado aos módulos importados em runtime
+    pub fn set_opt_level(&mut self, level: OptLevel) {
+        self.opt_level = level;
+    }
+
+    /// Define o arquivo do programa principal, exibido nos erros de execução
+    pub fn set_file(&mut self, file: &str) {
+        self.current_file = file.to_string();
+    }
+
+    /// Define a profundidade máxima de chamadas antes de um erro de estouro de pilha
+    pub fn set_max_call_depth(&mut self, depth: usize) {
+        self.max_call_depth = depth;
+    }
+
+    /// Instala um tracer, chamado durante toda a execução (depois dos já instalados)
+    pub fn add_tracer(&mut self, tracer: Arc<Mutex<dyn Tracer>>) {
+        self.tracers.push(tracer);
+    }
+
+    /// Arquivo do código em execução no momento
+    pub fn current_file(&self) -> &str {
+        &self.current_file
+    }
+
+    pub fn get_globals(&self) -> &SymbolTable {
+        &self.globals
+    }
+
+    /// Avalia uma expressão no escopo atual (usado pelo depurador)
+    pub fn evaluate(&mut self, expression: Expr) -> Result<Value, RuntimeError> {
+        self.evaluate_expression(expression)
+    }
+
+    /// Chama os tracers, sem deixá-los serem chamados de novo enquanto isso
+    fn trace(&mut self, mut f: impl FnMut(&mut dyn Tracer, &mut Interpreter)) {
+        let tracers = std::mem::take(&mut self.tracers);
+        for tracer in &tracers {
+            f(&mut *tracer.lock().unwrap_or_else(|e| e.into_inner()), self);
+        }
+        self.tracers = tracers;
+    }
+
+    fn function_file(&self, name: &str) -> String {
+        self.function_files.get(name).cloned().unwrap_or_else(|| self.current_file.clone())
+    }
+
     /// Chama uma função diretamente por Value, útil para chamadas de runtime
     pub fn call_function_by_value(&mut self, func_val: Value, args: Vec<Value>) -> Result<Value, String> {
         match func_val {
             Value::Function(func_decl) => {
-                if args.len() != func_decl.params.len() {
-                    return Err(format!("Número incorreto de argumentos para a função '{}'. Esperado {}, encontrado {}.", func_decl.name, func_decl.params.len(), args.len()));
-                }
-
-                self.globals.enter_scope();
-                for (i, (param_name, _param_type)) in func_decl.params.iter().enumerate() {
-                    self.globals.define(param_name.clone(), args[i].clone(), false, false);
-                }
-                let result = self.execute_block(func_decl.body.clone());
-                self.globals.exit_scope();
-
-                match result {
-                    ControlFlow::Return(val) => Ok(val),
-                    ControlFlow::Error(e) => Err(e),
-                    ControlFlow::Continue => Ok(Value::Nil), // Function finished without return
-                }
+                self.call_user_function(func_decl, args).map_err(|e| e.message)
             },
             Value::NativeFunction(func) => {
                 func(args, self)
@@ -63,53 +143,126 @@ impl Interpreter {
         }
     }
 
+    /// Executa o programa em uma thread com pilha suficiente para `max_call_depth` chamadas
     pub fn interpret(&mut self, program: Program) -> InterpretResult {
+        self.with_call_stack(|interpreter| interpreter.interpret_program(program))
+            .unwrap_or_else(InterpretResult::RuntimeError)
+    }
+
+    /// Como `interpret`, mas, se o último statement for uma expressão, devolve
+    /// o valor dela (usado pelo REPL para mostrar o resultado)
+    pub fn interpret_value(&mut self, mut program: Program) -> Result<Option<Value>, RuntimeError> {
+        let last = match program.last().map(|stmt| &stmt.kind) {
+            Some(StmtKind::Expression(_)) | Some(StmtKind::FuncCall(_)) => program.pop(),
+            _ => None,
+        };
+        self.with_call_stack(|interpreter| {
+            if let InterpretResult::RuntimeError(e) = interpreter.interpret_program(program) {
+                return Err(e);
+            }
+            let Some(Stmt { kind: StmtKind::Expression(expr) | StmtKind::FuncCall(expr), span }) = last else {
+                return Ok(None);
+            };
+            interpreter
+                .evaluate_expression(expr)
+                .map(Some)
+                .map_err(|e| e.at(span).with_frame("<principal>", &interpreter.current_file))
+        })?
+    }
+
+    /// Roda `f` em uma thread com pilha suficiente para `call_depth_limit` chamadas
+    pub fn with_call_stack<T: Send>(&mut self, f: impl FnOnce(&mut Interpreter) -> T + Send) -> Result<T, RuntimeError> {
+        let stack_size = self.call_depth_limit().saturating_mul(STACK_BYTES_PER_CALL).max(8 * 1024 * 1024);
+        std::thread::scope(|scope| {
+            let thread = std::thread::Builder::new()
+                .name("snask-interpreter".to_string())
+                .stack_size(stack_size)
+                .spawn_scoped(scope, || f(self))
+                .map_err(|e| {
+                    RuntimeError::new(format!(
+                        "não foi possível criar a thread do interpretador com {} MiB de pilha: {}",
+                        stack_size / (1024 * 1024),
+                        e
+                    ))
+                })?;
+            Ok(thread.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic)))
+        })
+    }
+
+    /// Profundidade máxima de chamadas de fato: `max_call_depth`, limitada ao que cabe em `MAX_STACK_BYTES`
+    fn call_depth_limit(&self) -> usize {
+        self.max_call_depth.min(MAX_STACK_BYTES / STACK_BYTES_PER_CALL)
+    }
+
+    fn interpret_program(&mut self, program: Program) -> InterpretResult {
         for statement in program {
             match self.execute_statement(statement) {
                 ControlFlow::Continue => continue,
-                ControlFlow::Return(_) => return InterpretResult::RuntimeError("Unexpected return statement at top level.".to_string()),
-                ControlFlow::Error(msg) => return InterpretResult::RuntimeError(msg),
+                ControlFlow::Return(_) | ControlFlow::TailCall(..) => return InterpretResult::RuntimeError(RuntimeError::new("Unexpected return statement at top level.".to_string())),
+                ControlFlow::Error(e) => return InterpretResult::RuntimeError(e.with_frame("<principal>", &self.current_file)),
             }
         }
         InterpretResult::Ok
     }
 
     fn execute_statement(&mut self, statement: Stmt) -> ControlFlow {
+        if !self.tracers.is_empty() {
+            self.trace(|tracer, interpreter| tracer.statement(interpreter, &statement));
+        }
+        let span = statement.span;
+        match self.execute_statement_kind(statement) {
+            ControlFlow::Error(e) => ControlFlow::Error(e.at(span)),
+            flow => flow,
+        }
+    }
+
+    fn execute_statement_kind(&mut self, statement: Stmt) -> ControlFlow {
         match statement.kind {
             StmtKind::VarDeclaration(var_decl) => self.execute_var_declaration(var_decl),
             StmtKind::MutDeclaration(mut_decl) => self.execute_mut_declaration(mut_decl),
             StmtKind::ConstDeclaration(const_decl) => self.execute_const_declaration(const_decl),
             StmtKind::VarAssignment(var_set) => self.execute_var_assignment(var_set),
             StmtKind::Print(expressions) => self.execute_print_statement(expressions),
-            StmtKind::Input { name, var_type } => self.execute_input_statement(name, var_type),
+            StmtKind::Input { name, var_type, .. } => self.execute_input_statement(name, var_type),
             StmtKind::Conditional(conditional) => self.execute_conditional_statement(conditional),
             StmtKind::Loop(loop_stmt) => self.execute_loop_statement(loop_stmt),
             StmtKind::FuncDeclaration(func_decl) => self.execute_func_declaration(func_decl),
             StmtKind::Return(expr) => self.execute_return_statement(expr),
-            StmtKind::FuncCall(expr) => {
+            StmtKind::FuncCall(expr) | StmtKind::Expression(expr) => {
                 match self.evaluate_expression(expr) {
                     Ok(_) => ControlFlow::Continue,
                     Err(e) => ControlFlow::Error(e),
                 }
             },
+            StmtKind::TypeAlias { .. } => ControlFlow::Continue, // Só usado pela análise semântica
+            StmtKind::Test { .. } => ControlFlow::Continue, // Só executado por `snask test`
             StmtKind::Import(path) => {
-                if path == "blaze" || path == "collections" { // Handle standard library module
+                if crate::modules::is_native_module(&path) { // Handle standard library module
                     ControlFlow::Continue
                 } else { // Handle .snask files
                     match crate::modules::load_module(&path) {
-                        Ok(module_program) => self.execute_block(module_program),
-                        Err(e) => ControlFlow::Error(e),
+                        Ok(module_program) => {
+                            let (module_program, _) = optimize(module_program, self.opt_level);
+                            let caller_file = std::mem::replace(&mut self.current_file, path.clone());
+                            let result = self.execute_block(module_program);
+                            self.current_file = caller_file;
+                            match result {
+                                ControlFlow::Error(e) => ControlFlow::Error(e.with_frame("<módulo>", &path)),
+                                flow => flow,
+                            }
+                        }
+                        Err(e) => ControlFlow::Error(e.into()),
                     }
                 }
             },
-            _ => ControlFlow::Error(format!("Statement not yet implemented: {:?}", statement.kind)),
+            _ => ControlFlow::Error(format!("Statement not yet implemented: {:?}", statement.kind).into()),
         }
     }
 
     fn execute_input_statement(&mut self, name: String, var_type: Type) -> ControlFlow {
         let mut input = String::new();
         if io::stdin().read_line(&mut input).is_err() {
-            return ControlFlow::Error("Não foi possível ler a entrada do console.".to_string());
+            return ControlFlow::Error("Não foi possível ler a entrada do console.".to_string().into());
         }
         let trimmed_input = input.trim();
 
@@ -117,27 +270,32 @@ impl Interpreter {
             Type::String => Value::String(trimmed_input.to_string()),
             Type::Float => match trimmed_input.parse::<f64>() {
                 Ok(n) => Value::Number(n),
-                Err(_) => return ControlFlow::Error(format!("Entrada inválida. Esperado um número float, mas recebeu '{}'.", trimmed_input)),
+                Err(_) => return ControlFlow::Error(format!("Entrada inválida. Esperado um número float, mas recebeu '{}'.", trimmed_input).into()),
             },
             Type::Int => match trimmed_input.parse::<i64>() {
                 Ok(n) => Value::Number(n as f64),
-                Err(_) => return ControlFlow::Error(format!("Entrada inválida. Esperado um número inteiro, mas recebeu '{}'.", trimmed_input)),
+                Err(_) => return ControlFlow::Error(format!("Entrada inválida. Esperado um número inteiro, mas recebeu '{}'.", trimmed_input).into()),
             },
             Type::Bool => match trimmed_input.parse::<bool>() {
                 Ok(b) => Value::Boolean(b),
-                Err(_) => return ControlFlow::Error(format!("Entrada inválida. Esperado 'true' ou 'false', mas recebeu '{}'.", trimmed_input)),
+                Err(_) => return ControlFlow::Error(format!("Entrada inválida. Esperado 'true' ou 'false', mas recebeu '{}'.", trimmed_input).into()),
             },
-            _ => return ControlFlow::Error(format!("Tipo de 'input' não suportado: {:?}", var_type)),
+            _ => return ControlFlow::Error(format!("Tipo de 'input' não suportado: {:?}", var_type).into()),
         };
 
         self.globals.define(name, value, true, true);
         ControlFlow::Continue
     }
 
-    fn evaluate_expression(&mut self, expression: Expr) -> Result<Value, String> {
-        match expression.kind {
+    fn evaluate_expression(&mut self, expression: Expr) -> Result<Value, RuntimeError> {
+        let span = expression.span;
+        self.evaluate_expression_kind(expression.kind).map_err(|e| e.at(span))
+    }
+
+    fn evaluate_expression_kind(&mut self, kind: ExprKind) -> Result<Value, RuntimeError> {
+        match kind {
             ExprKind::Literal(literal) => Ok(self.evaluate_literal(literal)),
-            ExprKind::Variable(name) => self.evaluate_variable(name),
+            ExprKind::Variable(name) => Ok(self.evaluate_variable(name)?),
             ExprKind::Unary { op, expr } => self.evaluate_unary_expression(op, *expr),
             ExprKind::Binary { op, left, right } => self.evaluate_binary_expression(op, *left, *right),
             ExprKind::FunctionCall { callee, args } => self.evaluate_function_call(*callee, args),
@@ -149,15 +307,15 @@ impl Interpreter {
                         let prop_key = Value::String(property.clone());
                         dict.get(&prop_key)
                             .cloned()
-                            .ok_or_else(|| format!("Propriedade '{}' não encontrada no objeto.", property))
+                            .ok_or_else(|| format!("Propriedade '{}' não encontrada no objeto.", property).into())
                     },
-                    _ => Err(format!("Tentativa de acessar propriedade '{}' em tipo não-objeto: {:?}", property, obj_val)),
+                    _ => Err(format!("Tentativa de acessar propriedade '{}' em tipo não-objeto: {:?}", property, obj_val).into()),
                 }
             }
         }
     }
 
-    fn evaluate_index_access(&mut self, target: Expr, index: Expr) -> Result<Value, String> {
+    fn evaluate_index_access(&mut self, target: Expr, index: Expr) -> Result<Value, RuntimeError> {
         let target_val = self.evaluate_expression(target)?;
         let index_val = self.evaluate_expression(index)?;
 
@@ -168,20 +326,20 @@ impl Interpreter {
                     if idx < list.len() {
                         Ok(list[idx].clone())
                     } else {
-                        Err(format!("Erro de tempo de execução: Índice fora dos limites da lista. Tamanho: {}, Índice: {}", list.len(), idx))
+                        Err(format!("Erro de tempo de execução: Índice fora dos limites da lista. Tamanho: {}, Índice: {}", list.len(), idx).into())
                     }
                 } else {
-                    Err(format!("Erro de tempo de execução: Índice de lista não numérico: {:?}", index_val))
+                    Err(format!("Erro de tempo de execução: Índice de lista não numérico: {:?}", index_val).into())
                 }
             },
             Value::Dict(dict) => {
                 if dict.contains_key(&index_val) {
                     Ok(dict[&index_val].clone())
                 } else {
-                    Err(format!("Erro de tempo de execução: Chave de dicionário não encontrada: {:?}", index_val))
+                    Err(format!("Erro de tempo de execução: Chave de dicionário não encontrada: {:?}", index_val).into())
                 }
             },
-            _ => Err(format!("Erro de tempo de execução: Tentativa de indexar valor não indexável: {:?}", target_val)),
+            _ => Err(format!("Erro de tempo de execução: Tentativa de indexar valor não indexável: {:?}", target_val).into()),
         }
     }
 
@@ -260,12 +418,12 @@ impl Interpreter {
         match self.globals.get_mut(&var_set.name) {
             Some(symbol) => {
                 if !symbol.is_reassignable {
-                    return ControlFlow::Error(format!("Variável '{}' não pode ser reatribuída (é constante).", var_set.name));
+                    return ControlFlow::Error(format!("Variável '{}' não pode ser reatribuída (é constante).", var_set.name).into());
                 }
                 symbol.value = value;
                 ControlFlow::Continue
             },
-            None => ControlFlow::Error(format!("Variável '{}' não encontrada para atribuição.", var_set.name)),
+            None => ControlFlow::Error(format!("Variável '{}' não encontrada para atribuição.", var_set.name).into()),
         }
     }
 
@@ -282,28 +440,32 @@ impl Interpreter {
                 Err(e) => return ControlFlow::Error(e),
             }
         }
-        println!("{}", output);
+        let mut handled = false;
+        self.trace(|tracer, _| handled |= tracer.output(&output));
+        if !handled {
+            println!("{}", output);
+        }
         ControlFlow::Continue
     }
 
-    fn evaluate_unary_expression(&mut self, op: UnaryOp, expr: Expr) -> Result<Value, String> {
+    fn evaluate_unary_expression(&mut self, op: UnaryOp, expr: Expr) -> Result<Value, RuntimeError> {
         let right = self.evaluate_expression(expr)?;
         match op {
             UnaryOp::Negative => {
                 if let Value::Number(n) = right {
                     Ok(Value::Number(-n))
                 } else {
-                    Err(format!("Operador unário '-' aplicado a tipo não numérico: {:?}", right))
+                    Err(format!("Operador unário '-' aplicado a tipo não numérico: {:?}", right).into())
                 }
             }
         }
     }
 
-    fn evaluate_binary_expression(&mut self, op: BinaryOp, left: Expr, right: Expr) -> Result<Value, String> {
+    fn evaluate_binary_expression(&mut self, op: BinaryOp, left: Expr, right: Expr) -> Result<Value, RuntimeError> {
         let left_val = self.evaluate_expression(left)?;
         let right_val = self.evaluate_expression(right)?;
 
-        match op {
+        let result = match op {
             BinaryOp::Add => self.add_values(left_val, right_val),
             BinaryOp::Subtract => self.subtract_values(left_val, right_val),
             BinaryOp::Multiply => self.multiply_values(left_val, right_val),
@@ -314,7 +476,8 @@ impl Interpreter {
             BinaryOp::LessThan => self.compare_values(left_val, right_val, |a, b| a < b),
             BinaryOp::GreaterThanOrEquals => self.compare_values(left_val, right_val, |a, b| a >= b),
             BinaryOp::LessThanOrEquals => self.compare_values(left_val, right_val, |a, b| a <= b),
-        }
+        };
+        Ok(result?)
     }
 
     fn add_values(&self, left: Value, right: Value) -> Result<Value, String> {
@@ -368,7 +531,7 @@ impl Interpreter {
         match self.evaluate_expression(conditional.if_block.condition) {
             Ok(Value::Boolean(true)) => return self.execute_block(conditional.if_block.body),
             Ok(Value::Boolean(false)) => {},
-            Ok(_) => return ControlFlow::Error("Condição do 'if' deve ser booleana.".to_string()),
+            Ok(_) => return ControlFlow::Error("Condição do 'if' deve ser booleana.".to_string().into()),
             Err(e) => return ControlFlow::Error(e),
         }
 
@@ -376,7 +539,7 @@ impl Interpreter {
             match self.evaluate_expression(block.condition) {
                 Ok(Value::Boolean(true)) => return self.execute_block(block.body),
                 Ok(Value::Boolean(false)) => {},
-                Ok(_) => return ControlFlow::Error("Condição do 'elif' deve ser booleana.".to_string()),
+                Ok(_) => return ControlFlow::Error("Condição do 'elif' deve ser booleana.".to_string().into()),
                 Err(e) => return ControlFlow::Error(e),
             }
         }
@@ -399,18 +562,17 @@ impl Interpreter {
                             self.globals.exit_scope();
                             match result {
                                 ControlFlow::Continue => continue,
-                                ControlFlow::Return(val) => return ControlFlow::Return(val),
-                                ControlFlow::Error(e) => return ControlFlow::Error(e),
+                                flow => return flow,
                             }
                         },
                         Ok(Value::Boolean(false)) => break,
-                        Ok(_) => return ControlFlow::Error("Condição do 'while' deve ser booleana.".to_string()),
+                        Ok(_) => return ControlFlow::Error("Condição do 'while' deve ser booleana.".to_string().into()),
                         Err(e) => return ControlFlow::Error(e),
                     }
                 }
                 ControlFlow::Continue
             },
-            _ => ControlFlow::Error(format!("Loop statement not yet implemented: {:?}", loop_stmt)),
+            _ => ControlFlow::Error(format!("Loop statement not yet implemented: {:?}", loop_stmt).into()),
         }
     }
 
@@ -418,55 +580,121 @@ impl Interpreter {
         for statement in statements {
             match self.execute_statement(statement) {
                 ControlFlow::Continue => continue,
-                ControlFlow::Return(val) => return ControlFlow::Return(val),
-                ControlFlow::Error(e) => return ControlFlow::Error(e),
+                flow => return flow,
             }
         }
         ControlFlow::Continue
     }
 
     fn execute_func_declaration(&mut self, func_decl: FuncDecl) -> ControlFlow {
+        self.function_files.insert(func_decl.name.clone(), self.current_file.clone());
         self.globals.define(func_decl.name.clone(), Value::Function(func_decl), false, false);
         ControlFlow::Continue
     }
 
-    fn evaluate_function_call(&mut self, callee: Expr, args: Vec<Expr>) -> Result<Value, String> {
+    fn evaluate_function_call(&mut self, callee: Expr, args: Vec<Expr>) -> Result<Value, RuntimeError> {
+        let callee_name = callee_name(&callee);
         let func_val = self.evaluate_expression(callee)?;
+        let mut evaluated_args = Vec::new();
+        for arg in args {
+            evaluated_args.push(self.evaluate_expression(arg)?);
+        }
         match func_val {
-            Value::Function(func_decl) => {
-                if args.len() != func_decl.params.len() {
-                    return Err(format!("Número incorreto de argumentos para a função '{}'. Esperado {}, encontrado {}.", func_decl.name, func_decl.params.len(), args.len()));
-                }
+            Value::Function(func_decl) => self.call_user_function(func_decl, evaluated_args),
+            Value::NativeFunction(func) => Ok(self.call_native(func, &callee_name, evaluated_args)?),
+            _ => Err(format!("Tentativa de chamar um valor não-invocável: {:?}", func_val).into())
+        }
+    }
 
-                self.globals.enter_scope();
-                for (i, (param_name, _param_type)) in func_decl.params.iter().enumerate() {
-                    let arg_value = self.evaluate_expression(args[i].clone())?;
-                    self.globals.define(param_name.clone(), arg_value, false, false);
-                }
-                let result = self.execute_block(func_decl.body.clone());
-                self.globals.exit_scope();
+    fn call_native(&mut self, func: NativeFunction, name: &str, args: Vec<Value>) -> Result<Value, String> {
+        if self.tracers.is_empty() {
+            return func(args, self);
+        }
+        self.trace(|tracer, _| tracer.enter_native(name));
+        let result = func(args, self);
+        self.trace(|tracer, _| tracer.exit_native(name));
+        result
+    }
 
-                match result {
-                    ControlFlow::Return(val) => Ok(val),
-                    ControlFlow::Error(e) => Err(e),
-                    ControlFlow::Continue => Ok(Value::Nil), // Function finished without return
-                }
-            },
-            Value::NativeFunction(func) => {
-                let mut evaluated_args = Vec::new();
-                for arg in args {
-                    evaluated_args.push(self.evaluate_expression(arg)?);
-                }
-                func(evaluated_args, self)
-            },
-            _ => Err(format!("Tentativa de chamar um valor não-invocável: {:?}", func_val))
+    /// Executa uma função Snask. Chamadas em posição de cauda (`return f(...)`)
+    /// reaproveitam este mesmo nível em vez de aumentar a pilha.
+    fn call_user_function(&mut self, mut func_decl: FuncDecl, mut args: Vec<Value>) -> Result<Value, RuntimeError> {
+        if self.call_depth >= self.call_depth_limit() {
+            return Err(format!("Estouro de pilha: profundidade máxima de {} chamadas excedida.", self.call_depth_limit()).into());
         }
+
+        self.call_depth += 1;
+        let caller_file = self.current_file.clone();
+        let result = loop {
+            if args.len() != func_decl.params.len() {
+                break Err(format!("Número incorreto de argumentos para a função '{}'. Esperado {}, encontrado {}.", func_decl.name, func_decl.params.len(), args.len()).into());
+            }
+
+            self.globals.enter_scope();
+            for (param, arg_value) in func_decl.params.iter().zip(args) {
+                self.globals.define(param.name.clone(), arg_value, false, false);
+            }
+            self.current_file = self.function_file(&func_decl.name);
+            if !self.tracers.is_empty() {
+                self.trace(|tracer, interpreter| tracer.enter_function(interpreter, &func_decl));
+            }
+            let result = self.execute_block(func_decl.body.clone());
+            self.globals.exit_scope();
+            if !self.tracers.is_empty() {
+                self.trace(|tracer, interpreter| tracer.exit_function(interpreter, &func_decl));
+            }
+
+            match result {
+                ControlFlow::Return(val) => break Ok(val),
+                ControlFlow::TailCall(next, next_args) => {
+                    func_decl = next;
+                    args = next_args;
+                }
+                ControlFlow::Error(e) => break Err(e.with_frame(&func_decl.name, &self.current_file)),
+                ControlFlow::Continue => break Ok(Value::Nil), // Function finished without return
+            }
+        };
+        self.current_file = caller_file;
+        self.call_depth -= 1;
+        result
     }
 
     fn execute_return_statement(&mut self, expr: Expr) -> ControlFlow {
+        if let ExprKind::FunctionCall { callee, args } = expr.kind {
+            return match self.evaluate_tail_call(*callee, args) {
+                Ok(flow) => flow,
+                Err(e) => ControlFlow::Error(e.at(expr.span)),
+            };
+        }
         match self.evaluate_expression(expr) {
             Ok(value) => ControlFlow::Return(value),
             Err(e) => ControlFlow::Error(e),
         }
     }
-}
\ No newline at end of file
+
+    /// Avalia `return f(...)`: funções Snask viram uma chamada de cauda, as nativas são chamadas na hora
+    fn evaluate_tail_call(&mut self, callee: Expr, args: Vec<Expr>) -> Result<ControlFlow, RuntimeError> {
+        let callee_name = callee_name(&callee);
+        let func_val = self.evaluate_expression(callee)?;
+        let mut evaluated_args = Vec::new();
+        for arg in args {
+            evaluated_args.push(self.evaluate_expression(arg)?);
+        }
+        match func_val {
+            Value::Function(func_decl) => Ok(ControlFlow::TailCall(func_decl, evaluated_args)),
+            Value::NativeFunction(func) => Ok(ControlFlow::Return(self.call_native(func, &callee_name, evaluated_args)?)),
+            _ => Err(format!("Tentativa de chamar um valor não-invocável: {:?}", func_val).into())
+        }
+    }
+}
+
+type NativeFunction = fn(Vec<Value>, &mut Interpreter) -> Result<Value, String>;
+
+/// Nome de uma função chamada, como aparece no código (`math.sqrt`)
+fn callee_name(callee: &Expr) -> String {
+    match &callee.kind {
+        ExprKind::Variable(name) => name.clone(),
+        ExprKind::PropertyAccess { target, property } => format!("{}.{}", callee_name(target), property),
+        _ => "<função>".to_string(),
+    }
+}
@@ -0,0 +1,597 @@
+//! Lints: avisos sobre código válido que provavelmente não faz o que se espera.
+//!
+//! Cada regra tem um ID, mostrado no aviso (`aviso[unused-variable]`), que pode
+//! ser desligado com um comentário:
+//!
+//! ```text
+//! let x = 1 // snask-allow: unused-variable
+//! // snask-allow: unused-parameter, shadowed-module   (vale para a próxima linha)
+//! // snask-allow-file: constant-condition            (vale para o arquivo inteiro)
+//! ```
+//!
+//! Variáveis e parâmetros com nome começando por `_` nunca são considerados sem uso.
+
+use crate::ast::{Expr, ExprKind, FuncDecl, LiteralValue, LoopStmt, Program, Stmt, StmtKind};
+use crate::declarations::stdlib_module;
+use crate::diagnostics::{Annotation, Diagnostic, Severity};
+use crate::modules;
+use crate::optimizer::constant_value;
+use crate::span::{Position, Span};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LintRule {
+    UnusedVariable,
+    UnusedParameter,
+    UnusedMut,
+    UnusedImport,
+    ShadowedModule,
+    ConstantCondition,
+    UnknownLint,
+}
+
+impl LintRule {
+    pub const ALL: [LintRule; 7] = [
+        LintRule::UnusedVariable,
+        LintRule::UnusedParameter,
+        LintRule::UnusedMut,
+        LintRule::UnusedImport,
+        LintRule::ShadowedModule,
+        LintRule::ConstantCondition,
+        LintRule::UnknownLint,
+    ];
+
+    pub fn id(&self) -> &'static str {
+        match self {
+            LintRule::UnusedVariable => "unused-variable",
+            LintRule::UnusedParameter => "unused-parameter",
+            LintRule::UnusedMut => "unused-mut",
+            LintRule::UnusedImport => "unused-import",
+            LintRule::ShadowedModule => "shadowed-module",
+            LintRule::ConstantCondition => "constant-condition",
+            LintRule::UnknownLint => "unknown-lint",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<LintRule> {
+        LintRule::ALL.into_iter().find(|rule| rule.id() == id)
+    }
+
+    /// `unused-mut` é só uma sugestão; as demais regras apontam prováveis erros
+    pub fn severity(&self) -> Severity {
+        match self {
+            LintRule::UnusedMut => Severity::Hint,
+            _ => Severity::Warning,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Lint {
+    pub rule: LintRule,
+    pub message: String,
+    pub span: Span,
+    pub label: String,
+    pub help: Option<String>,
+}
+
+impl Lint {
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        let severity = self.rule.severity();
+        let diagnostic = Diagnostic::new(severity, self.message.clone())
+            .with_code(self.rule.id().to_string())
+            .with_annotation(Annotation::new(self.span, Some(self.label.clone()), severity))
+            .with_note(format!("desligue com `// snask-allow: {}`", self.rule.id()));
+        match &self.help {
+            Some(help) => diagnostic.with_help(help.clone()),
+            None => diagnostic,
+        }
+    }
+}
+
+/// Roda todas as regras sobre um programa já analisado, descartando os avisos
+/// desligados por comentários `snask-allow` em `source`
+pub fn lint(program: &Program, source: &str) -> Vec<Lint> {
+    let pragmas = Pragmas::parse(source);
+    let mut linter = Linter {
+        source,
+        scopes: Vec::new(),
+        pending_functions: Vec::new(),
+        imports: Vec::new(),
+        lints: Vec::new(),
+    };
+    linter.enter_scope();
+    linter.walk_block(program);
+    linter.exit_scope();
+    linter.report_unused_imports();
+
+    let mut lints = linter.lints;
+    for (id, span) in &pragmas.unknown {
+        lints.push(Lint {
+            rule: LintRule::UnknownLint,
+            message: format!("a regra '{}' não existe", id),
+            span: *span,
+            label: "regra desconhecida".to_string(),
+            help: Some(format!(
+                "as regras são: {}",
+                LintRule::ALL.iter().map(|rule| rule.id()).collect::<Vec<_>>().join(", ")
+            )),
+        });
+    }
+    lints.retain(|lint| !pragmas.allows(lint.rule, lint.span.start.line));
+    lints.sort_by_key(|lint| lint.span.start.offset);
+    lints
+}
+
+/// Regras desligadas por comentários `snask-allow`
+#[derive(Debug, Default)]
+struct Pragmas {
+    file: Vec<LintRule>,
+    lines: HashMap<usize, Vec<LintRule>>,
+    /// IDs que não correspondem a nenhuma regra, com onde foram escritos
+    unknown: Vec<(String, Span)>,
+}
+
+impl Pragmas {
+    fn parse(source: &str) -> Self {
+        let mut pragmas = Pragmas::default();
+        let lines: Vec<&str> = source.split('\n').collect();
+        let mut line_offset = 0;
+        for (index, line) in lines.iter().enumerate() {
+            let line_number = index + 1;
+            if let Some(comment_start) = comment_start(line) {
+                let comment = &line[comment_start + 2..];
+                let trimmed = comment.trim_start();
+                let (ids, file_wide) = if let Some(ids) = trimmed.strip_prefix("snask-allow-file:") {
+                    (ids, true)
+                } else if let Some(ids) = trimmed.strip_prefix("snask-allow:") {
+                    (ids, false)
+                } else {
+                    line_offset += line.len() + 1;
+                    continue;
+                };
+                // Sozinho na linha, o comentário vale para a próxima linha com código
+                let target = if line[..comment_start].trim().is_empty() {
+                    (index + 1..lines.len()).find(|&next| !lines[next].trim().is_empty()).map_or(line_number + 1, |next| next + 1)
+                } else {
+                    line_number
+                };
+
+                let ids_start = comment_start + 2 + (comment.len() - trimmed.len()) + (trimmed.len() - ids.len());
+                let mut cursor = ids_start;
+                for part in ids.split(',') {
+                    let id = part.trim();
+                    let id_start = cursor + (part.len() - part.trim_start().len());
+                    cursor += part.len() + 1;
+                    if id.is_empty() {
+                        continue;
+                    }
+                    match LintRule::from_id(id) {
+                        Some(rule) if file_wide => pragmas.file.push(rule),
+                        Some(rule) => pragmas.lines.entry(target).or_default().push(rule),
+                        None => {
+                            let column = line[..id_start].chars().count() + 1;
+                            let start = Position::new(line_number, column, line_offset + id_start);
+                            let end = Position::new(line_number, column + id.chars().count(), line_offset + id_start + id.len());
+                            pragmas.unknown.push((id.to_string(), Span::new(start, end)));
+                        }
+                    }
+                }
+            }
+            line_offset += line.len() + 1;
+        }
+        pragmas
+    }
+
+    fn allows(&self, rule: LintRule, line: usize) -> bool {
+        self.file.contains(&rule) || self.lines.get(&line).is_some_and(|rules| rules.contains(&rule))
+    }
+}
+
+/// Posição do `//` que inicia um comentário na linha, ignorando `//` dentro de strings
+fn comment_start(line: &str) -> Option<usize> {
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut previous_slash = false;
+    for (index, c) in line.char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '/' if previous_slash => return Some(index - 1),
+            _ => {}
+        }
+        previous_slash = c == '/';
+    }
+    None
+}
+
+#[derive(Debug, PartialEq)]
+enum BindingKind {
+    Variable,
+    Mutable,
+    Parameter,
+}
+
+#[derive(Debug)]
+struct Binding {
+    name: String,
+    span: Span,
+    kind: BindingKind,
+    used: bool,
+    reassigned: bool,
+}
+
+/// Um `import` e os nomes que ele traz para o programa
+#[derive(Debug)]
+struct Import {
+    path: String,
+    span: Span,
+    names: Vec<String>,
+    used: bool,
+}
+
+struct Linter<'a> {
+    source: &'a str,
+    scopes: Vec<Vec<Binding>>,
+    /// Funções declaradas em cada escopo. Os corpos são verificados no fim do
+    /// escopo, quando já existem todas as variáveis que eles podem usar.
+    pending_functions: Vec<Vec<&'a FuncDecl>>,
+    imports: Vec<Import>,
+    lints: Vec<Lint>,
+}
+
+impl<'a> Linter<'a> {
+    fn enter_scope(&mut self) {
+        self.scopes.push(Vec::new());
+        self.pending_functions.push(Vec::new());
+    }
+
+    fn exit_scope(&mut self) {
+        for func in self.pending_functions.pop().unwrap_or_default() {
+            self.enter_scope();
+            for param in &func.params {
+                self.declare(&param.name, param.span, BindingKind::Parameter);
+            }
+            self.walk_block(&func.body);
+            self.exit_scope();
+        }
+        for binding in self.scopes.pop().unwrap_or_default() {
+            self.report_unused(binding);
+        }
+    }
+
+    fn report_unused(&mut self, binding: Binding) {
+        if binding.name.starts_with('_') {
+            return;
+        }
+        let name = &binding.name;
+        let lint = if !binding.used && binding.kind == BindingKind::Parameter {
+            Lint {
+                rule: LintRule::UnusedParameter,
+                message: format!("o parâmetro '{}' nunca é usado", name),
+                span: binding.span,
+                label: "parâmetro sem uso".to_string(),
+                help: Some(format!("se ele precisa existir (ex: em handlers do Blaze), renomeie para `_{}`", name)),
+            }
+        } else if !binding.used {
+            Lint {
+                rule: LintRule::UnusedVariable,
+                message: format!("a variável '{}' nunca é usada", name),
+                span: binding.span,
+                label: "variável sem uso".to_string(),
+                help: Some(format!("remova a declaração ou renomeie para `_{}`", name)),
+            }
+        } else if binding.kind == BindingKind::Mutable && !binding.reassigned {
+            Lint {
+                rule: LintRule::UnusedMut,
+                message: format!("'{}' é declarada com `mut`, mas nunca é reatribuída", name),
+                span: binding.span,
+                label: "declarada aqui".to_string(),
+                help: Some("declare com `let`".to_string()),
+            }
+        } else {
+            return;
+        };
+        self.lints.push(lint);
+    }
+
+    fn report_unused_imports(&mut self) {
+        for import in &self.imports {
+            if import.used || import.names.is_empty() {
+                continue;
+            }
+            self.lints.push(Lint {
+                rule: LintRule::UnusedImport,
+                message: format!("o import \"{}\" não é usado", import.path),
+                span: import.span,
+                label: "nada deste módulo é usado".to_string(),
+                help: Some("remova o import".to_string()),
+            });
+        }
+    }
+
+    fn declare(&mut self, name: &str, span: Span, kind: BindingKind) {
+        self.check_shadowing(name, span);
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.push(Binding { name: name.to_string(), span, kind, used: false, reassigned: false });
+        }
+    }
+
+    fn check_shadowing(&mut self, name: &str, span: Span) {
+        if stdlib_module(name).is_some() {
+            self.lints.push(Lint {
+                rule: LintRule::ShadowedModule,
+                message: format!("'{}' esconde o módulo nativo '{}'", name, name),
+                span,
+                label: "declarado aqui".to_string(),
+                help: Some(format!("enquanto este nome existir, `{}.…` se refere a ele e não ao módulo; use outro nome", name)),
+            });
+        }
+    }
+
+    fn binding_mut(&mut self, name: &str) -> Option<&mut Binding> {
+        self.scopes.iter_mut().rev().find_map(|scope| scope.iter_mut().rev().find(|binding| binding.name == name))
+    }
+
+    fn use_name(&mut self, name: &str) {
+        if let Some(binding) = self.binding_mut(name) {
+            binding.used = true;
+            return;
+        }
+        for import in &mut self.imports {
+            if import.names.iter().any(|imported| imported == name) {
+                import.used = true;
+            }
+        }
+    }
+
+    fn assign(&mut self, name: &str) {
+        if let Some(binding) = self.binding_mut(name) {
+            binding.reassigned = true;
+        }
+    }
+
+    fn name_span(&self, stmt_span: Span, name: &str) -> Span {
+        name_span(self.source, stmt_span, name)
+    }
+
+    fn walk_block(&mut self, body: &'a [Stmt]) {
+        for stmt in body {
+            self.walk_statement(stmt);
+        }
+    }
+
+    fn walk_scoped_block(&mut self, body: &'a [Stmt]) {
+        self.enter_scope();
+        self.walk_block(body);
+        self.exit_scope();
+    }
+
+    fn walk_statement(&mut self, stmt: &'a Stmt) {
+        match &stmt.kind {
+            StmtKind::VarDeclaration(decl) => self.walk_declaration(&decl.name, &decl.value, stmt.span, BindingKind::Variable),
+            StmtKind::ConstDeclaration(decl) => self.walk_declaration(&decl.name, &decl.value, stmt.span, BindingKind::Variable),
+            StmtKind::ListDeclaration(decl) => self.walk_declaration(&decl.name, &decl.value, stmt.span, BindingKind::Variable),
+            StmtKind::DictDeclaration(decl) => self.walk_declaration(&decl.name, &decl.value, stmt.span, BindingKind::Variable),
+            StmtKind::MutDeclaration(decl) => self.walk_declaration(&decl.name, &decl.value, stmt.span, BindingKind::Mutable),
+            StmtKind::Input { name, .. } => {
+                let span = self.name_span(stmt.span, name);
+                self.declare(name, span, BindingKind::Variable);
+            }
+            StmtKind::VarAssignment(set) => {
+                self.walk_expression(&set.value);
+                self.assign(&set.name);
+            }
+            StmtKind::ListPush(push) => {
+                self.walk_expression(&push.value);
+                self.use_name(&push.name);
+                self.assign(&push.name);
+            }
+            StmtKind::DictSet(set) => {
+                self.walk_expression(&set.key);
+                self.walk_expression(&set.value);
+                self.use_name(&set.name);
+                self.assign(&set.name);
+            }
+            StmtKind::FuncDeclaration(func) => {
+                self.check_shadowing(&func.name, func.name_span);
+                if let Some(pending) = self.pending_functions.last_mut() {
+                    pending.push(func);
+                }
+            }
+            StmtKind::Return(expr) | StmtKind::Expression(expr) | StmtKind::FuncCall(expr) => self.walk_expression(expr),
+            StmtKind::Print(exprs) => {
+                for expr in exprs {
+                    self.walk_expression(expr);
+                }
+            }
+            StmtKind::Conditional(cond) => {
+                for block in std::iter::once(&cond.if_block).chain(&cond.elif_blocks) {
+                    self.check_condition(&block.condition, false);
+                    self.walk_expression(&block.condition);
+                    self.walk_scoped_block(&block.body);
+                }
+                if let Some(else_block) = &cond.else_block {
+                    self.walk_scoped_block(else_block);
+                }
+            }
+            StmtKind::Loop(LoopStmt::While { condition, body }) => {
+                self.check_condition(condition, true);
+                self.walk_expression(condition);
+                self.walk_scoped_block(body);
+            }
+            StmtKind::Loop(LoopStmt::For { iterator, iterable, body }) => {
+                self.walk_expression(iterable);
+                self.enter_scope();
+                let span = self.name_span(stmt.span, iterator);
+                self.declare(iterator, span, BindingKind::Variable);
+                self.walk_block(body);
+                self.exit_scope();
+            }
+            StmtKind::Import(path) => {
+                let names = if modules::is_native_module(path) {
+                    vec![path.clone()]
+                } else {
+                    // Um módulo que não carrega é reportado na execução
+                    modules::load_module(path).map(|program| declared_names(&program)).unwrap_or_default()
+                };
+                self.imports.push(Import { path: path.clone(), span: stmt.span, names, used: false });
+            }
+            StmtKind::TypeAlias { .. } => {}
+            StmtKind::Test { body, .. } => self.walk_scoped_block(body),
+        }
+    }
+
+    fn walk_declaration(&mut self, name: &str, value: &Expr, stmt_span: Span, kind: BindingKind) {
+        self.walk_expression(value);
+        let span = self.name_span(stmt_span, name);
+        self.declare(name, span, kind);
+    }
+
+    fn walk_expression(&mut self, expr: &Expr) {
+        match &expr.kind {
+            ExprKind::Variable(name) => self.use_name(name),
+            ExprKind::Literal(LiteralValue::List(items)) => {
+                for item in items {
+                    self.walk_expression(item);
+                }
+            }
+            ExprKind::Literal(LiteralValue::Dict(pairs)) => {
+                for (key, value) in pairs {
+                    self.walk_expression(key);
+                    self.walk_expression(value);
+                }
+            }
+            ExprKind::Literal(_) => {}
+            ExprKind::Unary { expr, .. } => self.walk_expression(expr),
+            ExprKind::Binary { left, right, .. } => {
+                self.walk_expression(left);
+                self.walk_expression(right);
+            }
+            ExprKind::FunctionCall { callee, args } => {
+                self.walk_expression(callee);
+                for arg in args {
+                    self.walk_expression(arg);
+                }
+            }
+            ExprKind::PropertyAccess { target, .. } => self.walk_expression(target),
+            ExprKind::IndexAccess { target, index } => {
+                self.walk_expression(target);
+                self.walk_expression(index);
+            }
+        }
+    }
+
+    fn check_condition(&mut self, condition: &Expr, is_loop: bool) {
+        // `while true` é a forma de repetir até um `return`
+        if is_loop && matches!(condition.kind, ExprKind::Literal(LiteralValue::Boolean(true))) {
+            return;
+        }
+        if let Some(LiteralValue::Boolean(value)) = constant_value(condition) {
+            let (message, help) = if value {
+                ("a condição é sempre verdadeira", "o bloco sempre é executado; se isso é intencional, remova a condição")
+            } else {
+                ("a condição é sempre falsa", "o bloco nunca é executado; se isso é intencional, remova o bloco")
+            };
+            self.lints.push(Lint {
+                rule: LintRule::ConstantCondition,
+                message: message.to_string(),
+                span: condition.span,
+                label: "não depende de nenhum valor do programa".to_string(),
+                help: Some(help.to_string()),
+            });
+        }
+    }
+}
+
+/// Trecho do nome declarado dentro de um statement (`x` em `let x = ...`),
+/// para não sublinhar o statement inteiro. Procura só na primeira linha do
+/// statement; se não achar, devolve o statement.
+pub fn name_span(source: &str, stmt_span: Span, name: &str) -> Span {
+    let Some(text) = source.get(stmt_span.start.offset..stmt_span.end.offset) else {
+        return stmt_span;
+    };
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    let found = text.match_indices(name).map(|(index, _)| index).find(|&index| {
+        !text[..index].chars().next_back().is_some_and(is_word)
+            && !text[index + name.len()..].chars().next().is_some_and(is_word)
+    });
+    match found {
+        Some(index) if !text[..index].contains('\n') => {
+            let start = stmt_span.start;
+            let column = start.column + text[..index].chars().count();
+            Span::new(
+                Position::new(start.line, column, start.offset + index),
+                Position::new(start.line, column + name.chars().count(), start.offset + index + name.len()),
+            )
+        }
+        _ => stmt_span,
+    }
+}
+
+/// Nomes que um módulo importado define no programa que o importa
+pub fn declared_names(program: &Program) -> Vec<String> {
+    program
+        .iter()
+        .filter_map(|stmt| match &stmt.kind {
+            StmtKind::VarDeclaration(decl) => Some(decl.name.clone()),
+            StmtKind::MutDeclaration(decl) => Some(decl.name.clone()),
+            StmtKind::ConstDeclaration(decl) => Some(decl.name.clone()),
+            StmtKind::ListDeclaration(decl) => Some(decl.name.clone()),
+            StmtKind::DictDeclaration(decl) => Some(decl.name.clone()),
+            StmtKind::FuncDeclaration(func) => Some(func.name.clone()),
+            StmtKind::Input { name, .. } => Some(name.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::lint;
+    use crate::parser::parse_program;
+
+    /// Ids das regras disparadas em `source`
+    fn rules(source: &str) -> Vec<&'static str> {
+        let program = parse_program(source).expect("o programa de teste não compila");
+        lint(&program, source).iter().map(|lint| lint.rule.id()).collect()
+    }
+
+    #[test]
+    fn reports_unused_bindings() {
+        assert_eq!(rules("let x = 1;"), ["unused-variable"]);
+        assert_eq!(rules("let x = 1;\nprint(x);"), Vec::<&str>::new());
+        assert_eq!(rules("fun f(a) {\n    return 1;\n}\nprint(f(2));"), ["unused-parameter"]);
+        assert_eq!(rules("mut x = 1;\nprint(x);"), ["unused-mut"]);
+    }
+
+    #[test]
+    fn reports_constant_condition() {
+        assert_eq!(rules("if 1 > 2 {\n    print(1);\n}"), ["constant-condition"]);
+        assert_eq!(rules("let a = 1;\nif a > 2 {\n    print(1);\n}"), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn pragmas_silence_rules() {
+        assert_eq!(rules("let x = 1; // snask-allow: unused-variable"), Vec::<&str>::new());
+        assert_eq!(rules("// snask-allow: unused-variable\nlet x = 1;"), Vec::<&str>::new());
+        assert_eq!(rules("// snask-allow-file: unused-variable\nprint(1);\nlet x = 1;\nlet y = 2;"), Vec::<&str>::new());
+        // O pragma vale só para a linha seguinte
+        assert_eq!(rules("// snask-allow: unused-variable\nlet x = 1;\nlet y = 2;"), ["unused-variable"]);
+        // Outra regra não desliga esta
+        assert_eq!(rules("let x = 1; // snask-allow: unused-mut"), ["unused-variable"]);
+    }
+
+    #[test]
+    fn reports_unknown_pragma_ids() {
+        assert_eq!(rules("print(1); // snask-allow: nao-existe"), ["unknown-lint"]);
+    }
+}
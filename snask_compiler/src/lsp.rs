@@ -0,0 +1,685 @@
+//! `snask lsp`: servidor de Language Server Protocol em stdin/stdout.
+//!
+//! Reaproveita o parser, o `SemanticAnalyzer` e os lints de `snask check`:
+//! cada versão de um documento aberto é parseada e analisada por inteiro
+//! (sincronização completa). A última análise sem erro de sintaxe é guardada
+//! para hover, definição, completação e símbolos continuarem funcionando
+//! enquanto o usuário digita.
+
+use crate::ast::{Expr, ExprKind, FuncDecl, LiteralValue, LoopStmt, Program, Stmt, StmtKind};
+use crate::check::{analyze, primary_annotation};
+use crate::declarations::{stdlib_module, stdlib_modules, MemberDecl, ModuleDecl};
+use crate::diagnostics::{Diagnostic, Severity};
+use crate::lint::{self, name_span};
+use crate::modules::resolve_module_path;
+use crate::parser::parse_program;
+use crate::semantic_analyzer::{NameInfo, SemanticSymbolKind};
+use crate::span::Span;
+use crate::types::Type;
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, RequestId, Response};
+use lsp_types as lsp;
+use lsp_types::notification::{
+    DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, Notification as _, PublishDiagnostics,
+};
+use lsp_types::request::{Completion, DocumentSymbolRequest, GotoDefinition, HoverRequest, Request as _};
+use lsp_types::Url;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+/// Palavras-chave oferecidas no autocompletar (também pelo REPL)
+pub const KEYWORDS: &[&str] = &[
+    "let", "mut", "const", "fun", "return", "if", "elif", "else", "while", "for", "in", "import", "print", "input",
+    "type", "test", "true", "false", "nil",
+];
+
+/// Inicia o servidor e atende o editor até ele pedir `shutdown`/`exit`
+pub fn run() -> Result<(), String> {
+    let (connection, io_threads) = Connection::stdio();
+    let capabilities = lsp::ServerCapabilities {
+        text_document_sync: Some(lsp::TextDocumentSyncCapability::Kind(lsp::TextDocumentSyncKind::FULL)),
+        hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+        definition_provider: Some(lsp::OneOf::Left(true)),
+        completion_provider: Some(lsp::CompletionOptions {
+            trigger_characters: Some(vec![".".to_string()]),
+            ..Default::default()
+        }),
+        document_symbol_provider: Some(lsp::OneOf::Left(true)),
+        ..Default::default()
+    };
+    let capabilities = serde_json::to_value(capabilities).map_err(|e| e.to_string())?;
+    connection.initialize(capabilities).map_err(|e| format!("Falha ao inicializar o servidor de linguagem: {}", e))?;
+
+    let mut server = Server { connection: &connection, documents: HashMap::new() };
+    server.main_loop()?;
+    // A thread de escrita só termina quando a conexão é descartada
+    drop(connection);
+    io_threads.join().map_err(|e| format!("Falha na comunicação com o editor: {}", e))
+}
+
+/// Resultado da última análise de um documento que parseou sem erros
+struct Analysis {
+    program: Program,
+    /// Fonte da qual `program` e `names` vieram (os spans apontam para ela)
+    source: String,
+    names: Vec<NameInfo>,
+}
+
+struct Document {
+    text: String,
+    analysis: Option<Analysis>,
+}
+
+struct Server<'a> {
+    connection: &'a Connection,
+    documents: HashMap<Url, Document>,
+}
+
+impl Server<'_> {
+    fn main_loop(&mut self) -> Result<(), String> {
+        for message in &self.connection.receiver {
+            match message {
+                Message::Request(request) => {
+                    if self.connection.handle_shutdown(&request).map_err(|e| e.to_string())? {
+                        return Ok(());
+                    }
+                    let response = self.handle_request(request);
+                    self.send(Message::Response(response));
+                }
+                Message::Notification(notification) => self.handle_notification(notification),
+                Message::Response(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn send(&self, message: Message) {
+        // Se o editor fechou a conexão, o laço principal termina sozinho
+        let _ = self.connection.sender.send(message);
+    }
+
+    fn handle_notification(&mut self, notification: Notification) {
+        match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                if let Ok(params) = serde_json::from_value::<lsp::DidOpenTextDocumentParams>(notification.params) {
+                    let document = params.text_document;
+                    self.update(document.uri, document.text, Some(document.version));
+                }
+            }
+            DidChangeTextDocument::METHOD => {
+                if let Ok(params) = serde_json::from_value::<lsp::DidChangeTextDocumentParams>(notification.params) {
+                    // Sincronização completa: a última mudança tem o texto inteiro
+                    if let Some(change) = params.content_changes.into_iter().last() {
+                        let document = params.text_document;
+                        self.update(document.uri, change.text, Some(document.version));
+                    }
+                }
+            }
+            DidCloseTextDocument::METHOD => {
+                if let Ok(params) = serde_json::from_value::<lsp::DidCloseTextDocumentParams>(notification.params) {
+                    self.documents.remove(&params.text_document.uri);
+                    self.publish(params.text_document.uri, Vec::new(), None);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Reanalisa o documento e publica os diagnósticos
+    fn update(&mut self, uri: Url, text: String, version: Option<i32>) {
+        let previous = self.documents.remove(&uri).and_then(|document| document.analysis);
+        let (diagnostics, analysis) = match parse_program(&text) {
+            Ok(program) => {
+                let (analyzer, diagnostics) = analyze(&program, &text);
+                let analysis = Analysis { program, source: text.clone(), names: analyzer.names };
+                (diagnostics, Some(analysis))
+            }
+            Err(diagnostics) => (diagnostics, previous),
+        };
+
+        let index = LineIndex::new(&text);
+        let diagnostics = diagnostics.iter().map(|diagnostic| to_lsp_diagnostic(diagnostic, &uri, &text, &index)).collect();
+        self.documents.insert(uri.clone(), Document { text, analysis });
+        self.publish(uri, diagnostics, version);
+    }
+
+    fn publish(&self, uri: Url, diagnostics: Vec<lsp::Diagnostic>, version: Option<i32>) {
+        let params = lsp::PublishDiagnosticsParams { uri, diagnostics, version };
+        self.send(Message::Notification(Notification::new(PublishDiagnostics::METHOD.to_string(), params)));
+    }
+
+    fn handle_request(&self, request: Request) -> Response {
+        let id = request.id.clone();
+        let result = match request.method.as_str() {
+            HoverRequest::METHOD => self.respond::<HoverRequest>(request, |server, params| {
+                let position = params.text_document_position_params;
+                server.hover(&position.text_document.uri, position.position)
+            }),
+            GotoDefinition::METHOD => self.respond::<GotoDefinition>(request, |server, params| {
+                let position = params.text_document_position_params;
+                server.definition(&position.text_document.uri, position.position)
+            }),
+            Completion::METHOD => self.respond::<Completion>(request, |server, params| {
+                let position = params.text_document_position;
+                server.completion(&position.text_document.uri, position.position)
+            }),
+            DocumentSymbolRequest::METHOD => self.respond::<DocumentSymbolRequest>(request, |server, params| {
+                server.document_symbols(&params.text_document.uri)
+            }),
+            method => Err((ErrorCode::MethodNotFound, format!("método não suportado: {}", method))),
+        };
+        match result {
+            Ok(value) => Response { id, result: Some(value), error: None },
+            Err((code, message)) => Response::new_err(id, code as i32, message),
+        }
+    }
+
+    /// Lê os parâmetros de `R`, chama `handler` e serializa o resultado
+    fn respond<R: lsp::request::Request>(
+        &self,
+        request: Request,
+        handler: impl FnOnce(&Self, R::Params) -> R::Result,
+    ) -> Result<serde_json::Value, (ErrorCode, String)> {
+        let (_, params): (RequestId, R::Params) =
+            request.extract(R::METHOD).map_err(|e| (ErrorCode::InvalidParams, format!("{:?}", e)))?;
+        serde_json::to_value(handler(self, params)).map_err(|e| (ErrorCode::InternalError, e.to_string()))
+    }
+
+    /// Documento aberto e sua última análise
+    fn analysis(&self, uri: &Url) -> Option<(&Document, &Analysis)> {
+        let document = self.documents.get(uri)?;
+        Some((document, document.analysis.as_ref()?))
+    }
+
+    fn hover(&self, uri: &Url, position: lsp::Position) -> Option<lsp::Hover> {
+        let (document, analysis) = self.analysis(uri)?;
+        let offset = LineIndex::new(&document.text).offset(&document.text, position);
+        let (word, start) = word_at(&document.text, offset)?;
+        let offset = analysis_offset(&document.text, &analysis.source, offset)?;
+
+        let (text, doc) = if let Some((module, member)) = module_member_at(analysis, offset) {
+            let text = match module.member(&member)? {
+                MemberDecl::Function(function) => format!("fun {}.{}{}", module.name, member, function),
+                MemberDecl::Constant(constant_type) => format!("const {}.{}: {}", module.name, member, constant_type),
+            };
+            (text, module.member_doc(&member).map(str::to_string))
+        } else {
+            match name_at(analysis, offset, &word) {
+                Some(info) if info.declared_at.is_some() => (describe(info, &analysis.program), declaration_doc(info, &analysis.program)),
+                // Em `modulo.membro` o analisador não registra o nome do módulo
+                _ => {
+                    let module = stdlib_module(&word)?;
+                    (format!("module {}", module.name), module.doc.clone())
+                }
+            }
+        };
+        let index = LineIndex::new(&document.text);
+        Some(lsp::Hover {
+            contents: lsp::HoverContents::Markup(lsp::MarkupContent {
+                kind: lsp::MarkupKind::Markdown,
+                value: match doc {
+                    Some(doc) => format!("```snask\n{}\n```\n\n{}", text, doc),
+                    None => format!("```snask\n{}\n```", text),
+                },
+            }),
+            range: Some(lsp::Range::new(
+                index.position(&document.text, start),
+                index.position(&document.text, start + word.len()),
+            )),
+        })
+    }
+
+    fn definition(&self, uri: &Url, position: lsp::Position) -> Option<lsp::GotoDefinitionResponse> {
+        let (document, analysis) = self.analysis(uri)?;
+        let offset = LineIndex::new(&document.text).offset(&document.text, position);
+        let (word, _) = word_at(&document.text, offset).unwrap_or_default();
+        let offset = analysis_offset(&document.text, &analysis.source, offset)?;
+
+        // `import "modulo"`: o arquivo do módulo
+        let import = analysis.program.iter().find_map(|stmt| match &stmt.kind {
+            StmtKind::Import(path) if contains(stmt.span, offset) => Some(path),
+            _ => None,
+        });
+        if let Some(path) = import {
+            let file = resolve_import(uri, path)?;
+            let location = lsp::Location { uri: Url::from_file_path(fs::canonicalize(&file).ok()?).ok()?, range: lsp::Range::default() };
+            return Some(lsp::GotoDefinitionResponse::Scalar(location));
+        }
+
+        if word.is_empty() {
+            return None;
+        }
+        if let Some(declared_at) = name_at(analysis, offset, &word).and_then(|info| info.declared_at) {
+            let span = name_span(&analysis.source, declared_at, &word);
+            let range = LineIndex::new(&analysis.source).range(&analysis.source, span);
+            return Some(lsp::GotoDefinitionResponse::Scalar(lsp::Location { uri: uri.clone(), range }));
+        }
+
+        // Nomes definidos por um módulo importado
+        analysis.program.iter().find_map(|stmt| match &stmt.kind {
+            StmtKind::Import(path) => {
+                let file = resolve_import(uri, path)?;
+                let source = fs::read_to_string(&file).ok()?;
+                let program = parse_program(&source).ok()?;
+                let span = top_level_declaration(&program, &source, &word)?;
+                let range = LineIndex::new(&source).range(&source, span);
+                let uri = Url::from_file_path(fs::canonicalize(&file).ok()?).ok()?;
+                Some(lsp::GotoDefinitionResponse::Scalar(lsp::Location { uri, range }))
+            }
+            _ => None,
+        })
+    }
+
+    fn completion(&self, uri: &Url, position: lsp::Position) -> Option<lsp::CompletionResponse> {
+        let document = self.documents.get(uri)?;
+        let offset = LineIndex::new(&document.text).offset(&document.text, position);
+        let before = &document.text[..offset];
+        let typed = before.len() - before.trim_end_matches(is_word_char).len();
+        let before = &before[..before.len() - typed];
+
+        // `modulo.`: os membros declarados do módulo nativo
+        if let Some(target) = before.strip_suffix('.') {
+            let module = &target[target.trim_end_matches(is_word_char).len()..];
+            let items = stdlib_module(module)?
+                .members
+                .iter()
+                .map(|(name, member)| {
+                    let (kind, detail) = match member {
+                        MemberDecl::Function(function) => (lsp::CompletionItemKind::FUNCTION, function.to_string()),
+                        MemberDecl::Constant(constant_type) => (lsp::CompletionItemKind::CONSTANT, constant_type.to_string()),
+                    };
+                    lsp::CompletionItem { label: name.clone(), kind: Some(kind), detail: Some(detail), ..Default::default() }
+                })
+                .collect();
+            return Some(lsp::CompletionResponse::Array(items));
+        }
+
+        let mut items: Vec<lsp::CompletionItem> = Vec::new();
+        let mut push = |label: &str, kind: lsp::CompletionItemKind, detail: Option<String>| {
+            if !items.iter().any(|item| item.label == label) {
+                items.push(lsp::CompletionItem { label: label.to_string(), kind: Some(kind), detail, ..Default::default() });
+            }
+        };
+        if let Some(analysis) = &document.analysis {
+            let mut functions = HashMap::new();
+            parameter_functions(&analysis.program, &mut functions);
+            for info in analysis.names.iter().filter(|info| info.declared_at.is_some()) {
+                // Parâmetros só dentro da função que os declara
+                let function = info.declared_at.and_then(|span| functions.get(&span));
+                if info.kind == SemanticSymbolKind::Parameter
+                    && function.is_some_and(|span| offset < span.start.offset || offset > span.end.offset)
+                {
+                    continue;
+                }
+                let kind = match info.kind {
+                    SemanticSymbolKind::Function => lsp::CompletionItemKind::FUNCTION,
+                    SemanticSymbolKind::Constant => lsp::CompletionItemKind::CONSTANT,
+                    _ => lsp::CompletionItemKind::VARIABLE,
+                };
+                push(&info.name, kind, Some(info.symbol_type.to_string()));
+            }
+            for name in imported_names(uri, &analysis.program) {
+                push(&name, lsp::CompletionItemKind::VARIABLE, None);
+            }
+        }
+        let mut modules: Vec<&String> = stdlib_modules().keys().collect();
+        modules.sort();
+        for module in modules {
+            push(module, lsp::CompletionItemKind::MODULE, None);
+        }
+        for keyword in KEYWORDS {
+            push(keyword, lsp::CompletionItemKind::KEYWORD, None);
+        }
+        Some(lsp::CompletionResponse::Array(items))
+    }
+
+    fn document_symbols(&self, uri: &Url) -> Option<lsp::DocumentSymbolResponse> {
+        let (_, analysis) = self.analysis(uri)?;
+        let index = LineIndex::new(&analysis.source);
+        Some(lsp::DocumentSymbolResponse::Nested(symbols(&analysis.program, analysis, &index)))
+    }
+}
+
+/// Posições do LSP (linha a partir de 0, coluna em unidades UTF-16) para
+/// offsets em bytes e de volta
+struct LineIndex {
+    line_starts: Vec<usize>,
+}
+
+impl LineIndex {
+    fn new(text: &str) -> Self {
+        let mut line_starts = vec![0];
+        line_starts.extend(text.match_indices('\n').map(|(index, _)| index + 1));
+        LineIndex { line_starts }
+    }
+
+    fn offset(&self, text: &str, position: lsp::Position) -> usize {
+        let Some(&start) = self.line_starts.get(position.line as usize) else {
+            return text.len();
+        };
+        let mut units = 0;
+        for (index, c) in text[start..].char_indices() {
+            if c == '\n' || units >= position.character as usize {
+                return start + index;
+            }
+            units += c.len_utf16();
+        }
+        text.len()
+    }
+
+    fn position(&self, text: &str, offset: usize) -> lsp::Position {
+        let offset = offset.min(text.len());
+        let line = self.line_starts.partition_point(|&start| start <= offset) - 1;
+        let character: usize = text[self.line_starts[line]..offset].chars().map(char::len_utf16).sum();
+        lsp::Position::new(line as u32, character as u32)
+    }
+
+    fn range(&self, text: &str, span: Span) -> lsp::Range {
+        lsp::Range::new(self.position(text, span.start.offset), self.position(text, span.end.offset))
+    }
+}
+
+fn to_lsp_diagnostic(diagnostic: &Diagnostic, uri: &Url, text: &str, index: &LineIndex) -> lsp::Diagnostic {
+    let range = |span: Span| index.range(text, span);
+    let primary = primary_annotation(diagnostic);
+    let mut message = diagnostic.message.clone();
+    for note in &diagnostic.notes {
+        message.push_str(&format!("\nnota: {}", note));
+    }
+    if let Some(help) = &diagnostic.help {
+        message.push_str(&format!("\najuda: {}", help));
+    }
+    let related: Vec<lsp::DiagnosticRelatedInformation> = diagnostic
+        .annotations
+        .iter()
+        .filter(|annotation| !primary.is_some_and(|p| std::ptr::eq(p, *annotation)))
+        .filter_map(|annotation| {
+            Some(lsp::DiagnosticRelatedInformation {
+                location: lsp::Location { uri: uri.clone(), range: range(annotation.span) },
+                message: annotation.message.clone()?,
+            })
+        })
+        .collect();
+    let unused = diagnostic.code.as_deref().is_some_and(|code| code.starts_with("unused-"));
+
+    lsp::Diagnostic {
+        range: primary.map(|annotation| range(annotation.span)).unwrap_or_default(),
+        severity: Some(match diagnostic.severity {
+            Severity::Error => lsp::DiagnosticSeverity::ERROR,
+            Severity::Warning => lsp::DiagnosticSeverity::WARNING,
+            Severity::Info => lsp::DiagnosticSeverity::INFORMATION,
+            Severity::Hint => lsp::DiagnosticSeverity::HINT,
+        }),
+        code: diagnostic.code.clone().map(lsp::NumberOrString::String),
+        source: Some("snask".to_string()),
+        message,
+        related_information: (!related.is_empty()).then_some(related),
+        tags: unused.then(|| vec![lsp::DiagnosticTag::UNNECESSARY]),
+        ..Default::default()
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Identificador sob o cursor e o offset em que ele começa
+fn word_at(text: &str, offset: usize) -> Option<(String, usize)> {
+    let start = text[..offset].trim_end_matches(is_word_char).len();
+    let end = offset + text[offset..].len() - text[offset..].trim_start_matches(is_word_char).len();
+    (start < end).then(|| (text[start..end].to_string(), start))
+}
+
+/// O offset no texto analisado por último. Se o documento mudou desde então
+/// (tem um erro de sintaxe agora), só vale enquanto o texto até o cursor é o mesmo.
+fn analysis_offset(text: &str, source: &str, offset: usize) -> Option<usize> {
+    (text == source || (source.get(..offset) == text.get(..offset))).then_some(offset)
+}
+
+fn contains(span: Span, offset: usize) -> bool {
+    span.start.offset <= offset && offset <= span.end.offset
+}
+
+/// Nome que o analisador registrou sob o cursor; com vários (uma declaração
+/// contém os usos no seu valor), o de trecho menor
+fn name_at<'a>(analysis: &'a Analysis, offset: usize, word: &str) -> Option<&'a NameInfo> {
+    analysis
+        .names
+        .iter()
+        .filter(|info| info.name == word && contains(info.span, offset))
+        .min_by_key(|info| info.span.end.offset - info.span.start.offset)
+}
+
+/// `modulo.membro` de um módulo nativo com o cursor no membro
+fn module_member_at(analysis: &Analysis, offset: usize) -> Option<(&'static ModuleDecl, String)> {
+    let mut found = None;
+    for stmt in &analysis.program {
+        visit_exprs(stmt, &mut |expr| {
+            if let ExprKind::PropertyAccess { target, property } = &expr.kind {
+                let ExprKind::Variable(module) = &target.kind else { return };
+                let start = expr.span.end.offset.saturating_sub(property.len());
+                if !(start <= offset && offset <= expr.span.end.offset) {
+                    return;
+                }
+                // Só se o nome ainda se refere ao módulo, e não a uma variável que o esconde
+                let is_module = !analysis
+                    .names
+                    .iter()
+                    .any(|info| info.span == target.span && info.declared_at.is_some());
+                if let Some(module) = stdlib_module(module).filter(|_| is_module) {
+                    found = Some((module, property.clone()));
+                }
+            }
+        });
+    }
+    found
+}
+
+/// Texto do hover de um nome do programa
+fn describe(info: &NameInfo, program: &Program) -> String {
+    match (&info.kind, &info.symbol_type) {
+        (SemanticSymbolKind::Function, Type::Function(params, return_type)) => {
+            let decl = info.declared_at.and_then(|span| find_function(program, span));
+            let params: Vec<String> = params
+                .iter()
+                .enumerate()
+                .map(|(i, param_type)| match decl.and_then(|decl| decl.params.get(i)) {
+                    Some(param) => format!("{}: {}", param.name, param_type),
+                    None => param_type.to_string(),
+                })
+                .collect();
+            format!("fun {}({}): {}", info.name, params.join(", "), return_type)
+        }
+        (SemanticSymbolKind::Parameter, symbol_type) => format!("{}: {}", info.name, symbol_type),
+        (kind, symbol_type) => {
+            let keyword = match kind {
+                SemanticSymbolKind::Mutable => "mut",
+                SemanticSymbolKind::Constant => "const",
+                _ => "let",
+            };
+            format!("{} {}: {}", keyword, info.name, symbol_type)
+        }
+    }
+}
+
+/// Comentário `///` da função ou constante declarada em `info.declared_at`
+fn declaration_doc(info: &NameInfo, program: &Program) -> Option<String> {
+    let declared_at = info.declared_at?;
+    if let Some(decl) = find_function(program, declared_at) {
+        return decl.doc.clone();
+    }
+    program.iter().find_map(|stmt| match &stmt.kind {
+        StmtKind::ConstDeclaration(decl) if stmt.span == declared_at => decl.doc.clone(),
+        _ => None,
+    })
+}
+
+/// Declaração de função cujo nome está em `name_span`, em qualquer nível
+fn find_function(program: &[Stmt], name_span: Span) -> Option<&FuncDecl> {
+    program.iter().find_map(|stmt| match &stmt.kind {
+        StmtKind::FuncDeclaration(decl) if decl.name_span == name_span => Some(decl),
+        _ => child_blocks(stmt).into_iter().find_map(|block| find_function(block, name_span)),
+    })
+}
+
+/// Trecho do nome de uma declaração do nível de cima de um módulo
+fn top_level_declaration(program: &Program, source: &str, name: &str) -> Option<Span> {
+    program.iter().find_map(|stmt| match &stmt.kind {
+        StmtKind::FuncDeclaration(decl) if decl.name == name => Some(decl.name_span),
+        StmtKind::TypeAlias { name: alias, name_span, .. } if alias == name => Some(*name_span),
+        StmtKind::VarDeclaration(decl) if decl.name == name => Some(name_span(source, stmt.span, name)),
+        StmtKind::MutDeclaration(decl) if decl.name == name => Some(name_span(source, stmt.span, name)),
+        StmtKind::ConstDeclaration(decl) if decl.name == name => Some(name_span(source, stmt.span, name)),
+        _ => None,
+    })
+}
+
+/// Arquivo de um `import`, procurado primeiro ao lado do documento
+fn resolve_import(uri: &Url, path: &str) -> Option<PathBuf> {
+    let beside = uri.to_file_path().ok().and_then(|file| Some(file.parent()?.join(path)));
+    match beside {
+        Some(file) if file.is_file() => Some(file),
+        _ => resolve_module_path(path).ok(),
+    }
+}
+
+#[allow(deprecated)] // `DocumentSymbol::deprecated` é obrigatório no struct
+fn symbols(block: &[Stmt], analysis: &Analysis, index: &LineIndex) -> Vec<lsp::DocumentSymbol> {
+    let source = analysis.source.as_str();
+    let mut found = Vec::new();
+    for stmt in block {
+        let (name, kind, selection, children) = match &stmt.kind {
+            StmtKind::FuncDeclaration(decl) => {
+                (&decl.name, lsp::SymbolKind::FUNCTION, decl.name_span, symbols(&decl.body, analysis, index))
+            }
+            StmtKind::VarDeclaration(decl) => (&decl.name, lsp::SymbolKind::VARIABLE, name_span(source, stmt.span, &decl.name), vec![]),
+            StmtKind::MutDeclaration(decl) => (&decl.name, lsp::SymbolKind::VARIABLE, name_span(source, stmt.span, &decl.name), vec![]),
+            StmtKind::ConstDeclaration(decl) => (&decl.name, lsp::SymbolKind::CONSTANT, name_span(source, stmt.span, &decl.name), vec![]),
+            StmtKind::TypeAlias { name, name_span, .. } => (name, lsp::SymbolKind::TYPE_PARAMETER, *name_span, vec![]),
+            StmtKind::Test { name, name_span, body } => (name, lsp::SymbolKind::METHOD, *name_span, symbols(body, analysis, index)),
+            _ => {
+                // Declarações dentro de if/while/for aparecem no nível do bloco que os contém
+                for child in child_blocks(stmt) {
+                    found.extend(symbols(child, analysis, index));
+                }
+                continue;
+            }
+        };
+        let detail = analysis
+            .names
+            .iter()
+            .find(|info| &info.name == name && (info.span == stmt.span || info.span == selection))
+            .map(|info| match &stmt.kind {
+                StmtKind::FuncDeclaration(_) => describe(info, &analysis.program),
+                _ => info.symbol_type.to_string(),
+            });
+        found.push(lsp::DocumentSymbol {
+            name: name.clone(),
+            detail,
+            kind,
+            tags: None,
+            deprecated: None,
+            range: index.range(source, stmt.span),
+            selection_range: index.range(source, selection),
+            children: (!children.is_empty()).then_some(children),
+        });
+    }
+    found
+}
+
+/// Blocos de statements dentro de um statement de controle de fluxo
+/// Trecho da declaração de função de cada parâmetro, pelo trecho do parâmetro
+fn parameter_functions(program: &[Stmt], out: &mut HashMap<Span, Span>) {
+    for stmt in program {
+        if let StmtKind::FuncDeclaration(func) = &stmt.kind {
+            out.extend(func.params.iter().map(|param| (param.span, stmt.span)));
+        }
+        for block in child_blocks(stmt) {
+            parameter_functions(block, out);
+        }
+    }
+}
+
+fn child_blocks(stmt: &Stmt) -> Vec<&[Stmt]> {
+    match &stmt.kind {
+        StmtKind::FuncDeclaration(decl) => vec![&decl.body],
+        StmtKind::Conditional(conditional) => {
+            let mut blocks: Vec<&[Stmt]> = vec![&conditional.if_block.body];
+            blocks.extend(conditional.elif_blocks.iter().map(|block| block.body.as_slice()));
+            blocks.extend(conditional.else_block.as_deref());
+            blocks
+        }
+        StmtKind::Loop(LoopStmt::While { body, .. }) | StmtKind::Loop(LoopStmt::For { body, .. }) => vec![body],
+        StmtKind::Test { body, .. } => vec![body],
+        _ => vec![],
+    }
+}
+
+/// Chama `f` em cada expressão de um statement, incluindo subexpressões
+fn visit_exprs(stmt: &Stmt, f: &mut impl FnMut(&Expr)) {
+    fn walk(expr: &Expr, f: &mut impl FnMut(&Expr)) {
+        f(expr);
+        match &expr.kind {
+            ExprKind::Unary { expr, .. } => walk(expr, f),
+            ExprKind::Binary { left, right, .. } => {
+                walk(left, f);
+                walk(right, f);
+            }
+            ExprKind::FunctionCall { callee, args } => {
+                walk(callee, f);
+                args.iter().for_each(|arg| walk(arg, f));
+            }
+            ExprKind::PropertyAccess { target, .. } => walk(target, f),
+            ExprKind::IndexAccess { target, index } => {
+                walk(target, f);
+                walk(index, f);
+            }
+            ExprKind::Literal(LiteralValue::List(items)) => items.iter().for_each(|item| walk(item, f)),
+            ExprKind::Literal(LiteralValue::Dict(pairs)) => pairs.iter().for_each(|(key, value)| {
+                walk(key, f);
+                walk(value, f);
+            }),
+            ExprKind::Literal(_) | ExprKind::Variable(_) => {}
+        }
+    }
+
+    let exprs: Vec<&Expr> = match &stmt.kind {
+        StmtKind::Expression(expr) | StmtKind::FuncCall(expr) | StmtKind::Return(expr) => vec![expr],
+        StmtKind::VarDeclaration(decl) => vec![&decl.value],
+        StmtKind::MutDeclaration(decl) => vec![&decl.value],
+        StmtKind::ConstDeclaration(decl) => vec![&decl.value],
+        StmtKind::VarAssignment(set) => vec![&set.value],
+        StmtKind::ListDeclaration(decl) => vec![&decl.value],
+        StmtKind::DictDeclaration(decl) => vec![&decl.value],
+        StmtKind::ListPush(push) => vec![&push.value],
+        StmtKind::DictSet(set) => vec![&set.key, &set.value],
+        StmtKind::Print(exprs) => exprs.iter().collect(),
+        StmtKind::Conditional(conditional) => std::iter::once(&conditional.if_block.condition)
+            .chain(conditional.elif_blocks.iter().map(|block| &block.condition))
+            .collect(),
+        StmtKind::Loop(LoopStmt::While { condition, .. }) => vec![condition],
+        StmtKind::Loop(LoopStmt::For { iterable, .. }) => vec![iterable],
+        StmtKind::Input { .. } | StmtKind::FuncDeclaration(_) | StmtKind::Import(_) | StmtKind::TypeAlias { .. } | StmtKind::Test { .. } => vec![],
+    };
+    for expr in exprs {
+        walk(expr, f);
+    }
+    for block in child_blocks(stmt) {
+        for child in block {
+            visit_exprs(child, f);
+        }
+    }
+}
+
+/// Nomes que os módulos importados por `program` definem
+fn imported_names(uri: &Url, program: &Program) -> Vec<String> {
+    program
+        .iter()
+        .filter_map(|stmt| match &stmt.kind {
+            StmtKind::Import(path) => resolve_import(uri, path),
+            _ => None,
+        })
+        .filter_map(|file| parse_program(&fs::read_to_string(file).ok()?).ok())
+        .flat_map(|module| lint::declared_names(&module))
+        .collect()
+}
@@ -3,6 +3,7 @@ pub mod value;
 pub mod symbol_table;
 pub mod semantic_analyzer;
 pub mod types;
+pub mod declarations;
 pub mod parser;
 pub mod interpreter;
 pub mod modules;
@@ -11,14 +12,32 @@ pub mod span;
 pub mod diagnostics;
 pub mod repl;
 pub mod packages; // Módulo para o gerenciador de pacotes
+pub mod cache;
+pub mod optimizer;
+pub mod lint;
+pub mod check;
+pub mod formatter;
+pub mod lsp;
+pub mod debugger;
+pub mod dap;
+pub mod profiler;
+pub mod coverage;
+pub mod test_runner;
+pub mod docgen;
+pub mod assets;
+pub mod bundle;
+pub mod runtime_error;
+pub mod codegen_c;
 
 
 use std::fs;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
 
-use clap::{Parser as ClapParser, Subcommand};
+use clap::{Parser as ClapParser, Subcommand, ValueEnum};
 use interpreter::{Interpreter, InterpretResult};
+use optimizer::{optimize, OptLevel};
 use parser::parse_program;
-use semantic_analyzer::SemanticAnalyzer;
 use stdlib::register_stdlib;
 
 #[derive(ClapParser)]
@@ -31,25 +50,269 @@ struct Cli {
 #[derive(Subcommand)]
 enum Commands {
     /// Interprets a .snask file directly from its AST
-    Interpret { file: String },
+    #[command(visible_alias = "run")]
+    Interpret {
+        file: String,
+        /// Ignores the precompiled module cache (.snaskc)
+        #[arg(long)]
+        no_cache: bool,
+        /// Optimisation level: 0 = none, 1 = constant folding and dead code elimination, 2 = also inline `const` values
+        #[arg(long, default_value_t = 0, value_parser = clap::value_parser!(u8).range(0..=2))]
+        opt_level: u8,
+        /// Prints the (optimised) AST to stderr before running it
+        #[arg(long)]
+        dump_ast: bool,
+        /// Maximum call depth before a stack overflow error is raised (capped at 16384, what fits in a 1 GiB stack)
+        #[arg(long, default_value_t = interpreter::DEFAULT_MAX_CALL_DEPTH)]
+        max_call_depth: usize,
+        /// Records call counts and inclusive/exclusive time per function (native ones included) and prints a table to stderr at exit or on Ctrl+C
+        #[arg(long)]
+        profile: bool,
+        /// With --profile, also counts how many times each line runs
+        #[arg(long, requires = "profile")]
+        profile_lines: bool,
+        /// Writes the profile as collapsed stacks for flamegraph tools to FILE (implies --profile)
+        #[arg(long, value_name = "FILE")]
+        flamegraph: Option<String>,
+        /// Records which lines run (imported modules included) and prints a per-file summary to stderr
+        #[arg(long)]
+        coverage: bool,
+        /// Writes the coverage as an lcov report to FILE (implies --coverage)
+        #[arg(long, value_name = "FILE")]
+        lcov: Option<String>,
+        /// Exits with 1 if line coverage is below PERCENT (implies --coverage)
+        #[arg(long, value_name = "PERCENT")]
+        fail_under: Option<f64>,
+    },
+    /// Builds a standalone executable that embeds the interpreter, the program and its modules
+    Build {
+        file: String,
+        /// Path of the generated executable (defaults to the file name without extension)
+        #[arg(short, long)]
+        output: Option<String>,
+        /// Asset directory to embed, e.g. Blaze templates/static folders (repeatable)
+        #[arg(long = "assets")]
+        assets: Vec<String>,
+        /// Optimisation level applied when the executable starts
+        #[arg(long, default_value_t = 0, value_parser = clap::value_parser!(u8).range(0..=2))]
+        opt_level: u8,
+    },
+    /// Compiles a type-annotated program ahead of time to another language
+    Compile {
+        file: String,
+        /// Target language of the generated code
+        #[arg(long, value_enum, default_value_t = EmitTarget::C)]
+        emit: EmitTarget,
+        /// Path of the generated source file (defaults to the file name with the target's extension)
+        #[arg(short, long)]
+        output: Option<String>,
+        /// Also builds a native executable with the system C compiler
+        #[arg(long)]
+        build: bool,
+        /// C compiler used by --build
+        #[arg(long, default_value = "cc")]
+        cc: String,
+    },
+    /// Lexes, parses and analyses files without running them; exits with 1 on errors
+    Check {
+        /// Files or directories (searched recursively for .snask files)
+        #[arg(required = true)]
+        paths: Vec<String>,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = check::CheckFormat::Text)]
+        format: check::CheckFormat,
+    },
+    /// Rewrites files in the canonical style (reads stdin and writes stdout when no path or `-` is given)
+    Fmt {
+        /// Files or directories (searched recursively for .snask files)
+        paths: Vec<String>,
+        /// Only lists the files that would change; exits with 1 if there are any
+        #[arg(long)]
+        check: bool,
+    },
+    /// Runs a program under the debugger: breakpoints, stepping, call stack, expression evaluation and watches
+    Debug {
+        /// Program to debug (with --dap, the editor sends it in the `launch` request)
+        #[arg(required_unless_present = "dap")]
+        file: Option<String>,
+        /// Serves the Debug Adapter Protocol on stdin/stdout instead of the interactive prompt
+        #[arg(long)]
+        dap: bool,
+        /// Maximum call depth before a stack overflow error is raised (capped at 16384, what fits in a 1 GiB stack)
+        #[arg(long, default_value_t = interpreter::DEFAULT_MAX_CALL_DEPTH)]
+        max_call_depth: usize,
+    },
+    /// Runs the `test "..." { ... }` blocks of *_test.snask files, each in a fresh interpreter; exits with 1 if any fails
+    Test {
+        /// Test files, or directories searched recursively for *_test.snask files (defaults to the current directory)
+        paths: Vec<String>,
+        /// Only runs tests whose description or file path contains TEXT
+        #[arg(short, long, value_name = "TEXT")]
+        filter: Option<String>,
+        /// Number of tests run in parallel
+        #[arg(short, long, default_value_t = 1)]
+        jobs: usize,
+        /// Writes the results as a JUnit XML report to FILE
+        #[arg(long, value_name = "FILE")]
+        junit: Option<String>,
+        /// Maximum call depth before a stack overflow error is raised (capped at 16384, what fits in a 1 GiB stack)
+        #[arg(long, default_value_t = interpreter::DEFAULT_MAX_CALL_DEPTH)]
+        max_call_depth: usize,
+        /// Records which lines of the imported modules the tests run and prints a per-file summary to stderr
+        #[arg(long)]
+        coverage: bool,
+        /// Writes the coverage as an lcov report to FILE (implies --coverage)
+        #[arg(long, value_name = "FILE")]
+        lcov: Option<String>,
+        /// Exits with 1 if line coverage is below PERCENT (implies --coverage)
+        #[arg(long, value_name = "PERCENT")]
+        fail_under: Option<f64>,
+    },
+    /// Generates HTML and Markdown API docs from the `///` comments of a project and the modules it imports
+    Doc {
+        /// Files or directories (searched recursively for .snask files; defaults to the current directory)
+        paths: Vec<String>,
+        /// Directory the pages are written to
+        #[arg(short, long, default_value = "doc")]
+        output: String,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = docgen::DocFormat::Html)]
+        format: docgen::DocFormat,
+        /// Also generates reference pages for the native stdlib modules (only those, unless paths are given)
+        #[arg(long)]
+        stdlib: bool,
+    },
+    /// Starts a Language Server Protocol server on stdin/stdout (for editors)
+    Lsp,
     /// Starts the interactive REPL
     Repl,
     /// Installs a package from the official Snask package registry
     Install { name: String },
 }
 
+#[derive(Clone, Copy, ValueEnum)]
+enum EmitTarget {
+    /// C source plus the Snask runtime library (snask_runtime.c/.h)
+    C,
+}
+
 fn main() {
+    // Um executável gerado por `snask build` carrega o programa no próprio binário
+    if let Some(bundle) = bundle::load_embedded() {
+        std::process::exit(bundle.run());
+    }
+
     let cli = Cli::parse();
 
     match &cli.command {
-        Commands::Interpret { file } => {
+        Commands::Interpret { file, no_cache, opt_level, dump_ast, max_call_depth, profile, profile_lines, flamegraph, coverage, lcov, fail_under } => {
+            cache::set_enabled(!no_cache);
             if !file.ends_with(".snask") {
                 eprintln!("Erro: O arquivo de entrada deve ter a extensão '.snask'.");
                 return;
             }
-            match interpret_file(file) {
+            let profile = (*profile || flamegraph.is_some())
+                .then(|| profiler::ProfileOptions { lines: *profile_lines, flamegraph: flamegraph.clone() });
+            let coverage = (*coverage || lcov.is_some() || fail_under.is_some())
+                .then(|| coverage::CoverageOptions { lcov: lcov.clone(), fail_under: *fail_under });
+            match interpret_file(file, OptLevel::from_level(*opt_level), *dump_ast, *max_call_depth, profile, coverage) {
                 Ok(_) => println!("Execução concluída."),
-                Err(e) => eprintln!("Erro durante a execução: {}", e),
+                Err(e) => {
+                    eprintln!("Erro durante a execução: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Commands::Build { file, output, assets, opt_level } => {
+            if !file.ends_with(".snask") {
+                eprintln!("Erro: O arquivo de entrada deve ter a extensão '.snask'.");
+                return;
+            }
+            let output = output.clone().unwrap_or_else(|| {
+                Path::new(file).file_stem().unwrap().to_string_lossy().into_owned()
+            });
+            match build_executable(file, &output, assets, *opt_level) {
+                Ok(_) => println!("Executável gerado: {}", output),
+                Err(e) => {
+                    eprintln!("Erro durante o build: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Commands::Compile { file, emit: EmitTarget::C, output, build, cc } => {
+            if !file.ends_with(".snask") {
+                eprintln!("Erro: O arquivo de entrada deve ter a extensão '.snask'.");
+                return;
+            }
+            let output = output.clone().unwrap_or_else(|| {
+                Path::new(file).with_extension("c").to_string_lossy().into_owned()
+            });
+            match compile_to_c(file, &output, if *build { Some(cc.as_str()) } else { None }) {
+                Ok(Some(executable)) => println!("Executável gerado: {}", executable),
+                Ok(None) => println!("Código C gerado: {}", output),
+                Err(e) => {
+                    eprintln!("Erro durante a compilação: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Commands::Check { paths, format } => match check::run(paths, *format) {
+            Ok(code) => std::process::exit(code),
+            Err(e) => {
+                eprintln!("Erro: {}", e);
+                std::process::exit(2);
+            }
+        },
+        Commands::Fmt { paths, check } => match formatter::run(paths, *check) {
+            Ok(code) => std::process::exit(code),
+            Err(e) => {
+                eprintln!("Erro: {}", e);
+                std::process::exit(2);
+            }
+        },
+        Commands::Debug { file, dap, max_call_depth } => {
+            let result = match file {
+                _ if *dap => dap::run(*max_call_depth),
+                Some(file) => load_program(file).and_then(|program| debugger::run_cli(file, program, *max_call_depth)),
+                None => Ok(()),
+            };
+            if let Err(e) = result {
+                eprintln!("Erro: {}", e);
+                std::process::exit(1);
+            }
+        }
+        Commands::Test { paths, filter, jobs, junit, max_call_depth, coverage, lcov, fail_under } => {
+            let coverage = (*coverage || lcov.is_some() || fail_under.is_some())
+                .then(|| coverage::CoverageOptions { lcov: lcov.clone(), fail_under: *fail_under });
+            let options = test_runner::TestOptions {
+                filter: filter.clone(),
+                jobs: *jobs,
+                junit: junit.clone(),
+                max_call_depth: *max_call_depth,
+                coverage,
+            };
+            match test_runner::run(paths, options) {
+                Ok(code) => std::process::exit(code),
+                Err(e) => {
+                    eprintln!("Erro: {}", e);
+                    std::process::exit(2);
+                }
+            }
+        }
+        Commands::Doc { paths, output, format, stdlib } => {
+            let options = docgen::DocOptions { output: output.clone(), format: *format, stdlib: *stdlib };
+            match docgen::run(paths, &options) {
+                Ok(count) => println!("{} módulo(s) documentado(s) em {}", count, output),
+                Err(e) => {
+                    eprintln!("Erro: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Commands::Lsp => {
+            if let Err(e) = lsp::run() {
+                eprintln!("Erro: {}", e);
+                std::process::exit(1);
             }
         }
         Commands::Repl => {
@@ -64,61 +327,147 @@ fn main() {
     }
 }
 
-fn interpret_file(file_path: &str) -> Result<(), String> {
-    let source = fs::read_to_string(file_path)
-        .map_err(|e| format!("Não foi possível ler o arquivo {}: {}", file_path, e))?;
+fn interpret_file(
+    file_path: &str,
+    opt_level: OptLevel,
+    dump_ast: bool,
+    max_call_depth: usize,
+    profile: Option<profiler::ProfileOptions>,
+    coverage: Option<coverage::CoverageOptions>,
+) -> Result<(), String> {
+    let program = load_program(file_path)?;
+    // As linhas executáveis vêm do código original, antes do otimizador remover código morto
+    let coverage = coverage.map(|options| Arc::new(Mutex::new(coverage::Coverage::new(file_path, &program, options))));
 
-    let program = match parse_program(&source) {
-        Ok(p) => p,
-        Err(e) => {
-            let diagnostic = convert_parser_error(&e);
-            return Err(diagnostic.render(file_path, &source));
+    let (program, stats) = optimize(program, opt_level);
+    if dump_ast {
+        eprintln!("{:#?}", program);
+        if opt_level != OptLevel::None {
+            eprintln!("Otimizações ({:?}): {}", opt_level, stats);
         }
-    };
-
-    let mut analyzer = SemanticAnalyzer::new();
-    analyzer.analyze(&program);
-
-    if !analyzer.errors.is_empty() {
-        let mut error_msg = String::from("Análise semântica encontrou erros:\n");
-        for error in analyzer.errors {
-            error_msg.push_str(&format!("{:?}\n", error));
-        }
-        return Err(error_msg);
     }
 
     let mut interpreter = Interpreter::new();
     register_stdlib(interpreter.get_globals_mut());
-    match interpreter.interpret(program) {
+    interpreter.set_opt_level(opt_level);
+    interpreter.set_file(file_path);
+    interpreter.set_max_call_depth(max_call_depth);
+
+    let profiler = profile.map(|options| Arc::new(Mutex::new(profiler::Profiler::new(options))));
+    if let Some(profiler) = &profiler {
+        interpreter.add_tracer(profiler.clone());
+    }
+    if let Some(coverage) = &coverage {
+        interpreter.add_tracer(coverage.clone());
+    }
+    if profiler.is_some() || coverage.is_some() {
+        // Servidores (Blaze) só terminam com Ctrl+C: os relatórios são escritos antes de sair
+        let (profiler, coverage) = (profiler.clone(), coverage.clone());
+        let _ = ctrlc::set_handler(move || {
+            if let Err(e) = write_reports(profiler.as_ref(), coverage.as_ref()) {
+                eprintln!("Erro: {}", e);
+            }
+            std::process::exit(130);
+        });
+    }
+
+    let result = match interpreter.interpret(program) {
         InterpretResult::Ok => Ok(()),
-        InterpretResult::RuntimeError(msg) => Err(format!("Erro de execução: {}", msg)),
+        InterpretResult::RuntimeError(e) => Err(render_runtime_error(&e)),
+    };
+    let reports = write_reports(profiler.as_ref(), coverage.as_ref());
+    result.and(reports)
+}
+
+/// Relatórios de `--profile` e `--coverage`
+fn write_reports(
+    profiler: Option<&Arc<Mutex<profiler::Profiler>>>,
+    coverage: Option<&Arc<Mutex<coverage::Coverage>>>,
+) -> Result<(), String> {
+    if let Some(profiler) = profiler {
+        profiler.lock().unwrap_or_else(|e| e.into_inner()).write_report()?;
+    }
+    if let Some(coverage) = coverage {
+        coverage.lock().unwrap_or_else(|e| e.into_inner()).write_report()?;
+    }
+    Ok(())
+}
+
+/// Renderiza um erro de execução com o trecho de código do frame mais interno
+fn render_runtime_error(error: &runtime_error::RuntimeError) -> String {
+    let file = error.innermost().map(|frame| frame.file.as_str()).unwrap_or("");
+    let source = fs::read_to_string(file).unwrap_or_default();
+    error.to_diagnostic().render(file, &source)
+}
+
+fn build_executable(file_path: &str, output: &str, asset_dirs: &[String], opt_level: u8) -> Result<(), String> {
+    let program = load_program(file_path)?;
+    let entry = Path::new(file_path).file_name().unwrap().to_string_lossy().into_owned();
+    let bundle = bundle::Bundle::new(&entry, program, asset_dirs, opt_level)?;
+    bundle.write_executable(output)
+}
+
+/// Gera `output` (e o runtime C ao lado dele). Com `cc`, compila também um
+/// executável nativo e retorna o seu caminho.
+fn compile_to_c(file_path: &str, output: &str, cc: Option<&str>) -> Result<Option<String>, String> {
+    let program = load_program(file_path)?;
+    let code = codegen_c::generate(&program)?;
+
+    let output_path = Path::new(output);
+    let runtime_dir = output_path.parent().unwrap_or_else(|| Path::new(""));
+    let runtime_source = runtime_dir.join("snask_runtime.c");
+    let write = |path: &Path, contents: &str| {
+        fs::write(path, contents).map_err(|e| format!("Não foi possível escrever {}: {}", path.display(), e))
+    };
+    write(output_path, &code)?;
+    write(&runtime_dir.join("snask_runtime.h"), codegen_c::RUNTIME_HEADER)?;
+    write(&runtime_source, codegen_c::RUNTIME_SOURCE)?;
+
+    let Some(cc) = cc else {
+        return Ok(None);
+    };
+    let executable = output_path.with_extension("").to_string_lossy().into_owned();
+    let status = std::process::Command::new(cc)
+        .arg("-O2")
+        .arg(output_path)
+        .arg(&runtime_source)
+        .arg("-lm")
+        .arg("-o")
+        .arg(&executable)
+        .status()
+        .map_err(|e| format!("Não foi possível executar '{}': {}", cc, e))?;
+    if !status.success() {
+        return Err(format!("'{}' terminou com {}", cc, status));
     }
+    Ok(Some(executable))
 }
 
-fn convert_parser_error(error_msg: &str) -> diagnostics::Diagnostic {
-    use diagnostics::{Diagnostic, Annotation};
-    use span::{Span, Position};
-
-    if let Some(idx) = error_msg.rfind("na linha ") {
-        let suffix = &error_msg[idx..];
-        let parts: Vec<&str> = suffix.split_whitespace().collect();
-        
-        if parts.len() >= 4 {
-            let line_str = parts[2].trim_matches(',');
-            let col_str = parts[4];
-            
-            if let (Ok(line), Ok(col)) = (line_str.parse::<usize>(), col_str.parse::<usize>()) {
-                let pos = Position::new(line, col, 0);
-                let span = Span::new(pos, pos);
-                
-                let clean_msg = error_msg[..idx].trim().to_string();
-                
-                return Diagnostic::error(clean_msg)
-                    .with_code("P001".to_string())
-                    .with_annotation(Annotation::primary(span, "erro aqui".to_string()));
+/// Lê, parseia (ou carrega do cache) e analisa semanticamente um arquivo
+fn load_program(file_path: &str) -> Result<ast::Program, String> {
+    let source = fs::read_to_string(file_path)
+        .map_err(|e| format!("Não foi possível ler o arquivo {}: {}", file_path, e))?;
+
+    let program = match cache::load(Path::new(file_path), &source) {
+        Some(p) => p,
+        None => match parse_program(&source) {
+            Ok(p) => {
+                cache::store(Path::new(file_path), &source, &p);
+                p
             }
-        }
+            Err(diagnostics) => {
+                return Err(diagnostics::DiagnosticBag::from(diagnostics).render_all(file_path, &source));
+            }
+        },
+    };
+
+    let bag = diagnostics::DiagnosticBag::from(check::diagnose(&program, &source));
+    if bag.has_errors() {
+        return Err(bag.render_all(file_path, &source));
+    }
+    // Avisos não impedem a execução
+    if !bag.is_empty() {
+        eprint!("{}", bag.render_all(file_path, &source));
     }
 
-    Diagnostic::error(error_msg.to_string())
+    Ok(program)
 }
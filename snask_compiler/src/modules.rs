@@ -1,10 +1,39 @@
+use crate::diagnostics::DiagnosticBag;
 use crate::parser::parse_program;
 use crate::ast::Program;
 use std::fs;
 use std::path::{Path, PathBuf};
 use crate::packages; // Importa o novo módulo de pacotes
+use crate::cache;
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+lazy_static! {
+    /// Módulos embutidos em um executável gerado por `snask build`,
+    /// indexados pelo caminho usado no `import`.
+    static ref EMBEDDED_MODULES: RwLock<HashMap<String, Program>> = RwLock::new(HashMap::new());
+}
+
+/// Registra módulos pré-parseados que têm prioridade sobre pacotes e arquivos
+pub fn register_embedded_modules(modules: HashMap<String, Program>) {
+    EMBEDDED_MODULES.write().unwrap().extend(modules);
+}
+
+/// Imports que se referem a módulos nativos da stdlib e não a arquivos `.snask`
+pub fn is_native_module(path_str: &str) -> bool {
+    path_str == "blaze" || path_str == "collections"
+}
 
 pub fn load_module(path_str: &str) -> Result<Program, String> {
+    if let Some(program) = EMBEDDED_MODULES.read().unwrap().get(path_str) {
+        return Ok(program.clone());
+    }
+    read_and_parse_module(&resolve_module_path(path_str)?)
+}
+
+/// Arquivo a que um `import` se refere, sem carregá-lo
+pub fn resolve_module_path(path_str: &str) -> Result<PathBuf, String> {
     // Tenta resolver o módulo de diferentes maneiras.
     // 1. Como um pacote instalado.
     // 2. Como um caminho de arquivo relativo.
@@ -20,7 +49,7 @@ pub fn load_module(path_str: &str) -> Result<Program, String> {
         path_candidate.push("main.snask");
         
         if path_candidate.exists() {
-            return read_and_parse_module(&path_candidate);
+            return Ok(path_candidate);
         }
         tried_paths.push(path_candidate.to_string_lossy().into_owned());
 
@@ -31,7 +60,7 @@ pub fn load_module(path_str: &str) -> Result<Program, String> {
         }
 
         if path_candidate.exists() {
-            return read_and_parse_module(&path_candidate);
+            return Ok(path_candidate);
         }
         tried_paths.push(path_candidate.to_string_lossy().into_owned());
     }
@@ -39,7 +68,7 @@ pub fn load_module(path_str: &str) -> Result<Program, String> {
     // --- TENTATIVA 2: Resolver como caminho relativo ---
     let relative_path = Path::new(path_str);
     if relative_path.exists() {
-        return read_and_parse_module(relative_path);
+        return Ok(relative_path.to_path_buf());
     }
     tried_paths.push(relative_path.to_string_lossy().into_owned());
 
@@ -56,7 +85,15 @@ pub fn load_module(path_str: &str) -> Result<Program, String> {
 fn read_and_parse_module(path: &Path) -> Result<Program, String> {
     let source = fs::read_to_string(path)
         .map_err(|e| format!("Não foi possível ler o módulo {}: {}", path.display(), e))?;
-    
-    parse_program(&source)
-        .map_err(|e| format!("Erro de parsing no módulo {}: {}", path.display(), e))
+
+    if let Some(program) = cache::load(path, &source) {
+        return Ok(program);
+    }
+
+    let program = parse_program(&source).map_err(|diagnostics| {
+        let rendered = DiagnosticBag::from(diagnostics).render_all(&path.display().to_string(), &source);
+        format!("Erro de parsing no módulo {}:\n{}", path.display(), rendered)
+    })?;
+    cache::store(path, &source, &program);
+    Ok(program)
 }
@@ -0,0 +1,462 @@
+use crate::ast::{
+    BinaryOp, ConditionalStmt, Expr, ExprKind, IfBlock, LiteralValue, LoopStmt, Program, Stmt,
+    StmtKind, UnaryOp,
+};
+use crate::span::Span;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Nível de otimização aplicado entre o parser e o interpretador
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum OptLevel {
+    /// Nenhuma transformação: o AST é executado exatamente como foi parseado
+    #[default]
+    None,
+    /// Constant folding e eliminação de código morto
+    Basic,
+    /// `Basic` + inlining de valores `const`
+    Full,
+}
+
+impl OptLevel {
+    pub fn from_level(level: u8) -> Self {
+        match level {
+            0 => OptLevel::None,
+            1 => OptLevel::Basic,
+            _ => OptLevel::Full,
+        }
+    }
+}
+
+/// Contadores do que foi alterado, exibidos junto com o dump do AST
+#[derive(Debug, Default, Clone)]
+pub struct OptimizationStats {
+    pub folded_expressions: usize,
+    pub removed_branches: usize,
+    pub removed_unreachable: usize,
+    pub inlined_constants: usize,
+}
+
+impl fmt::Display for OptimizationStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} expressões avaliadas em tempo de compilação, {} ramos removidos, {} statements inalcançáveis removidos, {} constantes inlined",
+            self.folded_expressions, self.removed_branches, self.removed_unreachable, self.inlined_constants
+        )
+    }
+}
+
+pub struct Optimizer {
+    level: OptLevel,
+    // Pilha de escopos com os `const` conhecidos. `None` marca um nome que foi
+    // sombreado por outra declaração e portanto não pode ser inlined.
+    constants: Vec<HashMap<String, Option<LiteralValue>>>,
+    pub stats: OptimizationStats,
+}
+
+/// Otimiza um programa inteiro no nível indicado
+pub fn optimize(program: Program, level: OptLevel) -> (Program, OptimizationStats) {
+    let mut optimizer = Optimizer::new(level);
+    let program = optimizer.optimize_program(program);
+    (program, optimizer.stats)
+}
+
+/// Valor de uma expressão feita só de literais (ex: `1 > 2`), avaliada como no
+/// nível `Basic`; `None` quando depende de algo que só é conhecido em execução
+pub fn constant_value(expr: &Expr) -> Option<LiteralValue> {
+    match Optimizer::new(OptLevel::Basic).optimize_expression(expr.clone()).kind {
+        ExprKind::Literal(lit) if is_scalar(&lit) => Some(lit),
+        _ => None,
+    }
+}
+
+impl Optimizer {
+    pub fn new(level: OptLevel) -> Self {
+        Optimizer {
+            level,
+            constants: vec![HashMap::new()],
+            stats: OptimizationStats::default(),
+        }
+    }
+
+    pub fn optimize_program(&mut self, program: Program) -> Program {
+        if self.level == OptLevel::None {
+            return program;
+        }
+        self.optimize_block(program)
+    }
+
+    fn optimize_block(&mut self, statements: Vec<Stmt>) -> Vec<Stmt> {
+        let mut result = Vec::new();
+        let mut iter = statements.into_iter();
+        while let Some(stmt) = iter.next() {
+            let is_return = matches!(stmt.kind, StmtKind::Return(_));
+            result.extend(self.optimize_statement(stmt));
+            if is_return {
+                let removed = iter.count();
+                self.stats.removed_unreachable += removed;
+                break;
+            }
+        }
+        result
+    }
+
+    fn optimize_scoped_block(&mut self, statements: Vec<Stmt>) -> Vec<Stmt> {
+        self.constants.push(HashMap::new());
+        let result = self.optimize_block(statements);
+        self.constants.pop();
+        result
+    }
+
+    /// Um statement pode virar zero ou vários (ex: `if true { ... }` vira o corpo)
+    fn optimize_statement(&mut self, stmt: Stmt) -> Vec<Stmt> {
+        let span = stmt.span;
+        let kind = match stmt.kind {
+            StmtKind::Expression(expr) => StmtKind::Expression(self.optimize_expression(expr)),
+            StmtKind::FuncCall(expr) => StmtKind::FuncCall(self.optimize_expression(expr)),
+            StmtKind::VarDeclaration(mut decl) => {
+                decl.value = self.optimize_expression(decl.value);
+                self.shadow(&decl.name);
+                StmtKind::VarDeclaration(decl)
+            }
+            StmtKind::MutDeclaration(mut decl) => {
+                decl.value = self.optimize_expression(decl.value);
+                self.shadow(&decl.name);
+                StmtKind::MutDeclaration(decl)
+            }
+            StmtKind::ConstDeclaration(mut decl) => {
+                decl.value = self.optimize_expression(decl.value);
+                match &decl.value.kind {
+                    ExprKind::Literal(lit) if self.level >= OptLevel::Full && is_scalar(lit) => {
+                        self.define_constant(&decl.name, lit.clone());
+                    }
+                    _ => self.shadow(&decl.name),
+                }
+                StmtKind::ConstDeclaration(decl)
+            }
+            StmtKind::VarAssignment(mut set) => {
+                set.value = self.optimize_expression(set.value);
+                StmtKind::VarAssignment(set)
+            }
+            StmtKind::Print(exprs) => {
+                StmtKind::Print(exprs.into_iter().map(|e| self.optimize_expression(e)).collect())
+            }
+            StmtKind::Input { name, var_type, type_span } => {
+                self.shadow(&name);
+                StmtKind::Input { name, var_type, type_span }
+            }
+            StmtKind::FuncDeclaration(mut func) => {
+                self.shadow(&func.name);
+                self.constants.push(HashMap::new());
+                for param in &func.params {
+                    self.shadow(&param.name);
+                }
+                func.body = self.optimize_block(func.body);
+                self.constants.pop();
+                StmtKind::FuncDeclaration(func)
+            }
+            StmtKind::Return(expr) => StmtKind::Return(self.optimize_expression(expr)),
+            StmtKind::Conditional(cond) => return self.optimize_conditional(cond, span),
+            StmtKind::Loop(LoopStmt::While { condition, body }) => {
+                let condition = self.optimize_expression(condition);
+                if let ExprKind::Literal(LiteralValue::Boolean(false)) = condition.kind {
+                    self.stats.removed_branches += 1;
+                    return Vec::new();
+                }
+                let body = self.optimize_scoped_block(body);
+                StmtKind::Loop(LoopStmt::While { condition, body })
+            }
+            StmtKind::Loop(LoopStmt::For { iterator, iterable, body }) => {
+                let iterable = self.optimize_expression(iterable);
+                self.constants.push(HashMap::new());
+                self.shadow(&iterator);
+                let body = self.optimize_block(body);
+                self.constants.pop();
+                StmtKind::Loop(LoopStmt::For { iterator, iterable, body })
+            }
+            StmtKind::ListDeclaration(mut decl) => {
+                decl.value = self.optimize_expression(decl.value);
+                self.shadow(&decl.name);
+                StmtKind::ListDeclaration(decl)
+            }
+            StmtKind::DictDeclaration(mut decl) => {
+                decl.value = self.optimize_expression(decl.value);
+                self.shadow(&decl.name);
+                StmtKind::DictDeclaration(decl)
+            }
+            StmtKind::ListPush(mut push) => {
+                push.value = self.optimize_expression(push.value);
+                StmtKind::ListPush(push)
+            }
+            StmtKind::DictSet(mut set) => {
+                set.key = self.optimize_expression(set.key);
+                set.value = self.optimize_expression(set.value);
+                StmtKind::DictSet(set)
+            }
+            StmtKind::Import(path) => StmtKind::Import(path),
+            alias @ StmtKind::TypeAlias { .. } => alias,
+            StmtKind::Test { name, name_span, body } => {
+                StmtKind::Test { name, name_span, body: self.optimize_scoped_block(body) }
+            }
+        };
+        vec![Stmt { kind, span }]
+    }
+
+    fn optimize_conditional(&mut self, cond: ConditionalStmt, span: Span) -> Vec<Stmt> {
+        let mut branches = Vec::new();
+        for block in std::iter::once(cond.if_block).chain(cond.elif_blocks) {
+            let condition = self.optimize_expression(block.condition);
+            match condition.kind {
+                ExprKind::Literal(LiteralValue::Boolean(false)) => {
+                    self.stats.removed_branches += 1;
+                }
+                ExprKind::Literal(LiteralValue::Boolean(true)) => {
+                    // Este ramo sempre executa: ele vira o `else` e os seguintes são descartados.
+                    let body = self.optimize_scoped_block(block.body);
+                    return self.finish_conditional(branches, Some(body), span);
+                }
+                _ => {
+                    let body = self.optimize_scoped_block(block.body);
+                    branches.push(IfBlock { condition, body });
+                }
+            }
+        }
+        let else_block = cond.else_block.map(|body| self.optimize_scoped_block(body));
+        self.finish_conditional(branches, else_block, span)
+    }
+
+    fn finish_conditional(&mut self, mut branches: Vec<IfBlock>, else_block: Option<Vec<Stmt>>, span: Span) -> Vec<Stmt> {
+        if branches.is_empty() {
+            // Nenhuma condição restou: o `else` (se houver) executa incondicionalmente.
+            // O interpretador não cria escopo para blocos de `if`, então inlinar é seguro.
+            return else_block.unwrap_or_default();
+        }
+        let if_block = branches.remove(0);
+        vec![Stmt {
+            kind: StmtKind::Conditional(ConditionalStmt {
+                if_block,
+                elif_blocks: branches,
+                else_block,
+            }),
+            span,
+        }]
+    }
+
+    fn optimize_expression(&mut self, expr: Expr) -> Expr {
+        let span = expr.span;
+        let kind = match expr.kind {
+            ExprKind::Variable(name) => match self.lookup_constant(&name) {
+                Some(lit) => {
+                    self.stats.inlined_constants += 1;
+                    ExprKind::Literal(lit)
+                }
+                None => ExprKind::Variable(name),
+            },
+            ExprKind::Literal(LiteralValue::List(items)) => ExprKind::Literal(LiteralValue::List(
+                items.into_iter().map(|e| self.optimize_expression(e)).collect(),
+            )),
+            ExprKind::Literal(LiteralValue::Dict(pairs)) => ExprKind::Literal(LiteralValue::Dict(
+                pairs
+                    .into_iter()
+                    .map(|(k, v)| (self.optimize_expression(k), self.optimize_expression(v)))
+                    .collect(),
+            )),
+            ExprKind::Literal(lit) => ExprKind::Literal(lit),
+            ExprKind::Unary { op, expr } => {
+                let inner = self.optimize_expression(*expr);
+                match (&op, &inner.kind) {
+                    (UnaryOp::Negative, ExprKind::Literal(LiteralValue::Number(n))) => {
+                        self.stats.folded_expressions += 1;
+                        ExprKind::Literal(LiteralValue::Number(-n))
+                    }
+                    _ => ExprKind::Unary { op, expr: Box::new(inner) },
+                }
+            }
+            ExprKind::Binary { op, left, right } => {
+                let left = self.optimize_expression(*left);
+                let right = self.optimize_expression(*right);
+                let folded = match (&left.kind, &right.kind) {
+                    (ExprKind::Literal(l), ExprKind::Literal(r)) => fold_binary(&op, l, r),
+                    _ => None,
+                };
+                match folded {
+                    Some(lit) => {
+                        self.stats.folded_expressions += 1;
+                        ExprKind::Literal(lit)
+                    }
+                    None => ExprKind::Binary { op, left: Box::new(left), right: Box::new(right) },
+                }
+            }
+            ExprKind::FunctionCall { callee, args } => ExprKind::FunctionCall {
+                callee: Box::new(self.optimize_expression(*callee)),
+                args: args.into_iter().map(|a| self.optimize_expression(a)).collect(),
+            },
+            ExprKind::PropertyAccess { target, property } => ExprKind::PropertyAccess {
+                target: Box::new(self.optimize_expression(*target)),
+                property,
+            },
+            ExprKind::IndexAccess { target, index } => ExprKind::IndexAccess {
+                target: Box::new(self.optimize_expression(*target)),
+                index: Box::new(self.optimize_expression(*index)),
+            },
+        };
+        Expr { kind, span }
+    }
+
+    fn define_constant(&mut self, name: &str, value: LiteralValue) {
+        if let Some(scope) = self.constants.last_mut() {
+            scope.insert(name.to_string(), Some(value));
+        }
+    }
+
+    fn shadow(&mut self, name: &str) {
+        if let Some(scope) = self.constants.last_mut() {
+            scope.insert(name.to_string(), None);
+        }
+    }
+
+    fn lookup_constant(&self, name: &str) -> Option<LiteralValue> {
+        if self.level < OptLevel::Full {
+            return None;
+        }
+        for scope in self.constants.iter().rev() {
+            if let Some(entry) = scope.get(name) {
+                return entry.clone();
+            }
+        }
+        None
+    }
+}
+
+fn is_scalar(lit: &LiteralValue) -> bool {
+    matches!(
+        lit,
+        LiteralValue::Number(_) | LiteralValue::String(_) | LiteralValue::Boolean(_) | LiteralValue::Nil
+    )
+}
+
+/// Texto de um literal escalar, igual ao `Display` de `Value` usado pelo interpretador
+fn scalar_to_string(lit: &LiteralValue) -> Option<String> {
+    match lit {
+        LiteralValue::Number(n) => Some(format!("{}", n)),
+        LiteralValue::String(s) => Some(s.clone()),
+        LiteralValue::Boolean(b) => Some(format!("{}", b)),
+        LiteralValue::Nil => Some("nil".to_string()),
+        _ => None,
+    }
+}
+
+/// Avalia uma operação binária entre literais com a mesma semântica do
+/// interpretador. Retorna `None` quando a operação precisa ficar para o
+/// runtime (tipos incompatíveis, divisão por zero, coleções...).
+fn fold_binary(op: &BinaryOp, left: &LiteralValue, right: &LiteralValue) -> Option<LiteralValue> {
+    use LiteralValue::{Boolean, Number, String as Str};
+
+    match (op, left, right) {
+        (BinaryOp::Add, Number(a), Number(b)) => Some(Number(a + b)),
+        (BinaryOp::Add, Str(_), _) | (BinaryOp::Add, _, Str(_)) => {
+            let l = scalar_to_string(left)?;
+            let r = scalar_to_string(right)?;
+            Some(Str(l + &r))
+        }
+        (BinaryOp::Subtract, Number(a), Number(b)) => Some(Number(a - b)),
+        (BinaryOp::Multiply, Number(a), Number(b)) => Some(Number(a * b)),
+        (BinaryOp::Divide, Number(a), Number(b)) if *b != 0.0 => Some(Number(a / b)),
+        (BinaryOp::Equals, l, r) if is_scalar(l) && is_scalar(r) => Some(Boolean(l == r)),
+        (BinaryOp::NotEquals, l, r) if is_scalar(l) && is_scalar(r) => Some(Boolean(l != r)),
+        (BinaryOp::GreaterThan, Number(a), Number(b)) => Some(Boolean(a > b)),
+        (BinaryOp::LessThan, Number(a), Number(b)) => Some(Boolean(a < b)),
+        (BinaryOp::GreaterThanOrEquals, Number(a), Number(b)) => Some(Boolean(a >= b)),
+        (BinaryOp::LessThanOrEquals, Number(a), Number(b)) => Some(Boolean(a <= b)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{optimize, OptLevel, OptimizationStats};
+    use crate::ast::{ExprKind, LiteralValue, Program, StmtKind};
+    use crate::interpreter::Interpreter;
+    use crate::parser::parse_program;
+
+    fn optimized(source: &str, level: OptLevel) -> (Program, OptimizationStats) {
+        optimize(parse_program(source).expect("o programa de teste não compila"), level)
+    }
+
+    /// Literal a que o último statement (uma expressão) foi reduzido, se algum
+    fn folded(source: &str) -> Option<LiteralValue> {
+        let (program, _) = optimized(source, OptLevel::Full);
+        match &program.last()?.kind {
+            StmtKind::Expression(expr) => match &expr.kind {
+                ExprKind::Literal(value) => Some(value.clone()),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Resultado do programa no interpretador, como texto (ou o erro)
+    fn run(program: Program) -> String {
+        match Interpreter::new().interpret_value(program) {
+            Ok(Some(value)) => value.to_string(),
+            Ok(None) => String::new(),
+            Err(_) => "erro".to_string(),
+        }
+    }
+
+    #[test]
+    fn folds_literal_arithmetic() {
+        assert_eq!(folded("1 + 2 * 3;"), Some(LiteralValue::Number(7.0)));
+        assert_eq!(folded("\"n = \" + 1;"), Some(LiteralValue::String("n = 1".to_string())));
+        assert_eq!(folded("1 < 2;"), Some(LiteralValue::Boolean(true)));
+        assert_eq!(folded("const A = 2;\nA * 5;"), Some(LiteralValue::Number(10.0)));
+    }
+
+    #[test]
+    fn leaves_runtime_errors_to_the_interpreter() {
+        assert_eq!(folded("1 / 0;"), None);
+        assert_eq!(folded("true + 1;"), None);
+        assert_eq!(folded("[1] + [2];"), None);
+    }
+
+    #[test]
+    fn removes_dead_branches() {
+        let (program, stats) = optimized("if false {\n    print(1);\n} else {\n    print(2);\n}", OptLevel::Basic);
+        assert_eq!(stats.removed_branches, 1);
+        assert_eq!(program.len(), 1);
+        assert!(matches!(program[0].kind, StmtKind::Print(_)), "{:?}", program);
+
+        let (program, _) = optimized("if 1 > 2 {\n    print(1);\n}", OptLevel::Basic);
+        assert!(program.is_empty(), "{:?}", program);
+    }
+
+    #[test]
+    fn none_level_keeps_program_unchanged() {
+        let source = "if false {\n    print(1);\n}\n1 + 2;";
+        let (program, stats) = optimized(source, OptLevel::None);
+        assert_eq!(program, parse_program(source).unwrap());
+        assert_eq!(stats.folded_expressions + stats.removed_branches, 0);
+    }
+
+    #[test]
+    fn folding_preserves_results() {
+        let programs = [
+            "1 + 2 * 3 - 4 / 2;",
+            "\"a\" + 1 + 2;",
+            "1 + 2 + \"a\";",
+            "\"x\" + true + nil;",
+            "1 == \"1\";",
+            "nil != false;",
+            "const A = 3;\nfun f(x) {\n    return x * A;\n}\nf(2) + A;",
+            "const A = 1;\nfun f(A) {\n    return A + 1;\n}\nf(10);",
+            "mut x = 0;\nif 2 > 1 {\n    x = 1;\n} else {\n    x = 2;\n}\nx;",
+            "1 / 0;",
+        ];
+        for source in programs {
+            let unoptimized = run(optimized(source, OptLevel::None).0);
+            let full = run(optimized(source, OptLevel::Full).0);
+            assert_eq!(unoptimized, full, "resultado diferente com otimização em:\n{}", source);
+        }
+    }
+}
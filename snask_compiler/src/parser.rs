@@ -1,12 +1,18 @@
 use crate::ast::{
     Program, Stmt, StmtKind, Expr, ExprKind, VarDecl, MutDecl, ConstDecl, LiteralValue, 
-    BinaryOp, UnaryOp, ConditionalStmt, IfBlock, LoopStmt, FuncDecl, Location
+    BinaryOp, UnaryOp, ConditionalStmt, IfBlock, LoopStmt, FuncDecl, Param, Location
 };
+use crate::declarations::{FunctionDecl, MemberDecl, ModuleDecl, ParamDecl};
+use crate::diagnostics::{Annotation, Diagnostic};
+use crate::span::{Position, Span};
 use crate::types::Type;
+use std::collections::HashMap;
 use std::iter::Peekable;
 use std::str::FromStr;
 use std::str::Chars;
 
+/// Erros de sintaxe viram diagnósticos com o trecho exato do código
+type ParseResult<T> = Result<T, Box<Diagnostic>>;
 
 
 #[derive(Debug, PartialEq, Clone)]
@@ -62,6 +68,8 @@ pub enum Token {
     Dot(Location),
     Colon(Location),
     Semicolon(Location),
+    Question(Location),
+    Pipe(Location),
 
     // End of File
     Eof(Location),
@@ -113,6 +121,8 @@ impl Token {
             Token::Dot(loc) |
             Token::Colon(loc) |
             Token::Semicolon(loc) |
+            Token::Question(loc) |
+            Token::Pipe(loc) |
             Token::Eof(loc) => loc,
         }
     }
@@ -162,29 +172,66 @@ impl Token {
             Token::Dot(_) => "'.'".to_string(),
             Token::Colon(_) => "':'".to_string(),
             Token::Semicolon(_) => "';'".to_string(),
+            Token::Question(_) => "'?'".to_string(),
+            Token::Pipe(_) => "'|'".to_string(),
             Token::Eof(_) => "fim de arquivo".to_string(),
         }
     }
 }
 
+/// Comentário `// ...`, guardado à parte dos tokens para que o formatador possa reproduzi-lo
+#[derive(Debug, PartialEq, Clone)]
+pub struct Comment {
+    /// Texto a partir do `//`, sem os espaços do fim da linha
+    pub text: String,
+    pub span: Span,
+}
+
 pub struct Tokenizer<'a> {
     chars: Peekable<Chars<'a>>,
     line: usize,
     column: usize,
+    offset: usize,
+    /// Erros léxicos; o caractere problemático é descartado e a leitura continua
+    diagnostics: Vec<Diagnostic>,
+    /// Comentários encontrados até agora, na ordem do código
+    comments: Vec<Comment>,
+    /// Linhas `///` ainda sem o token que documentam
+    pending_doc: Vec<String>,
+    /// Comentários `///` pela posição (offset) do token que vem logo depois deles
+    docs: HashMap<usize, String>,
+    /// Linha onde terminou o último token; um `///` depois de código na mesma linha é comentário comum
+    last_token_line: usize,
 }
 
 impl<'a> Tokenizer<'a> {
-    fn new(input: &'a str) -> Self {
+    pub fn new(input: &'a str) -> Self {
         Tokenizer {
             chars: input.chars().peekable(),
             line: 1,
             column: 1,
+            offset: 0,
+            diagnostics: Vec::new(),
+            comments: Vec::new(),
+            pending_doc: Vec::new(),
+            docs: HashMap::new(),
+            last_token_line: 0,
         }
     }
 
+    pub fn take_comments(&mut self) -> Vec<Comment> {
+        std::mem::take(&mut self.comments)
+    }
+
+    /// Retira o comentário `///` que precede o token na posição `offset`
+    pub fn take_doc(&mut self, offset: usize) -> Option<String> {
+        self.docs.remove(&offset)
+    }
+
     fn advance(&mut self) -> Option<char> {
         let ch = self.chars.next();
         if let Some(c) = ch {
+            self.offset += c.len_utf8();
             if c == '\n' {
                 self.line += 1;
                 self.column = 1;
@@ -215,80 +262,114 @@ impl<'a> Tokenizer<'a> {
         }
     }
 
-    pub fn next_token(&mut self) -> Result<Token, String> {
-        self.skip_whitespace();
+    fn current_position(&self) -> Position {
+        Position::new(self.line, self.column, self.offset)
+    }
 
-        let loc = self.current_location();
-        let ch = match self.advance() {
-            Some(c) => c,
-            None => return Ok(Token::Eof(loc)),
-        };
+    fn error(&mut self, code: &str, message: String, span: Span, label: &str) {
+        self.diagnostics.push(
+            Diagnostic::error(message)
+                .with_code(code.to_string())
+                .with_annotation(Annotation::primary(span, label.to_string())),
+        );
+    }
 
-        let token = if ch.is_alphabetic() || ch == '_' {
-            self.read_identifier_or_keyword(ch, loc)
-        } else if ch.is_digit(10) {
-            self.read_number(ch, loc)
-        } else {
-            match ch {
-                '(' => Token::LeftParen(loc),
-                ')' => Token::RightParen(loc),
-                '{' => Token::LeftBrace(loc),
-                '}' => Token::RightBrace(loc),
-                '[' => Token::LeftBracket(loc),
-                ']' => Token::RightBracket(loc),
-                ',' => Token::Comma(loc),
-                '.' => Token::Dot(loc),
-                ':' => Token::Colon(loc),
-                ';' => Token::Semicolon(loc),
-                '+' => Token::Plus(loc),
-                '-' => Token::Minus(loc),
-                '*' => Token::Star(loc),
-                '/' => {
-                    if self.match_char('/') {
-                        while self.peek() != Some(&'\n') && self.peek().is_some() {
-                            self.advance();
+    /// Lê o próximo token e o trecho do código que ele ocupa
+    pub fn next_token(&mut self) -> (Token, Span) {
+        loop {
+            self.skip_whitespace();
+
+            let start = self.current_position();
+            let loc = self.current_location();
+            let ch = match self.advance() {
+                Some(c) => c,
+                None => return (Token::Eof(loc), Span::single(start)),
+            };
+
+            let token = if ch.is_alphabetic() || ch == '_' {
+                self.read_identifier_or_keyword(ch, loc)
+            } else if ch.is_ascii_digit() {
+                self.read_number(ch, loc, start)
+            } else {
+                match ch {
+                    '(' => Token::LeftParen(loc),
+                    ')' => Token::RightParen(loc),
+                    '{' => Token::LeftBrace(loc),
+                    '}' => Token::RightBrace(loc),
+                    '[' => Token::LeftBracket(loc),
+                    ']' => Token::RightBracket(loc),
+                    ',' => Token::Comma(loc),
+                    '.' => Token::Dot(loc),
+                    ':' => Token::Colon(loc),
+                    ';' => Token::Semicolon(loc),
+                    '?' => Token::Question(loc),
+                    '|' => Token::Pipe(loc),
+                    '+' => Token::Plus(loc),
+                    '-' => Token::Minus(loc),
+                    '*' => Token::Star(loc),
+                    '/' => {
+                        if self.match_char('/') {
+                            let mut text = String::from("//");
+                            while self.peek() != Some(&'\n') && self.peek().is_some() {
+                                text.push(self.advance().unwrap());
+                            }
+                            let text = text.trim_end().to_string();
+                            let end = Position::new(start.line, start.column + text.chars().count(), start.offset + text.len());
+                            if let Some(doc) = text.strip_prefix("///").filter(|doc| !doc.starts_with('/')) {
+                                if start.line != self.last_token_line {
+                                    self.pending_doc.push(doc.strip_prefix(' ').unwrap_or(doc).to_string());
+                                }
+                            }
+                            self.comments.push(Comment { text, span: Span::new(start, end) });
+                            continue; // Comentário: segue para o próximo token
+                        } else {
+                            Token::Slash(loc)
                         }
-                        return self.next_token(); // Recursively call to get the next token after comment
-                    } else {
-                        Token::Slash(loc)
                     }
-                }
-                '=' => {
-                    if self.match_char('=') {
-                        Token::EqualEqual(loc)
-                    } else {
-                        Token::Equal(loc)
+                    '=' => {
+                        if self.match_char('=') {
+                            Token::EqualEqual(loc)
+                        } else {
+                            Token::Equal(loc)
+                        }
                     }
-                }
-                '!' => {
-                    if self.match_char('=') {
-                        Token::BangEqual(loc)
-                    } else {
-                        return Err(format!("Caractere inesperado: {} na linha {}, coluna {}", ch, loc.line, loc.column));
+                    '!' => {
+                        if self.match_char('=') {
+                            Token::BangEqual(loc)
+                        } else {
+                            let span = Span::new(start, self.current_position());
+                            self.error("P003", "Caractere inesperado: !".to_string(), span, "talvez você quis dizer '!='");
+                            continue;
+                        }
                     }
-                }
-                '<' => {
-                    if self.match_char('=') {
-                        Token::LessEqual(loc)
-                    } else {
-                        Token::Less(loc)
+                    '<' => {
+                        if self.match_char('=') {
+                            Token::LessEqual(loc)
+                        } else {
+                            Token::Less(loc)
+                        }
                     }
-                }
-                '>' => {
-                    if self.match_char('=') {
-                        Token::GreaterEqual(loc)
-                    } else {
-                        Token::Greater(loc)
+                    '>' => {
+                        if self.match_char('=') {
+                            Token::GreaterEqual(loc)
+                        } else {
+                            Token::Greater(loc)
+                        }
+                    }
+                    '"' => self.read_string(loc, start),
+                    _ => {
+                        let span = Span::new(start, self.current_position());
+                        self.error("P003", format!("Caractere inesperado: {}", ch), span, "caractere não reconhecido");
+                        continue;
                     }
                 }
-                '"' => self.read_string(loc),
-                _ => return Err(format!(
-                    "Caractere inesperado: {} na linha {}, coluna {}",
-                    ch, loc.line, loc.column
-                )),
+            };
+            if !self.pending_doc.is_empty() {
+                self.docs.insert(start.offset, std::mem::take(&mut self.pending_doc).join("\n"));
             }
-        };
-        Ok(token)
+            self.last_token_line = self.line;
+            return (token, Span::new(start, self.current_position()));
+        }
     }
 
     fn skip_whitespace(&mut self) {
@@ -335,20 +416,27 @@ impl<'a> Tokenizer<'a> {
         }
     }
 
-    fn read_number(&mut self, first_char: char, loc: Location) -> Token {
+    fn read_number(&mut self, first_char: char, loc: Location, start: Position) -> Token {
         let mut number = String::new();
         number.push(first_char);
         while let Some(&c) = self.peek() {
-            if c.is_digit(10) || c == '.' {
+            if c.is_ascii_digit() || c == '.' {
                 number.push(self.advance().unwrap());
             } else {
                 break;
             }
         }
-        Token::Number(number.parse().unwrap(), loc)
+        match number.parse() {
+            Ok(n) => Token::Number(n, loc),
+            Err(_) => {
+                let span = Span::new(start, self.current_position());
+                self.error("P005", format!("Número inválido: {}", number), span, "número mal formado");
+                Token::Number(0.0, loc)
+            }
+        }
     }
 
-    fn read_string(&mut self, loc: Location) -> Token {
+    fn read_string(&mut self, loc: Location, start: Position) -> Token {
         let mut s = String::new();
         while let Some(c) = self.peek().copied() {
             if c != '"' {
@@ -357,7 +445,10 @@ impl<'a> Tokenizer<'a> {
                 break;
             }
         }
-        self.advance();
+        if self.advance().is_none() {
+            let span = Span::new(start, self.current_position());
+            self.error("P004", "String não fechada".to_string(), span, "falta o '\"' de fechamento");
+        }
         Token::String(s, loc)
     }
 }
@@ -379,79 +470,163 @@ enum Precedence {
 pub struct Parser<'a> {
     tokenizer: Tokenizer<'a>,
     current_token: Token,
+    current_span: Span,
     peek_token: Token, // Adicionado para permitir o lookahead
+    peek_span: Span,
+    /// Fim do último token consumido; é onde termina o nó que está sendo analisado
+    previous_end: Position,
+    /// Quantos tokens já foram consumidos; usado para garantir progresso na recuperação de erros
+    consumed: usize,
+    /// `{` descartados durante a recuperação, cujos `}` não devem gerar novos erros
+    skipped_braces: usize,
+    diagnostics: Vec<Diagnostic>,
 }
 
 impl<'a> Parser<'a> {
-    pub fn new(input: &'a str) -> Result<Self, String> {
+    pub fn new(input: &'a str) -> Self {
         let mut tokenizer = Tokenizer::new(input);
-        let current_token = tokenizer.next_token()?;
-        let peek_token = tokenizer.next_token()?; // Initialize peek_token
-        Ok(Parser {
+        let (current_token, current_span) = tokenizer.next_token();
+        let (peek_token, peek_span) = tokenizer.next_token(); // Initialize peek_token
+        Parser {
             tokenizer,
             current_token,
+            current_span,
             peek_token,
-        })
+            peek_span,
+            previous_end: current_span.start,
+            consumed: 0,
+            skipped_braces: 0,
+            diagnostics: Vec::new(),
+        }
     }
 
     pub fn debug_tokens(input: &'a str) {
         let mut tokenizer = Tokenizer::new(input);
         loop {
             match tokenizer.next_token() {
-                Ok(Token::Eof(_)) => break,
-                Ok(token) => println!("{:?}", token),
-                Err(e) => println!("Token Error: {}", e),
+                (Token::Eof(_), _) => break,
+                (token, span) => println!("{:?} @ {}..{}", token, span.start, span.end),
             }
         }
+        for diagnostic in &tokenizer.diagnostics {
+            println!("Token Error: {}", diagnostic.message);
+        }
     }
 
-    fn consume_token(&mut self, expected_variant: &Token) -> Result<Token, String> {
+    /// Avança para o próximo token, retornando o atual
+    fn advance(&mut self) -> Token {
+        let (next_token, next_span) = self.tokenizer.next_token();
+        self.previous_end = self.current_span.end;
+        let peek_token = std::mem::replace(&mut self.peek_token, next_token);
+        self.current_span = std::mem::replace(&mut self.peek_span, next_span);
+        self.consumed += 1;
+        std::mem::replace(&mut self.current_token, peek_token)
+    }
+
+    /// Erro "esperado X, mas encontrado Y" apontando para o token atual
+    fn error_expected(&self, expected: &str) -> Box<Diagnostic> {
+        Box::new(
+            Diagnostic::error(format!("Esperado {}, mas encontrado {}", expected, self.current_token.friendly_name()))
+                .with_code("P001".to_string())
+                .with_annotation(Annotation::primary(self.current_span, format!("esperado {}", expected))),
+        )
+    }
+
+    fn consume_token(&mut self, expected_variant: &Token) -> ParseResult<Token> {
         if std::mem::discriminant(&self.current_token) == std::mem::discriminant(expected_variant) {
-            let consumed_token = self.current_token.clone();
-            self.current_token = self.peek_token.clone();
-            self.peek_token = self.tokenizer.next_token()?;
-            Ok(consumed_token)
+            Ok(self.advance())
         } else {
-            let found_loc = self.current_token.get_location().clone();
-            Err(format!(
-                "Esperado {}, mas encontrado {} na linha {}, coluna {}",
-                expected_variant.friendly_name(), 
-                self.current_token.friendly_name(), 
-                found_loc.line, 
-                found_loc.column
-            ))
+            Err(self.error_expected(&expected_variant.friendly_name()))
         }
     }
     
-    fn consume_identifier(&mut self) -> Result<(String, Location), String> {
-        let (name, loc) = match self.current_token.clone() {
-            Token::Identifier(s, loc) => (s, loc),
-            _ => {
-                let found_loc = self.current_token.get_location().clone();
-                return Err(format!("Esperado identificador, mas encontrado {} na linha {}, coluna {}", self.current_token.friendly_name(), found_loc.line, found_loc.column));
+    fn consume_identifier(&mut self) -> ParseResult<(String, Location)> {
+        match self.current_token.clone() {
+            Token::Identifier(s, loc) => {
+                self.advance();
+                Ok((s, loc))
             }
-        };
-        // This was the bug: it was consuming directly from tokenizer,
-        // bypassing the peek_token mechanism.
-        // It should update current_token from peek_token, and peek_token from tokenizer.
-        self.current_token = self.peek_token.clone();
-        self.peek_token = self.tokenizer.next_token()?;
-        Ok((name, loc))
+            _ => Err(self.error_expected("identificador")),
+        }
     }
     
+    /// Trecho que vai do início de `start` até o fim do último token consumido
+    fn span_from(&self, start: Span) -> Span {
+        Span::new(start.start, self.previous_end)
+    }
+
     fn at_end(&self) -> bool {
         matches!(self.current_token, Token::Eof(_))
     }
 
-    pub fn parse_program(&mut self) -> Result<Program, String> {
+    /// Analisa o arquivo inteiro. Erros de sintaxe são acumulados em `diagnostics`
+    /// e a análise continua a partir do próximo statement.
+    pub fn parse_program(&mut self) -> Program {
         let mut program = Vec::new();
         while !self.at_end() {
-            program.push(self.parse_statement()?);
+            if matches!(self.current_token, Token::RightBrace(_)) && self.skipped_braces > 0 {
+                self.skipped_braces -= 1;
+                self.advance();
+                continue;
+            }
+            let start = self.consumed;
+            match self.parse_statement() {
+                Ok(stmt) => program.push(stmt),
+                Err(diagnostic) => self.recover(*diagnostic, start),
+            }
+        }
+        program
+    }
+
+    /// Registra o erro e descarta tokens até um ponto seguro para recomeçar
+    fn recover(&mut self, diagnostic: Diagnostic, start: usize) {
+        self.diagnostics.push(diagnostic);
+        self.synchronize();
+        if self.consumed == start && !self.at_end() {
+            // Nada foi consumido (ex: um '}' solto): pula o token para não entrar em loop
+            self.advance();
         }
-        Ok(program)
     }
 
-    fn desugar_interpolation(&self, s: &str, loc: Location) -> Result<Expr, String> {
+    /// Avança até o fim do statement atual (`;`), um `}` ou o início de outro statement
+    fn synchronize(&mut self) {
+        loop {
+            match self.current_token {
+                Token::Eof(_) | Token::RightBrace(_) => return,
+                Token::Semicolon(_) => {
+                    self.advance();
+                    return;
+                }
+                Token::Let(_) | Token::Mut(_) | Token::Const(_) | Token::Print(_) | Token::Input(_) |
+                Token::Fun(_) | Token::Return(_) | Token::If(_) | Token::While(_) | Token::For(_) |
+                Token::Import(_) => return,
+                Token::LeftBrace(_) => {
+                    self.skipped_braces += 1;
+                    self.advance();
+                }
+                _ => {
+                    self.advance();
+                }
+            }
+        }
+    }
+
+    /// Todos os erros léxicos e sintáticos encontrados, na ordem do código
+    pub fn take_diagnostics(&mut self) -> Vec<Diagnostic> {
+        let mut diagnostics = std::mem::take(&mut self.tokenizer.diagnostics);
+        diagnostics.append(&mut self.diagnostics);
+        diagnostics.sort_by_key(|d| d.annotations.first().map(|a| a.span.start.offset).unwrap_or(0));
+        diagnostics
+    }
+
+    fn desugar_interpolation(&self, s: &str, span: Span) -> ParseResult<Expr> {
+        let error = |message: &str| {
+            Box::new(
+                Diagnostic::error(message.to_string())
+                    .with_code("P008".to_string())
+                    .with_annotation(Annotation::primary(span, "nesta string".to_string())),
+            )
+        };
         let mut template = String::new();
         let mut args = Vec::new();
         let mut chars = s.chars().peekable();
@@ -478,20 +653,20 @@ impl<'a> Parser<'a> {
                 }
                 
                 if !closed {
-                    return Err(format!("String interpolada não fechada na linha {}, coluna {}", loc.line, loc.column));
+                    return Err(error("String interpolada não fechada"));
                 }
                 
                 var_name = var_name.trim().to_string();
                 
                 if var_name.is_empty() {
-                    return Err(format!("Interpolação vazia '{{}}' não permitida na linha {}, coluna {}", loc.line, loc.column));
+                    return Err(error("Interpolação vazia '{}' não permitida"));
                 }
                 
                 template.push_str("{}");
                 
                 args.push(crate::ast::Expr {
                     kind: crate::ast::ExprKind::Variable(var_name),
-                    loc: loc.clone(),
+                    span,
                 });
                 
             } else if c == '}' {
@@ -502,7 +677,7 @@ impl<'a> Parser<'a> {
                         continue;
                     }
                 }
-                return Err(format!("'}}' solto em string interpolada na linha {}, coluna {}", loc.line, loc.column));
+                return Err(error("'}' solto em string interpolada"));
             } else {
                 template.push(c);
             }
@@ -511,7 +686,7 @@ impl<'a> Parser<'a> {
         let mut call_args = Vec::new();
         call_args.push(crate::ast::Expr {
             kind: crate::ast::ExprKind::Literal(crate::ast::LiteralValue::String(template)),
-            loc: loc.clone(),
+            span,
         });
         call_args.append(&mut args);
         
@@ -519,29 +694,30 @@ impl<'a> Parser<'a> {
             kind: crate::ast::ExprKind::FunctionCall {
                 callee: Box::new(crate::ast::Expr {
                     kind: crate::ast::ExprKind::Variable("format".to_string()),
-                    loc: loc.clone(),
+                    span,
                 }),
                 args: call_args,
             },
-            loc,
+            span,
         })
     }
 
-    fn parse_assignment_statement(&mut self) -> Result<Stmt, String> {
-        let loc = self.current_token.get_location().clone();
+    fn parse_assignment_statement(&mut self) -> ParseResult<Stmt> {
+        let start = self.current_span;
         let (name, _) = self.consume_identifier()?;
         self.consume_token(&Token::Equal(Location{line:0,column:0}))?;
         let value = self.parse_expression(Precedence::Assignment)?;
 
         Ok(Stmt {
             kind: StmtKind::VarAssignment(crate::ast::VarSet { name, value }),
-            loc,
+            span: self.span_from(start),
         })
     }
 
-    fn parse_statement(&mut self) -> Result<Stmt, String> {
-        if let Token::Identifier(_, loc) = self.current_token.clone() {
+    fn parse_statement(&mut self) -> ParseResult<Stmt> {
+        if let Token::Identifier(..) = self.current_token {
             if let Token::Equal(_) = self.peek_token {
+                let start = self.current_span;
                 // This is an assignment statement.
                 let (name, _) = self.consume_identifier()?;
                 self.consume_token(&Token::Equal(Location{line:0,column:0}))?;
@@ -550,10 +726,19 @@ impl<'a> Parser<'a> {
                     self.consume_token(&Token::Semicolon(Location{line:0, column:0}))?;
                 }
                 let kind = StmtKind::VarAssignment(crate::ast::VarSet { name, value });
-                return Ok(Stmt { kind, loc });
+                return Ok(Stmt { kind, span: self.span_from(start) });
             }
         }
 
+        // `type` só é palavra-chave no início de uma declaração `type Nome = ...`
+        if matches!(&self.current_token, Token::Identifier(word, _) if word == "type") && matches!(self.peek_token, Token::Identifier(..)) {
+            return self.parse_type_alias();
+        }
+        // O mesmo vale para `test "descrição" { ... }`
+        if matches!(&self.current_token, Token::Identifier(word, _) if word == "test") && matches!(self.peek_token, Token::String(..)) {
+            return self.parse_test();
+        }
+
         match self.current_token {
             Token::Let(_) => self.parse_var_declaration(),
             Token::Mut(_) => self.parse_mut_declaration(),
@@ -567,7 +752,7 @@ impl<'a> Parser<'a> {
             Token::Return(_) => self.parse_return_statement(),
             Token::Import(_) => self.parse_import_statement(),
             _ => {
-                let loc = self.current_token.get_location().clone();
+                let start = self.current_span;
                 let expr = self.parse_expression(Precedence::Assignment)?;
                 
                 let kind = match expr.kind {
@@ -577,52 +762,111 @@ impl<'a> Parser<'a> {
                 if let Token::Semicolon(_) = self.current_token {
                     self.consume_token(&Token::Semicolon(Location{line:0, column:0}))?;
                 }
-                Ok(Stmt { kind, loc })
+                Ok(Stmt { kind, span: self.span_from(start) })
             }
         }
     }
 
-    fn parse_input_statement(&mut self) -> Result<Stmt, String> {
-        let loc = self.consume_token(&Token::Input(Location{line:0, column:0}))?.get_location().clone();
+    fn parse_type_alias(&mut self) -> ParseResult<Stmt> {
+        let start = self.current_span;
+        self.advance(); // `type`
+        let name_span = self.current_span;
+        let (name, _) = self.consume_identifier()?;
+        if Type::from_str(&name).is_ok() {
+            return Err(Box::new(
+                Diagnostic::error(format!("'{}' é um tipo embutido e não pode ser redefinido", name))
+                    .with_code("P010".to_string())
+                    .with_annotation(Annotation::primary(name_span, "nome reservado".to_string()))
+                    .with_help("escolha outro nome para o alias".to_string()),
+            ));
+        }
+        self.consume_token(&Token::Equal(Location{line:0, column:0}))?;
+        let type_start = self.current_span;
+        let target = self.parse_type()?;
+        let type_span = self.span_from(type_start);
+        if let Token::Semicolon(_) = self.current_token {
+            self.consume_token(&Token::Semicolon(Location{line:0, column:0}))?;
+        }
+        Ok(Stmt {
+            kind: StmtKind::TypeAlias { name, name_span, target, type_span },
+            span: self.span_from(start),
+        })
+    }
+
+    fn parse_test(&mut self) -> ParseResult<Stmt> {
+        let start = self.current_span;
+        self.advance(); // `test`
+        let name_span = self.current_span;
+        let name = match self.current_token.clone() {
+            Token::String(s, _) => {
+                self.advance();
+                s
+            }
+            _ => return Err(self.error_expected("string com a descrição do teste")),
+        };
+        let body = self.parse_block()?;
+        Ok(Stmt {
+            kind: StmtKind::Test { name, name_span, body },
+            span: self.span_from(start),
+        })
+    }
+
+    fn parse_input_statement(&mut self) -> ParseResult<Stmt> {
+        let start = self.current_span;
+        self.consume_token(&Token::Input(Location{line:0, column:0}))?;
         let (name, _) = self.consume_identifier()?;
-        let var_type = self.parse_type_annotation()?
-            .ok_or_else(|| "Esperado anotação de tipo (ex: ': str') após nome da variável para comando 'input'.".to_string())?;
+        let (var_type, type_span) = match self.parse_type_annotation()? {
+            Some(annotation) => annotation,
+            None => {
+                return Err(Box::new(
+                    Diagnostic::error("Esperado anotação de tipo (ex: ': str') após nome da variável para comando 'input'.".to_string())
+                        .with_code("P007".to_string())
+                        .with_annotation(Annotation::primary(self.current_span, "falta o tipo aqui".to_string())),
+                ));
+            }
+        };
         Ok(Stmt {
-            kind: StmtKind::Input { name, var_type },
-            loc,
+            kind: StmtKind::Input { name, var_type, type_span },
+            span: self.span_from(start),
         })
     }
 
-    fn parse_import_statement(&mut self) -> Result<Stmt, String> {
-        let loc = self.consume_token(&Token::Import(Location{line:0, column:0}))?.get_location().clone();
+    fn parse_import_statement(&mut self) -> ParseResult<Stmt> {
+        let start = self.current_span;
+        self.consume_token(&Token::Import(Location{line:0, column:0}))?;
         let path = match self.current_token.clone() {
             Token::String(s, _) => {
                 self.consume_token(&Token::String("".to_string(), Location{line:0, column:0}))?;
                 s
             },
-            _ => return Err(format!("Esperado string literal após 'import', encontrado {}", self.current_token.friendly_name())),
+            _ => return Err(self.error_expected("string com o caminho do módulo após 'import'")),
         };
         if let Token::Semicolon(_) = self.current_token {
             self.consume_token(&Token::Semicolon(Location{line:0, column:0}))?;
         }
         Ok(Stmt {
             kind: StmtKind::Import(path),
-            loc,
+            span: self.span_from(start),
         })
     }
     
-    fn parse_block(&mut self) -> Result<Vec<Stmt>, String> {
+    fn parse_block(&mut self) -> ParseResult<Vec<Stmt>> {
         self.consume_token(&Token::LeftBrace(Location{line:0, column:0}))?;
         let mut stmts = Vec::new();
         while !matches!(self.current_token, Token::RightBrace(_)) && !self.at_end() {
-            stmts.push(self.parse_statement()?);
+            let start = self.consumed;
+            match self.parse_statement() {
+                Ok(stmt) => stmts.push(stmt),
+                Err(diagnostic) => self.recover(*diagnostic, start),
+            }
         }
         self.consume_token(&Token::RightBrace(Location{line:0, column:0}))?;
         Ok(stmts)
     }
 
-    fn parse_if_statement(&mut self) -> Result<Stmt, String> {
-        let loc = self.consume_token(&Token::If(Location{line:0, column:0}))?.get_location().clone();
+    fn parse_if_statement(&mut self) -> ParseResult<Stmt> {
+        let start = self.current_span;
+        self.consume_token(&Token::If(Location{line:0, column:0}))?;
         let condition = self.parse_expression(Precedence::Assignment)?;
         let body = self.parse_block()?;
         
@@ -645,47 +889,52 @@ impl<'a> Parser<'a> {
 
         Ok(Stmt {
             kind: StmtKind::Conditional(ConditionalStmt { if_block, elif_blocks, else_block }),
-            loc,
+            span: self.span_from(start),
         })
     }
 
-    fn parse_while_statement(&mut self) -> Result<Stmt, String> {
-        let loc = self.consume_token(&Token::While(Location{line:0, column:0}))?.get_location().clone();
+    fn parse_while_statement(&mut self) -> ParseResult<Stmt> {
+        let start = self.current_span;
+        self.consume_token(&Token::While(Location{line:0, column:0}))?;
         let condition = self.parse_expression(Precedence::Assignment)?;
         let body = self.parse_block()?;
         Ok(Stmt {
             kind: StmtKind::Loop(LoopStmt::While { condition, body }),
-            loc,
+            span: self.span_from(start),
         })
     }
 
-    fn parse_for_statement(&mut self) -> Result<Stmt, String> {
-        let loc = self.consume_token(&Token::For(Location{line:0, column:0}))?.get_location().clone();
+    fn parse_for_statement(&mut self) -> ParseResult<Stmt> {
+        let start = self.current_span;
+        self.consume_token(&Token::For(Location{line:0, column:0}))?;
         let (iterator, _) = self.consume_identifier()?;
         self.consume_token(&Token::In(Location{line:0, column:0}))?;
         let iterable = self.parse_expression(Precedence::Assignment)?;
         let body = self.parse_block()?;
         Ok(Stmt {
             kind: StmtKind::Loop(LoopStmt::For { iterator, iterable, body }),
-            loc,
+            span: self.span_from(start),
         })
     }
 
-    fn parse_fun_declaration(&mut self) -> Result<Stmt, String> {
-        let loc = self.consume_token(&Token::Fun(Location{line:0, column:0}))?.get_location().clone();
+    fn parse_fun_declaration(&mut self) -> ParseResult<Stmt> {
+        let start = self.current_span;
+        let doc = self.tokenizer.take_doc(start.start.offset);
+        self.consume_token(&Token::Fun(Location{line:0, column:0}))?;
+        let name_span = self.current_span;
         let (name, _) = self.consume_identifier()?;
         self.consume_token(&Token::LeftParen(Location{line:0, column:0}))?;
         
         let mut params = Vec::new();
         if !matches!(self.current_token, Token::RightParen(_)) {
             loop {
+                let param_start = self.current_span;
                 let (param_name, _) = self.consume_identifier()?;
-                let param_type = self.parse_type_annotation()?;
-                let param_type_resolved = match param_type {
-                    Some(t) => t,
-                    None => Type::Any,
+                let (param_type, type_span) = match self.parse_type_annotation()? {
+                    Some((t, span)) => (t, Some(span)),
+                    None => (Type::Any, None), // Default to Type::Any if not specified
                 };
-                params.push((param_name, param_type_resolved)); // Default to Type::Any if not specified
+                params.push(Param { name: param_name, param_type, span: self.span_from(param_start), type_span });
                 if !matches!(self.current_token, Token::Comma(_)) {
                     break;
                 }
@@ -694,72 +943,304 @@ impl<'a> Parser<'a> {
         }
         self.consume_token(&Token::RightParen(Location{line:0, column:0}))?;
         
-        let return_type: Option<Type> = self.parse_type_annotation()?;
+        let (return_type, return_type_span) = self.parse_type_annotation()?.unzip();
         let body = self.parse_block()?;
 
         Ok(Stmt {
-            kind: StmtKind::FuncDeclaration(FuncDecl { name, params, return_type, body }),
-            loc,
+            kind: StmtKind::FuncDeclaration(FuncDecl { name, name_span, params, return_type, return_type_span, body, doc }),
+            span: self.span_from(start),
         })
     }
 
-    fn parse_return_statement(&mut self) -> Result<Stmt, String> {
-        let loc = self.consume_token(&Token::Return(Location { line: 0, column: 0 }))?.get_location().clone();
+    fn parse_return_statement(&mut self) -> ParseResult<Stmt> {
+        let start = self.current_span;
+        self.consume_token(&Token::Return(Location { line: 0, column: 0 }))?;
         let value = self.parse_expression(Precedence::Assignment)?;
         if let Token::Semicolon(_) = self.current_token {
             self.consume_token(&Token::Semicolon(Location{line:0, column:0}))?;
         }
         Ok(Stmt {
             kind: StmtKind::Return(value),
-            loc,
+            span: self.span_from(start),
         })
     }
 
-    fn parse_type_annotation(&mut self) -> Result<Option<Type>, String> {
+    /// Lê `: tipo`, retornando o tipo e o trecho do tipo
+    fn parse_type_annotation(&mut self) -> ParseResult<Option<(Type, Span)>> {
         if matches!(self.current_token, Token::Colon(_)) {
             self.consume_token(&Token::Colon(Location{line:0, column:0}))?;
-            let (type_name, _) = self.consume_identifier()?;
-            let var_type = Type::from_str(&type_name).map_err(|_| format!("Tipo desconhecido: {}", type_name))?;
-            Ok(Some(var_type))
+            let start = self.current_span;
+            let var_type = self.parse_type()?;
+            Ok(Some((var_type, self.span_from(start))))
         } else {
             Ok(None)
         }
     }
 
-    fn parse_var_declaration(&mut self) -> Result<Stmt, String> {
-        let loc = self.consume_token(&Token::Let(Location{line:0, column:0}))?.get_location().clone();
+    /// Lê um tipo, incluindo os parâmetros de coleções: `list<int>`, `dict<str, list<float>>`.
+    /// `list` e `dict` sem parâmetros equivalem a `list<any>` e `dict<any, any>`;
+    /// um `?` no final torna o tipo opcional, aceitando também `nil` (ex: `str?`).
+    /// Tipos separados por `|` formam uma união (ex: `int | str`).
+    fn parse_type(&mut self) -> ParseResult<Type> {
+        let first = self.parse_optional_type()?;
+        if !matches!(self.current_token, Token::Pipe(_)) {
+            return Ok(first);
+        }
+        let mut members = vec![first];
+        while matches!(self.current_token, Token::Pipe(_)) {
+            self.advance();
+            members.push(self.parse_optional_type()?);
+        }
+        Ok(Type::union(members))
+    }
+
+    fn parse_optional_type(&mut self) -> ParseResult<Type> {
+        let base = self.parse_base_type()?;
+        if matches!(self.current_token, Token::Question(_)) {
+            self.advance();
+            return Ok(Type::optional(base));
+        }
+        Ok(base)
+    }
+
+    fn parse_base_type(&mut self) -> ParseResult<Type> {
+        match self.current_token.clone() {
+            Token::List(_) => {
+                self.advance();
+                if !matches!(self.current_token, Token::Less(_)) {
+                    return Ok(Type::list(Type::Any));
+                }
+                self.advance();
+                let element = self.parse_type()?;
+                self.consume_token(&Token::Greater(Location{line:0, column:0}))?;
+                Ok(Type::list(element))
+            }
+            Token::Dict(_) => {
+                self.advance();
+                if !matches!(self.current_token, Token::Less(_)) {
+                    return Ok(Type::dict(Type::Any, Type::Any));
+                }
+                self.advance();
+                let key = self.parse_type()?;
+                self.consume_token(&Token::Comma(Location{line:0, column:0}))?;
+                let value = self.parse_type()?;
+                self.consume_token(&Token::Greater(Location{line:0, column:0}))?;
+                Ok(Type::dict(key, value))
+            }
+            // `{ status: int, body: str }`: dict com campos conhecidos
+            Token::LeftBrace(_) => {
+                self.advance();
+                let mut fields: Vec<(String, Type)> = Vec::new();
+                while !matches!(self.current_token, Token::RightBrace(_)) {
+                    let field_span = self.current_span;
+                    let (field, _) = self.consume_identifier()?;
+                    if fields.iter().any(|(name, _)| *name == field) {
+                        return Err(Box::new(
+                            Diagnostic::error(format!("Campo '{}' repetido no tipo", field))
+                                .with_code("P011".to_string())
+                                .with_annotation(Annotation::primary(field_span, "campo já declarado".to_string())),
+                        ));
+                    }
+                    self.consume_token(&Token::Colon(Location{line:0, column:0}))?;
+                    let field_type = self.parse_type()?;
+                    fields.push((field, field_type));
+                    if !matches!(self.current_token, Token::Comma(_)) {
+                        break;
+                    }
+                    self.advance();
+                }
+                self.consume_token(&Token::RightBrace(Location{line:0, column:0}))?;
+                Ok(Type::Record(fields))
+            }
+            Token::LeftParen(_) => {
+                self.advance();
+                let inner = self.parse_type()?;
+                self.consume_token(&Token::RightParen(Location{line:0, column:0}))?;
+                Ok(inner)
+            }
+            Token::Nil(_) => {
+                self.advance();
+                Ok(Type::Nil)
+            }
+            // `fun(int, str): bool`; sem `: retorno`, a função retorna `any`
+            Token::Fun(_) => {
+                self.advance();
+                self.consume_token(&Token::LeftParen(Location{line:0, column:0}))?;
+                let mut params = Vec::new();
+                while !matches!(self.current_token, Token::RightParen(_)) {
+                    params.push(self.parse_type()?);
+                    if !matches!(self.current_token, Token::Comma(_)) {
+                        break;
+                    }
+                    self.advance();
+                }
+                self.consume_token(&Token::RightParen(Location{line:0, column:0}))?;
+                let return_type = match self.current_token {
+                    Token::Colon(_) => {
+                        self.advance();
+                        self.parse_type()?
+                    }
+                    _ => Type::Any,
+                };
+                Ok(Type::Function(params, Box::new(return_type)))
+            }
+            // Nomes que não são tipos embutidos são aliases (`type Nome = ...`),
+            // resolvidos na análise semântica
+            Token::Identifier(type_name, _) => {
+                self.advance();
+                Ok(Type::from_str(&type_name).unwrap_or(Type::Named(type_name)))
+            }
+            _ => Err(self.error_expected("tipo")),
+        }
+    }
+
+    /// Lê um arquivo de declarações (`.snaskd`): aliases `type Nome = tipo`, válidos
+    /// só no arquivo, e blocos `module nome { ... }` com as assinaturas dos membros
+    fn parse_declaration_file(&mut self) -> Vec<ModuleDecl> {
+        let mut aliases: HashMap<String, Type> = HashMap::new();
+        let mut modules = Vec::new();
+        while !self.at_end() {
+            let start = self.consumed;
+            let result = match &self.current_token {
+                Token::Identifier(word, _) if word == "type" => self.parse_type_alias().map(|stmt| {
+                    if let StmtKind::TypeAlias { name, target, type_span, .. } = stmt.kind {
+                        let resolved = self.resolve_declared_type(&target, type_span, &aliases);
+                        aliases.insert(name, resolved);
+                    }
+                }),
+                Token::Identifier(word, _) if word == "module" => {
+                    self.parse_module_declaration(&aliases).map(|module| modules.push(module))
+                }
+                _ => Err(self.error_expected("`type` ou `module`")),
+            };
+            if let Err(diagnostic) = result {
+                self.recover(*diagnostic, start);
+            }
+        }
+        modules
+    }
+
+    fn parse_module_declaration(&mut self, aliases: &HashMap<String, Type>) -> ParseResult<ModuleDecl> {
+        let doc = self.tokenizer.take_doc(self.current_span.start.offset);
+        self.advance(); // `module`
+        let (name, _) = self.consume_identifier()?;
+        self.consume_token(&Token::LeftBrace(Location{line:0, column:0}))?;
+        let mut members = Vec::new();
+        let mut member_docs = HashMap::new();
+        while !matches!(self.current_token, Token::RightBrace(_)) && !self.at_end() {
+            let member_doc = self.tokenizer.take_doc(self.current_span.start.offset);
+            let member = match self.current_token {
+                Token::Fun(_) => self.parse_function_signature(aliases)?,
+                Token::Const(_) => {
+                    self.advance();
+                    let (name, _) = self.consume_identifier()?;
+                    let (constant_type, type_span) = match self.parse_type_annotation()? {
+                        Some(annotation) => annotation,
+                        None => return Err(self.error_expected("':' com o tipo da constante")),
+                    };
+                    (name, MemberDecl::Constant(self.resolve_declared_type(&constant_type, type_span, aliases)))
+                }
+                _ => return Err(self.error_expected("`fun` ou `const`")),
+            };
+            if let Some(member_doc) = member_doc {
+                member_docs.insert(member.0.clone(), member_doc);
+            }
+            members.push(member);
+        }
+        self.consume_token(&Token::RightBrace(Location{line:0, column:0}))?;
+        Ok(ModuleDecl { name, members, doc, member_docs })
+    }
+
+    /// `fun nome(param: tipo, opcional?: tipo, resto: tipo...): retorno`
+    fn parse_function_signature(&mut self, aliases: &HashMap<String, Type>) -> ParseResult<(String, MemberDecl)> {
+        self.advance(); // `fun`
+        let (name, _) = self.consume_identifier()?;
+        self.consume_token(&Token::LeftParen(Location{line:0, column:0}))?;
+        let mut params = Vec::new();
+        while !matches!(self.current_token, Token::RightParen(_)) {
+            let (param_name, _) = self.consume_identifier()?;
+            let optional = matches!(self.current_token, Token::Question(_));
+            if optional {
+                self.advance();
+            }
+            self.consume_token(&Token::Colon(Location{line:0, column:0}))?;
+            let type_start = self.current_span;
+            let param_type = self.parse_type()?;
+            let param_type = self.resolve_declared_type(&param_type, self.span_from(type_start), aliases);
+            let variadic = matches!(self.current_token, Token::Dot(_));
+            if variadic {
+                for _ in 0..3 {
+                    self.consume_token(&Token::Dot(Location{line:0, column:0}))?;
+                }
+            }
+            params.push(ParamDecl { name: param_name, param_type, optional, variadic });
+            if !matches!(self.current_token, Token::Comma(_)) {
+                break;
+            }
+            self.advance();
+        }
+        self.consume_token(&Token::RightParen(Location{line:0, column:0}))?;
+        let return_type = match self.parse_type_annotation()? {
+            Some((return_type, span)) => self.resolve_declared_type(&return_type, span, aliases),
+            None => Type::Void,
+        };
+        Ok((name, MemberDecl::Function(FunctionDecl { params, return_type })))
+    }
+
+    /// Substitui os aliases de um arquivo de declarações; nomes desconhecidos viram erro
+    fn resolve_declared_type(&mut self, declared: &Type, span: Span, aliases: &HashMap<String, Type>) -> Type {
+        let diagnostics = &mut self.diagnostics;
+        declared.resolve_named(&mut |name| match aliases.get(name) {
+            Some(resolved) => resolved.clone(),
+            None => {
+                diagnostics.push(
+                    Diagnostic::error(format!("O tipo '{}' não foi declarado", name))
+                        .with_code("P012".to_string())
+                        .with_annotation(Annotation::primary(span, "tipo desconhecido".to_string())),
+                );
+                Type::Any
+            }
+        })
+    }
+
+    fn parse_var_declaration(&mut self) -> ParseResult<Stmt> {
+        let start = self.current_span;
+        self.consume_token(&Token::Let(Location{line:0, column:0}))?;
         let (name, _) = self.consume_identifier()?;
-        let var_type = self.parse_type_annotation()?;
+        let (var_type, type_span) = self.parse_type_annotation()?.unzip();
         self.consume_token(&Token::Equal(Location{line:0, column:0}))?;
         let value = self.parse_expression(Precedence::Assignment)?;
         if let Token::Semicolon(_) = self.current_token {
             self.consume_token(&Token::Semicolon(Location{line:0, column:0}))?;
         }
         Ok(Stmt {
-            kind: StmtKind::VarDeclaration(VarDecl { name, var_type, value }),
-            loc,
+            kind: StmtKind::VarDeclaration(VarDecl { name, var_type, type_span, value }),
+            span: self.span_from(start),
         })
     }
 
-    fn parse_mut_declaration(&mut self) -> Result<Stmt, String> {
-        let loc = self.consume_token(&Token::Mut(Location{line:0, column:0}))?.get_location().clone();
+    fn parse_mut_declaration(&mut self) -> ParseResult<Stmt> {
+        let start = self.current_span;
+        self.consume_token(&Token::Mut(Location{line:0, column:0}))?;
         let (name, _) = self.consume_identifier()?;
-        let var_type = self.parse_type_annotation()?;
+        let (var_type, type_span) = self.parse_type_annotation()?.unzip();
         self.consume_token(&Token::Equal(Location{line:0, column:0}))?;
         let value = self.parse_expression(Precedence::Assignment)?;
         if let Token::Semicolon(_) = self.current_token {
             self.consume_token(&Token::Semicolon(Location{line:0, column:0}))?;
         }
         Ok(Stmt {
-            kind: StmtKind::MutDeclaration(MutDecl { name, var_type, value }),
-            loc,
+            kind: StmtKind::MutDeclaration(MutDecl { name, var_type, type_span, value }),
+            span: self.span_from(start),
         })
     }
 
-    fn parse_const_declaration(&mut self) -> Result<Stmt, String> {
-        let loc = self.consume_token(&Token::Const(Location{line:0, column:0}))?.get_location().clone();
+    fn parse_const_declaration(&mut self) -> ParseResult<Stmt> {
+        let start = self.current_span;
+        let doc = self.tokenizer.take_doc(start.start.offset);
+        self.consume_token(&Token::Const(Location{line:0, column:0}))?;
         let (name, _) = self.consume_identifier()?;
-        let var_type = self.parse_type_annotation()?;
+        let (var_type, type_span) = self.parse_type_annotation()?.unzip();
         self.consume_token(&Token::Equal(Location{line:0, column:0}))?;
         let value = self.parse_expression(Precedence::Assignment)?;
         if let Token::Semicolon(_) = self.current_token {
@@ -767,13 +1248,14 @@ impl<'a> Parser<'a> {
         }
 
         Ok(Stmt {
-            kind: StmtKind::ConstDeclaration(ConstDecl { name, var_type, value }),
-            loc,
+            kind: StmtKind::ConstDeclaration(ConstDecl { name, var_type, type_span, value, doc }),
+            span: self.span_from(start),
         })
     }
 
-    fn parse_print_statement(&mut self) -> Result<Stmt, String> {
-        let loc = self.consume_token(&Token::Print(Location{line:0, column:0}))?.get_location().clone();
+    fn parse_print_statement(&mut self) -> ParseResult<Stmt> {
+        let start = self.current_span;
+        self.consume_token(&Token::Print(Location{line:0, column:0}))?;
         self.consume_token(&Token::LeftParen(Location{line:0, column:0}))?;
         let mut expressions = Vec::new();
         if !matches!(self.current_token, Token::RightParen(_)) {
@@ -791,11 +1273,11 @@ impl<'a> Parser<'a> {
         }
         Ok(Stmt {
             kind: StmtKind::Print(expressions),
-            loc,
+            span: self.span_from(start),
         })
     }
 
-    fn parse_expression(&mut self, precedence: Precedence) -> Result<Expr, String> {
+    fn parse_expression(&mut self, precedence: Precedence) -> ParseResult<Expr> {
         let mut expr = self.parse_prefix()?;
 
         while precedence <= self.get_precedence(&self.current_token) {
@@ -819,7 +1301,7 @@ impl<'a> Parser<'a> {
         }
     }
 
-    fn binary_op_from_token(&self, token: &Token) -> Result<BinaryOp, String> {
+    fn binary_op_from_token(&self, token: &Token) -> ParseResult<BinaryOp> {
         match token {
             Token::Plus(_) => Ok(BinaryOp::Add),
             Token::Minus(_) => Ok(BinaryOp::Subtract),
@@ -831,18 +1313,19 @@ impl<'a> Parser<'a> {
             Token::LessEqual(_) => Ok(BinaryOp::LessThanOrEquals),
             Token::Greater(_) => Ok(BinaryOp::GreaterThan),
             Token::GreaterEqual(_) => Ok(BinaryOp::GreaterThanOrEquals),
-            _ => Err("Operador binário inválido".to_string()),
+            _ => Err(self.error_expected("operador binário")),
         }
     }
 
-    fn parse_prefix(&mut self) -> Result<Expr, String> {
+    fn parse_prefix(&mut self) -> ParseResult<Expr> {
+        let start = self.current_span;
         let loc = self.current_token.get_location().clone();
         match self.current_token.clone() {
             Token::Number(n, _) => {
                 self.consume_token(&Token::Number(0.0, loc.clone()))?;
                 Ok(Expr {
                     kind: ExprKind::Literal(LiteralValue::Number(n)),
-                    loc,
+                    span: self.span_from(start),
                 })
             }
             Token::String(s, _) => {
@@ -850,35 +1333,35 @@ impl<'a> Parser<'a> {
                 
                 Ok(Expr {
                     kind: ExprKind::Literal(LiteralValue::String(s)),
-                    loc,
+                    span: self.span_from(start),
                 })
             }
             Token::True(_) => {
                 self.consume_token(&Token::True(loc.clone()))?;
                 Ok(Expr {
                     kind: ExprKind::Literal(LiteralValue::Boolean(true)),
-                    loc,
+                    span: self.span_from(start),
                 })
             }
             Token::False(_) => {
                 self.consume_token(&Token::False(loc.clone()))?;
                 Ok(Expr {
                     kind: ExprKind::Literal(LiteralValue::Boolean(false)),
-                    loc,
+                    span: self.span_from(start),
                 })
             }
             Token::Nil(_) => {
                 self.consume_token(&Token::Nil(loc.clone()))?;
                 Ok(Expr {
                     kind: ExprKind::Literal(LiteralValue::Nil),
-                    loc,
+                    span: self.span_from(start),
                 })
             }
             Token::Identifier(s, _) => {
                 self.consume_identifier()?;
                 Ok(Expr {
                     kind: ExprKind::Variable(s),
-                    loc,
+                    span: self.span_from(start),
                 })
             }
             Token::Minus(_) => {
@@ -886,26 +1369,28 @@ impl<'a> Parser<'a> {
                 let expr = self.parse_expression(Precedence::Unary)?;
                 Ok(Expr {
                     kind: ExprKind::Unary { op: UnaryOp::Negative, expr: Box::new(expr) },
-                    loc,
+                    span: self.span_from(start),
                 })
             }
             Token::LeftParen(_) => {
                 self.consume_token(&Token::LeftParen(loc))?;
+                // Os parênteses não viram nó: a expressão mantém o próprio trecho
                 let expr = self.parse_expression(Precedence::Assignment)?;
                 self.consume_token(&Token::RightParen(Location{line:0, column:0}))?;
                 Ok(expr)
             }
             Token::LeftBracket(_) => self.parse_list_literal(),
             Token::LeftBrace(_) => self.parse_dict_literal(),
-            _ => Err(format!(
-                "Esperada expressão, mas encontrado {} na linha {}, coluna {}",
-                self.current_token.friendly_name(), loc.line, loc.column
+            _ => Err(Box::new(
+                Diagnostic::error(format!("Esperada expressão, mas encontrado {}", self.current_token.friendly_name()))
+                    .with_code("P002".to_string())
+                    .with_annotation(Annotation::primary(self.current_span, "esperada uma expressão aqui".to_string())),
             )),
         }
     }
 
-    fn parse_infix(&mut self, left: Expr) -> Result<Expr, String> {
-        let loc = self.current_token.get_location().clone();
+    fn parse_infix(&mut self, left: Expr) -> ParseResult<Expr> {
+        let start = left.span;
         match self.current_token.clone() {
             Token::Plus(_) | Token::Minus(_) | Token::Star(_) | Token::Slash(_) |
             Token::EqualEqual(_) | Token::BangEqual(_) | Token::Less(_) |
@@ -920,13 +1405,13 @@ impl<'a> Parser<'a> {
                         left: Box::new(left),
                         right: Box::new(right),
                     },
-                    loc,
+                    span: self.span_from(start),
                 })
             }
             Token::LeftParen(_) => self.parse_call_expression(left),
             Token::LeftBracket(_) => self.parse_index_access(left),
             Token::Dot(_) => {
-                let dot_loc = self.consume_token(&Token::Dot(Location{line:0, column:0}))?.get_location().clone();
+                self.consume_token(&Token::Dot(Location{line:0, column:0}))?;
                 let (property_name, _) = self.consume_identifier()?;
 
                 if matches!(self.current_token, Token::LeftParen(_)) {
@@ -936,7 +1421,7 @@ impl<'a> Parser<'a> {
                             target: Box::new(left),
                             property: property_name,
                         },
-                        loc: dot_loc,
+                        span: self.span_from(start),
                     };
                     self.parse_call_expression(callee)
                 } else {
@@ -946,16 +1431,21 @@ impl<'a> Parser<'a> {
                             target: Box::new(left),
                             property: property_name,
                         },
-                        loc: dot_loc,
+                        span: self.span_from(start),
                     })
                 }
             }
-            _ => Err(format!("Token inesperado em expressão: {} na linha {}, coluna {}", self.current_token.friendly_name(), loc.line, loc.column)),
+            _ => Err(Box::new(
+                Diagnostic::error(format!("Token inesperado em expressão: {}", self.current_token.friendly_name()))
+                    .with_code("P009".to_string())
+                    .with_annotation(Annotation::primary(self.current_span, "token inesperado".to_string())),
+            )),
         }
     }
     
-    fn parse_call_expression(&mut self, callee: Expr) -> Result<Expr, String> {
-        let loc = self.consume_token(&Token::LeftParen(Location{line:0, column:0}))?.get_location().clone();
+    fn parse_call_expression(&mut self, callee: Expr) -> ParseResult<Expr> {
+        let start = callee.span;
+        self.consume_token(&Token::LeftParen(Location{line:0, column:0}))?;
         let mut args = Vec::new();
         if !matches!(self.current_token, Token::RightParen(_)) {
             loop {
@@ -970,12 +1460,13 @@ impl<'a> Parser<'a> {
         
         Ok(Expr {
             kind: ExprKind::FunctionCall { callee: Box::new(callee), args },
-            loc,
+            span: self.span_from(start),
         })
     }
 
-    fn parse_list_literal(&mut self) -> Result<Expr, String> {
-        let loc = self.consume_token(&Token::LeftBracket(Location{line:0, column:0}))?.get_location().clone();
+    fn parse_list_literal(&mut self) -> ParseResult<Expr> {
+        let start = self.current_span;
+        self.consume_token(&Token::LeftBracket(Location{line:0, column:0}))?;
         let mut elements = Vec::new();
         if !matches!(self.current_token, Token::RightBracket(_)) {
             loop {
@@ -989,12 +1480,13 @@ impl<'a> Parser<'a> {
         self.consume_token(&Token::RightBracket(Location{line:0, column:0}))?;
         Ok(Expr {
             kind: ExprKind::Literal(LiteralValue::List(elements)),
-            loc,
+            span: self.span_from(start),
         })
     }
 
-    fn parse_dict_literal(&mut self) -> Result<Expr, String> {
-        let loc = self.consume_token(&Token::LeftBrace(Location{line:0, column:0}))?.get_location().clone();
+    fn parse_dict_literal(&mut self) -> ParseResult<Expr> {
+        let start = self.current_span;
+        self.consume_token(&Token::LeftBrace(Location{line:0, column:0}))?;
         let mut pairs = Vec::new();
         if !matches!(self.current_token, Token::RightBrace(_)) {
             loop {
@@ -1011,22 +1503,54 @@ impl<'a> Parser<'a> {
         self.consume_token(&Token::RightBrace(Location{line:0, column:0}))?;
         Ok(Expr {
             kind: ExprKind::Literal(LiteralValue::Dict(pairs)),
-            loc,
+            span: self.span_from(start),
         })
     }
 
-    fn parse_index_access(&mut self, target: Expr) -> Result<Expr, String> {
-        let loc = self.consume_token(&Token::LeftBracket(Location{line:0, column:0}))?.get_location().clone();
+    fn parse_index_access(&mut self, target: Expr) -> ParseResult<Expr> {
+        let start = target.span;
+        self.consume_token(&Token::LeftBracket(Location{line:0, column:0}))?;
         let index = self.parse_expression(Precedence::Assignment)?;
         self.consume_token(&Token::RightBracket(Location{line:0, column:0}))?;
         Ok(Expr {
             kind: ExprKind::IndexAccess { target: Box::new(target), index: Box::new(index) },
-            loc,
+            span: self.span_from(start),
         })
     }
 }
 
-pub fn parse_program(source: &str) -> Result<Program, String> {
-    let mut parser = Parser::new(source)?;
-    parser.parse_program()
-}
\ No newline at end of file
+/// Analisa um arquivo completo, retornando todos os erros de sintaxe encontrados
+pub fn parse_program(source: &str) -> Result<Program, Vec<Diagnostic>> {
+    let mut parser = Parser::new(source);
+    let program = parser.parse_program();
+    let diagnostics = parser.take_diagnostics();
+    if diagnostics.is_empty() {
+        Ok(program)
+    } else {
+        Err(diagnostics)
+    }
+}
+
+/// Como `parse_program`, devolvendo também os comentários do arquivo
+pub fn parse_with_comments(source: &str) -> Result<(Program, Vec<Comment>), Vec<Diagnostic>> {
+    let mut parser = Parser::new(source);
+    let program = parser.parse_program();
+    let diagnostics = parser.take_diagnostics();
+    if diagnostics.is_empty() {
+        Ok((program, parser.tokenizer.take_comments()))
+    } else {
+        Err(diagnostics)
+    }
+}
+
+/// Analisa um arquivo de declarações de módulos nativos (`.snaskd`)
+pub fn parse_declarations(source: &str) -> Result<Vec<ModuleDecl>, Vec<Diagnostic>> {
+    let mut parser = Parser::new(source);
+    let modules = parser.parse_declaration_file();
+    let diagnostics = parser.take_diagnostics();
+    if diagnostics.is_empty() {
+        Ok(modules)
+    } else {
+        Err(diagnostics)
+    }
+}
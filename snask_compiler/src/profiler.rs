@@ -0,0 +1,206 @@
+//! `snask run --profile`: chamadas e tempo por função, contagem de execuções
+//! por linha e pilhas no formato "collapsed" dos geradores de flamegraph
+//! (`flamegraph.pl`, `inferno-flamegraph`).
+//!
+//! Funções nativas (`blaze.render_template`, `blaze_db.query`, ...) entram no
+//! perfil como frames próprios, então o tempo de um handler do Blaze aparece
+//! dividido entre templates, banco de dados e código Snask.
+
+use crate::ast::{FuncDecl, Stmt};
+use crate::interpreter::{Interpreter, Tracer};
+use std::collections::HashMap;
+use std::fs;
+use std::time::{Duration, Instant};
+
+/// Quantas linhas aparecem na tabela de linhas mais executadas
+const TOP_LINES: usize = 20;
+
+#[derive(Debug, Clone, Default)]
+pub struct ProfileOptions {
+    /// Conta também quantas vezes cada linha é executada
+    pub lines: bool,
+    /// Arquivo onde gravar as pilhas no formato collapsed
+    pub flamegraph: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct FunctionStats {
+    calls: u64,
+    inclusive: Duration,
+    exclusive: Duration,
+    native: bool,
+}
+
+/// Uma chamada em andamento
+struct OpenFrame {
+    name: String,
+    start: Instant,
+    /// Tempo gasto nas chamadas feitas a partir deste frame
+    children: Duration,
+}
+
+pub struct Profiler {
+    options: ProfileOptions,
+    stack: Vec<OpenFrame>,
+    functions: HashMap<String, FunctionStats>,
+    /// Tempo exclusivo de cada pilha (`<principal>;handler;blaze_db.query`)
+    stacks: HashMap<String, Duration>,
+    lines: HashMap<(String, usize), u64>,
+    started: Instant,
+    finished: Option<Duration>,
+}
+
+impl Profiler {
+    pub fn new(options: ProfileOptions) -> Self {
+        let now = Instant::now();
+        Profiler {
+            options,
+            stack: vec![OpenFrame { name: "<principal>".to_string(), start: now, children: Duration::ZERO }],
+            functions: HashMap::new(),
+            stacks: HashMap::new(),
+            lines: HashMap::new(),
+            started: now,
+            finished: None,
+        }
+    }
+
+    fn enter(&mut self, name: &str, native: bool) {
+        let stats = self.functions.entry(name.to_string()).or_default();
+        stats.calls += 1;
+        stats.native = native;
+        self.stack.push(OpenFrame { name: name.to_string(), start: Instant::now(), children: Duration::ZERO });
+    }
+
+    fn exit(&mut self) {
+        // O frame do programa principal só fecha em `finish`
+        if self.stack.len() > 1 {
+            self.close_frame();
+        }
+    }
+
+    fn close_frame(&mut self) {
+        let Some(frame) = self.stack.pop() else { return };
+        let elapsed = frame.start.elapsed();
+        let exclusive = elapsed.saturating_sub(frame.children);
+        let path = self.stack.iter().map(|open| open.name.as_str()).chain([frame.name.as_str()]).collect::<Vec<_>>().join(";");
+        *self.stacks.entry(path).or_default() += exclusive;
+
+        // Numa recursão, o tempo inclusivo conta só a chamada mais externa
+        let recursive = self.stack.iter().any(|open| open.name == frame.name);
+        let stats = self.functions.entry(frame.name).or_default();
+        stats.exclusive += exclusive;
+        if !recursive {
+            stats.inclusive += elapsed;
+        }
+        if let Some(parent) = self.stack.last_mut() {
+            parent.children += elapsed;
+        }
+    }
+
+    /// Fecha as chamadas ainda abertas (o programa terminou ou foi interrompido)
+    pub fn finish(&mut self) {
+        if self.finished.is_some() {
+            return;
+        }
+        if let Some(main) = self.functions.get_mut("<principal>") {
+            main.calls = 1;
+        } else {
+            self.functions.insert("<principal>".to_string(), FunctionStats { calls: 1, ..Default::default() });
+        }
+        while !self.stack.is_empty() {
+            self.close_frame();
+        }
+        self.finished = Some(self.started.elapsed());
+    }
+
+    /// Tabela de funções, da que tem mais tempo exclusivo para a que tem menos,
+    /// e, se pedido, as linhas mais executadas
+    pub fn report(&self) -> String {
+        let total = self.finished.unwrap_or_else(|| self.started.elapsed());
+        let mut functions: Vec<(&String, &FunctionStats)> = self.functions.iter().collect();
+        functions.sort_by(|a, b| b.1.exclusive.cmp(&a.1.exclusive).then_with(|| a.0.cmp(b.0)));
+
+        let width = functions.iter().map(|(name, stats)| name.len() + if stats.native { 9 } else { 0 }).max().unwrap_or(0).max(6);
+        let mut out = format!("Perfil de execução: {}\n\n", milliseconds(total));
+        out.push_str(&format!(
+            "{:<width$}  {:>9}  {:>13}  {:>13}  {:>7}\n",
+            "função", "chamadas", "inclusivo", "exclusivo", "% excl.",
+            width = width
+        ));
+        for (name, stats) in functions {
+            let label = if stats.native { format!("{} (nativa)", name) } else { name.clone() };
+            let percent = if total.is_zero() { 0.0 } else { stats.exclusive.as_secs_f64() / total.as_secs_f64() * 100.0 };
+            out.push_str(&format!(
+                "{:<width$}  {:>9}  {:>13}  {:>13}  {:>6.1}%\n",
+                label,
+                stats.calls,
+                milliseconds(stats.inclusive),
+                milliseconds(stats.exclusive),
+                percent,
+                width = width
+            ));
+        }
+
+        if self.options.lines && !self.lines.is_empty() {
+            let mut lines: Vec<(&(String, usize), &u64)> = self.lines.iter().collect();
+            lines.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+            let mut sources: HashMap<&str, Vec<String>> = HashMap::new();
+            out.push_str(&format!("\nLinhas mais executadas:\n{:>10}  local\n", "execuções"));
+            for ((file, line), hits) in lines.into_iter().take(TOP_LINES) {
+                let source = sources
+                    .entry(file)
+                    .or_insert_with(|| fs::read_to_string(file).map(|s| s.lines().map(str::to_string).collect()).unwrap_or_default());
+                let text = source.get(line - 1).map(|text| text.trim()).unwrap_or("");
+                out.push_str(&format!("{:>10}  {}:{}  {}\n", hits, file, line, text));
+            }
+        }
+        out
+    }
+
+    /// Uma linha `frame;frame;frame microssegundos` por pilha
+    pub fn collapsed_stacks(&self) -> String {
+        let mut stacks: Vec<(&String, &Duration)> = self.stacks.iter().filter(|(_, time)| !time.is_zero()).collect();
+        stacks.sort();
+        stacks.iter().map(|(stack, time)| format!("{} {}\n", stack.replace(' ', "_"), time.as_micros())).collect()
+    }
+
+    /// Fecha o perfil, escreve a tabela em stderr e grava o flamegraph, se pedido
+    pub fn write_report(&mut self) -> Result<(), String> {
+        self.finish();
+        eprint!("\n{}", self.report());
+        if let Some(path) = &self.options.flamegraph {
+            fs::write(path, self.collapsed_stacks()).map_err(|e| format!("Não foi possível gravar {}: {}", path, e))?;
+            eprintln!("\nPilhas para flamegraph gravadas em {}", path);
+        }
+        Ok(())
+    }
+}
+
+fn milliseconds(duration: Duration) -> String {
+    format!("{:.3} ms", duration.as_secs_f64() * 1000.0)
+}
+
+impl Tracer for Profiler {
+    fn statement(&mut self, interpreter: &mut Interpreter, statement: &Stmt) {
+        if self.options.lines {
+            let key = (interpreter.current_file().to_string(), statement.span.start.line);
+            *self.lines.entry(key).or_default() += 1;
+        }
+    }
+
+    fn enter_function(&mut self, _interpreter: &mut Interpreter, function: &FuncDecl) {
+        self.enter(&function.name, false);
+    }
+
+    fn exit_function(&mut self, _interpreter: &mut Interpreter, _function: &FuncDecl) {
+        self.exit();
+    }
+
+    fn enter_native(&mut self, name: &str) {
+        self.enter(name, true);
+    }
+
+    fn exit_native(&mut self, _name: &str) {
+        self.exit();
+    }
+}
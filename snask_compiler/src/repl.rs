@@ -1,19 +1,70 @@
-use std::io::{self, Write};
+use crate::ast::{Expr, ExprKind, LiteralValue};
+use crate::interpreter::Interpreter;
+use crate::semantic_analyzer::SemanticAnalyzer;
+use crate::stdlib::testing::literal;
+use crate::value::Value;
+use directories::ProjectDirs;
+use rustyline::completion::{Completer, Pair};
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::{FileHistory, History};
+use rustyline::validate::Validator;
+use rustyline::{CompletionType, Config, Context, Editor, Helper};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+const PROMPT: &str = "snask> ";
+/// Prompt das linhas seguintes de uma entrada com chaves ainda abertas
+const CONTINUATION_PROMPT: &str = "  ...> ";
+const MAX_HISTORY: usize = 1000;
+/// Comandos do REPL que começam com `:`
+const META_COMMANDS: &[&str] = &[":type", ":ast", ":vars", ":load", ":reset", ":time"];
+/// Largura a partir da qual listas e dicionários são mostrados um item por linha
+const PRETTY_WIDTH: usize = 80;
+/// `:time` repete a expressão até somar este tempo (ou `TIME_MAX_RUNS` execuções)
+const TIME_BUDGET: Duration = Duration::from_secs(1);
+const TIME_MAX_RUNS: usize = 100_000;
 
 /// REPL interativo para Snask
 pub struct Repl {
-    interpreter: crate::interpreter::Interpreter,
-    history: Vec<String>,
+    interpreter: Interpreter,
+    /// Acompanha as declarações já executadas, para o `:type`
+    analyzer: SemanticAnalyzer,
+    /// Globais da stdlib, que o `:vars` não lista
+    builtins: HashSet<String>,
+    editor: Editor<ReplHelper, FileHistory>,
+    history_file: Option<PathBuf>,
 }
 
 impl Repl {
     pub fn new() -> Self {
-        let mut interpreter = crate::interpreter::Interpreter::new();
-        crate::stdlib::register_stdlib(interpreter.get_globals_mut());
-        
+        let interpreter = Interpreter::new();
+        let builtins = global_names(&interpreter).into_iter().collect();
+
+        let config = Config::builder()
+            .max_history_size(MAX_HISTORY)
+            .expect("tamanho de histórico inválido")
+            .history_ignore_dups(true)
+            .expect("configuração de histórico inválida")
+            .completion_type(CompletionType::List)
+            .build();
+        let mut editor = Editor::with_config(config).expect("não foi possível iniciar o editor de linha");
+        editor.set_helper(Some(ReplHelper::default()));
+
+        let history_file = history_file();
+        if let Some(path) = &history_file {
+            // Na primeira execução o arquivo ainda não existe
+            let _ = editor.load_history(path);
+        }
+
         Repl {
             interpreter,
-            history: Vec::new(),
+            analyzer: SemanticAnalyzer::new(),
+            builtins,
+            editor,
+            history_file,
         }
     }
 
@@ -28,14 +79,20 @@ impl Repl {
         println!();
 
         loop {
-            print!("snask> ");
-            io::stdout().flush().unwrap();
-
-            let mut input = String::new();
-            if io::stdin().read_line(&mut input).is_err() {
-                eprintln!("Erro ao ler entrada");
-                continue;
-            }
+            self.refresh_completions();
+            let input = match self.read_input() {
+                Ok(Some(input)) => input,
+                // Ctrl+C descarta a entrada atual
+                Ok(None) => continue,
+                Err(ReadlineError::Eof) => {
+                    println!("Até logo!");
+                    break;
+                }
+                Err(e) => {
+                    eprintln!("Erro ao ler entrada: {}", e);
+                    break;
+                }
+            };
 
             let input = input.trim();
 
@@ -54,7 +111,7 @@ impl Repl {
                     continue;
                 }
                 "clear" => {
-                    self.history.clear();
+                    let _ = self.editor.clear_history();
                     println!("Histórico limpo");
                     continue;
                 }
@@ -66,11 +123,75 @@ impl Repl {
             }
 
             // Adicionar ao histórico
-            self.history.push(input.to_string());
+            let _ = self.editor.add_history_entry(input);
+
+            if input.starts_with(':') {
+                self.meta_command(input);
+                continue;
+            }
 
             // Tentar executar
             self.execute(input);
         }
+
+        if let Some(path) = &self.history_file {
+            if let Err(e) = self.editor.save_history(path) {
+                eprintln!("Não foi possível salvar o histórico em {}: {}", path.display(), e);
+            }
+        }
+    }
+
+    /// Lê uma entrada completa: continua pedindo linhas enquanto houver
+    /// chaves, colchetes ou parênteses abertos. `None` se o usuário desistiu com Ctrl+C.
+    fn read_input(&mut self) -> Result<Option<String>, ReadlineError> {
+        let mut input = String::new();
+        loop {
+            let prompt = if input.is_empty() { PROMPT } else { CONTINUATION_PROMPT };
+            match self.editor.readline(prompt) {
+                Ok(line) => {
+                    if !input.is_empty() {
+                        input.push('\n');
+                    }
+                    input.push_str(&line);
+                    if open_delimiters(&input) <= 0 {
+                        return Ok(Some(input));
+                    }
+                }
+                Err(ReadlineError::Interrupted) => return Ok(None),
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Atualiza os nomes oferecidos pelo Tab com as variáveis globais atuais
+    fn refresh_completions(&mut self) {
+        let mut globals = Vec::new();
+        let mut members = HashMap::new();
+        if let Some(scope) = self.interpreter.get_globals().scopes().first() {
+            for (name, symbol) in scope {
+                globals.push(name.clone());
+                // Módulos (math, blaze, ...) são dicionários: completa `modulo.membro`
+                if let Value::Dict(entries) = &symbol.value {
+                    let mut keys: Vec<String> = entries
+                        .keys()
+                        .filter_map(|key| match key {
+                            Value::String(key) => Some(key.clone()),
+                            _ => None,
+                        })
+                        .collect();
+                    keys.sort();
+                    members.insert(name.clone(), keys);
+                }
+            }
+        }
+        globals.extend(crate::lsp::KEYWORDS.iter().map(|keyword| keyword.to_string()));
+        globals.extend(["exit", "quit", "help", "clear", "history"].map(String::from));
+        globals.sort();
+        globals.dedup();
+        if let Some(helper) = self.editor.helper_mut() {
+            helper.globals = globals;
+            helper.members = members;
+        }
     }
 
     fn execute(&mut self, input: &str) {
@@ -84,29 +205,183 @@ impl Repl {
         // Parse e execute
         match crate::parser::parse_program(&input_with_semicolon) {
             Ok(program) => {
-                match self.interpreter.interpret(program) {
-                    crate::interpreter::InterpretResult::Ok => {
-                        // Sucesso silencioso
+                match self.interpreter.interpret_value(program.clone()) {
+                    Ok(value) => {
+                        self.remember(&program);
+                        // Mostra o valor de expressões soltas; chamadas como `print(...)` retornam nil
+                        if let Some(value) = value.filter(|value| *value != Value::Nil) {
+                            println!("{}", pretty(&value));
+                        }
                     }
-                    crate::interpreter::InterpretResult::RuntimeError(msg) => {
+                    Err(msg) => {
                         eprintln!("❌ Erro de execução: {}", msg);
                     }
                 }
             }
+            Err(diagnostics) => {
+                for diagnostic in diagnostics {
+                    eprint!("{}", diagnostic.render("<repl>", &input_with_semicolon));
+                }
+            }
+        }
+    }
+
+    /// Registra no analisador as declarações de uma entrada que rodou sem erro
+    fn remember(&mut self, program: &crate::ast::Program) {
+        self.analyzer.analyze(program);
+        // Erros aqui repetiriam os da execução; só as declarações interessam
+        self.analyzer.errors.clear();
+        self.analyzer.warnings.clear();
+        self.analyzer.names.clear();
+    }
+
+    /// Executa um comando `:nome argumento`
+    fn meta_command(&mut self, input: &str) {
+        let (command, argument) = input.split_once(char::is_whitespace).unwrap_or((input, ""));
+        let argument = argument.trim();
+        let needs_argument = matches!(command, ":type" | ":ast" | ":load" | ":time");
+        if needs_argument && argument.is_empty() {
+            let what = if command == ":load" { "<arquivo.snask>" } else { "<expressão>" };
+            eprintln!("Uso: {} {}", command, what);
+            return;
+        }
+        match command {
+            ":type" => self.show_type(argument),
+            ":ast" => match crate::debugger::parse_expression(argument) {
+                Ok(expr) => print!("{}", ast_tree(&expr)),
+                Err(e) => eprintln!("❌ {}", e),
+            },
+            ":vars" => self.show_vars(),
+            ":load" => self.load(argument),
+            ":reset" => {
+                self.interpreter = Interpreter::new();
+                self.analyzer = SemanticAnalyzer::new();
+                println!("Estado reiniciado: variáveis e funções apagadas");
+            }
+            ":time" => self.time(argument),
+            _ => eprintln!("Comando desconhecido: {} (digite 'help' para ver os comandos)", command),
+        }
+    }
+
+    fn show_type(&mut self, text: &str) {
+        let expr = match crate::debugger::parse_expression(text) {
+            Ok(expr) => expr,
+            Err(e) => return eprintln!("❌ {}", e),
+        };
+        let result = self.analyzer.expression_type(&expr);
+        self.analyzer.names.clear();
+        match result {
+            Ok(expr_type) => println!("{}", expr_type),
             Err(e) => {
-                eprintln!("❌ Erro de sintaxe: {}", e);
+                let source = format!("{};", text.trim().trim_end_matches(';'));
+                eprint!("{}", e.to_diagnostic().render("<repl>", &source));
+            }
+        }
+    }
+
+    /// Lista as variáveis e funções definidas na sessão, com o tipo visto pelo analisador
+    fn show_vars(&self) {
+        let mut names: Vec<String> =
+            global_names(&self.interpreter).into_iter().filter(|name| !self.builtins.contains(name)).collect();
+        if names.is_empty() {
+            println!("Nenhuma variável definida");
+            return;
+        }
+        names.sort();
+        let globals = self.interpreter.get_globals();
+        for name in names {
+            let Some(symbol) = globals.get(&name) else { continue };
+            let annotation = match self.analyzer.symbol_table.lookup(&name) {
+                Some(declared) => format!(": {}", declared.symbol_type),
+                None => String::new(),
+            };
+            match &symbol.value {
+                Value::Function(_) | Value::NativeFunction(_) => println!("{}{}", name, annotation),
+                value => println!("{}{} = {}", name, annotation, pretty(value)),
             }
         }
     }
 
+    /// Executa um arquivo na sessão atual, deixando as declarações dele disponíveis
+    fn load(&mut self, path: &str) {
+        let program = match crate::load_program(path) {
+            Ok(program) => program,
+            Err(e) => return eprint!("{}", e),
+        };
+        let previous_file = self.interpreter.current_file().to_string();
+        self.interpreter.set_file(path);
+        let result = self.interpreter.interpret(program.clone());
+        self.interpreter.set_file(&previous_file);
+        match result {
+            crate::interpreter::InterpretResult::Ok => {
+                self.remember(&program);
+                println!("Carregado: {}", path);
+            }
+            crate::interpreter::InterpretResult::RuntimeError(e) => eprint!("{}", crate::render_runtime_error(&e)),
+        }
+    }
+
+    /// Avalia a expressão várias vezes e mostra quanto cada execução levou
+    fn time(&mut self, text: &str) {
+        let expr = match crate::debugger::parse_expression(text) {
+            Ok(expr) => expr,
+            Err(e) => return eprintln!("❌ {}", e),
+        };
+        let result = self.interpreter.with_call_stack(|interpreter| {
+            let mut samples = Vec::new();
+            let mut value = Value::Nil;
+            let start = Instant::now();
+            while samples.is_empty() || (start.elapsed() < TIME_BUDGET && samples.len() < TIME_MAX_RUNS) {
+                let run = Instant::now();
+                value = interpreter.evaluate(expr.clone())?;
+                samples.push(run.elapsed());
+            }
+            Ok::<_, crate::runtime_error::RuntimeError>((value, samples))
+        });
+        let result = result.and_then(|result| result);
+        let (value, samples) = match result {
+            Ok(result) => result,
+            Err(e) => return eprintln!("❌ Erro de execução: {}", e),
+        };
+        if value != Value::Nil {
+            println!("{}", pretty(&value));
+        }
+        let total: Duration = samples.iter().sum();
+        let runs = samples.len();
+        let min = samples.iter().min().copied().unwrap_or_default();
+        let max = samples.iter().max().copied().unwrap_or_default();
+        println!(
+            "{} {} em {}: média {}, mínimo {}, máximo {}",
+            runs,
+            if runs == 1 { "execução" } else { "execuções" },
+            duration(total),
+            duration(total / runs as u32),
+            duration(min),
+            duration(max)
+        );
+    }
+
     fn show_help(&self) {
         println!("╔═══════════════════════════════════════════════════════════╗");
         println!("║                    Comandos do REPL                       ║");
         println!("╠═══════════════════════════════════════════════════════════╣");
-        println!("║  exit, quit    - Sair do REPL                             ║");
+        println!("║  exit, quit    - Sair do REPL (ou Ctrl+D)                 ║");
         println!("║  help          - Mostrar esta ajuda                       ║");
         println!("║  clear         - Limpar histórico                         ║");
         println!("║  history       - Mostrar histórico de comandos            ║");
+        println!("║  :type expr    - Mostrar o tipo de uma expressão          ║");
+        println!("║  :ast expr     - Mostrar a árvore sintática da expressão  ║");
+        println!("║  :vars         - Listar as variáveis e funções definidas  ║");
+        println!("║  :load arquivo - Executar um arquivo .snask na sessão     ║");
+        println!("║  :reset        - Apagar todas as variáveis e funções      ║");
+        println!("║  :time expr    - Medir o tempo de execução da expressão   ║");
+        println!("╠═══════════════════════════════════════════════════════════╣");
+        println!("║                        Edição                             ║");
+        println!("╠═══════════════════════════════════════════════════════════╣");
+        println!("║  ↑ / ↓         - Navegar pelo histórico (Ctrl+R busca)    ║");
+        println!("║  Tab           - Completar variáveis e membros de módulos ║");
+        println!("║  Ctrl+C        - Descartar a entrada atual                ║");
+        println!("║  {{ aberta      - Continua na linha seguinte (  ...>)      ║");
         println!("╠═══════════════════════════════════════════════════════════╣");
         println!("║                   Exemplos de Código                      ║");
         println!("╠═══════════════════════════════════════════════════════════╣");
@@ -119,7 +394,8 @@ impl Repl {
     }
 
     fn show_history(&self) {
-        if self.history.is_empty() {
+        let history = self.editor.history();
+        if history.is_empty() {
             println!("Histórico vazio");
             return;
         }
@@ -127,9 +403,222 @@ impl Repl {
         println!("╔═══════════════════════════════════════════════════════════╗");
         println!("║                    Histórico de Comandos                  ║");
         println!("╚═══════════════════════════════════════════════════════════╝");
-        
-        for (i, cmd) in self.history.iter().enumerate() {
-            println!("{:3}. {}", i + 1, cmd);
+
+        for (i, cmd) in history.iter().enumerate() {
+            // Entradas de várias linhas ficam alinhadas sob o número
+            println!("{:3}. {}", i + 1, cmd.replace('\n', "\n     "));
+        }
+    }
+}
+
+/// Nomes do escopo global do interpretador
+/// Tempo com a unidade que combina com a magnitude (ns, µs, ms ou s)
+fn duration(duration: Duration) -> String {
+    let nanos = duration.as_nanos();
+    if nanos < 1_000 {
+        format!("{} ns", nanos)
+    } else if nanos < 1_000_000 {
+        format!("{:.2} µs", nanos as f64 / 1e3)
+    } else if nanos < 1_000_000_000 {
+        format!("{:.2} ms", nanos as f64 / 1e6)
+    } else {
+        format!("{:.3} s", duration.as_secs_f64())
+    }
+}
+
+fn global_names(interpreter: &Interpreter) -> Vec<String> {
+    interpreter.get_globals().scopes().first().map(|scope| scope.keys().cloned().collect()).unwrap_or_default()
+}
+
+/// Valor como seria escrito no código; listas e dicionários longos ficam com
+/// um item por linha
+fn pretty(value: &Value) -> String {
+    let mut out = String::new();
+    write_pretty(value, 0, &mut out);
+    out
+}
+
+fn write_pretty(value: &Value, indent: usize, out: &mut String) {
+    let flat = literal(value);
+    if indent + flat.chars().count() <= PRETTY_WIDTH {
+        out.push_str(&flat);
+        return;
+    }
+    let pad = " ".repeat(indent + 2);
+    match value {
+        // Valores simples vão lado a lado, quantos couberem na linha
+        Value::List(items) if !items.iter().any(|item| matches!(item, Value::List(_) | Value::Dict(_))) => {
+            out.push_str("[\n");
+            let mut line = String::new();
+            for item in items {
+                let item = format!("{},", literal(item));
+                if !line.is_empty() && pad.len() + line.chars().count() + 1 + item.chars().count() > PRETTY_WIDTH {
+                    out.push_str(&format!("{}{}\n", pad, line));
+                    line.clear();
+                }
+                if !line.is_empty() {
+                    line.push(' ');
+                }
+                line.push_str(&item);
+            }
+            out.push_str(&format!("{}{}\n{}]", pad, line, " ".repeat(indent)));
         }
+        Value::List(items) => {
+            out.push_str("[\n");
+            for item in items {
+                out.push_str(&pad);
+                write_pretty(item, indent + 2, out);
+                out.push_str(",\n");
+            }
+            out.push_str(&format!("{}]", " ".repeat(indent)));
+        }
+        Value::Dict(entries) => {
+            let mut entries: Vec<(String, &Value)> = entries.iter().map(|(key, value)| (literal(key), value)).collect();
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            out.push_str("{\n");
+            for (key, value) in entries {
+                out.push_str(&format!("{}{}: ", pad, key));
+                write_pretty(value, indent + 2, out);
+                out.push_str(",\n");
+            }
+            out.push_str(&format!("{}}}", " ".repeat(indent)));
+        }
+        _ => out.push_str(&flat),
     }
 }
+
+/// Árvore de uma expressão, um nó por linha, para o `:ast`
+fn ast_tree(expr: &Expr) -> String {
+    let mut out = String::new();
+    write_node(expr, "", "", &mut out);
+    out
+}
+
+fn write_node(expr: &Expr, label: &str, indent: &str, out: &mut String) {
+    let start = expr.span.start;
+    let (node, children): (String, Vec<(&str, &Expr)>) = match &expr.kind {
+        ExprKind::Literal(LiteralValue::Number(n)) => (format!("Número {}", n), vec![]),
+        ExprKind::Literal(LiteralValue::String(s)) => (format!("String {:?}", s), vec![]),
+        ExprKind::Literal(LiteralValue::Boolean(b)) => (format!("Booleano {}", b), vec![]),
+        ExprKind::Literal(LiteralValue::Nil) => ("Nil".to_string(), vec![]),
+        ExprKind::Literal(LiteralValue::List(items)) => ("Lista".to_string(), items.iter().map(|item| ("", item)).collect()),
+        ExprKind::Literal(LiteralValue::Dict(entries)) => (
+            "Dicionário".to_string(),
+            entries.iter().flat_map(|(key, value)| [("chave: ", key), ("valor: ", value)]).collect(),
+        ),
+        ExprKind::Variable(name) => (format!("Variável {}", name), vec![]),
+        ExprKind::Unary { op, expr } => (format!("Unária {:?}", op), vec![("", expr.as_ref())]),
+        ExprKind::Binary { op, left, right } => (format!("Binária {:?}", op), vec![("", left.as_ref()), ("", right.as_ref())]),
+        ExprKind::FunctionCall { callee, args } => (
+            "Chamada".to_string(),
+            std::iter::once(("função: ", callee.as_ref())).chain(args.iter().map(|arg| ("argumento: ", arg))).collect(),
+        ),
+        ExprKind::PropertyAccess { target, property } => (format!("Propriedade .{}", property), vec![("", target.as_ref())]),
+        ExprKind::IndexAccess { target, index } => {
+            ("Índice".to_string(), vec![("alvo: ", target.as_ref()), ("índice: ", index.as_ref())])
+        }
+    };
+    out.push_str(&format!("{}{}  ({}:{})\n", label, node, start.line, start.column));
+    for (i, (child_label, child)) in children.iter().enumerate() {
+        let last = i + 1 == children.len();
+        let branch = if last { "└─ " } else { "├─ " };
+        let continuation = if last { "   " } else { "│  " };
+        out.push_str(indent);
+        write_node(child, &format!("{}{}", branch, child_label), &format!("{}{}", indent, continuation), out);
+    }
+}
+
+/// Arquivo do histórico, no diretório de dados do usuário (ao lado de `packages`)
+fn history_file() -> Option<PathBuf> {
+    let dir = ProjectDirs::from("com", "Snask", "Snask")?.data_dir().to_path_buf();
+    std::fs::create_dir_all(&dir).ok()?;
+    Some(dir.join("repl_history"))
+}
+
+/// Quantos `{`, `[` e `(` continuam abertos, ignorando strings e comentários
+fn open_delimiters(source: &str) -> i32 {
+    let mut depth = 0;
+    let mut chars = source.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                while let Some(c) = chars.next() {
+                    match c {
+                        '\\' => {
+                            chars.next();
+                        }
+                        '"' => break,
+                        _ => {}
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            '{' | '[' | '(' => depth += 1,
+            '}' | ']' | ')' => depth -= 1,
+            _ => {}
+        }
+    }
+    depth
+}
+
+/// Autocompletar do REPL: nomes globais, palavras-chave e membros de módulos
+#[derive(Default)]
+struct ReplHelper {
+    globals: Vec<String>,
+    /// Membros de cada global que é um dicionário (os módulos da stdlib)
+    members: HashMap<String, Vec<String>>,
+}
+
+impl Completer for ReplHelper {
+    type Candidate = Pair;
+
+    fn complete(&self, line: &str, pos: usize, _ctx: &Context<'_>) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let before = &line[..pos];
+        let start = before
+            .char_indices()
+            .rev()
+            .find(|(_, c)| !(c.is_alphanumeric() || *c == '_' || *c == '.'))
+            .map_or(0, |(i, c)| i + c.len_utf8());
+        let word = &before[start..];
+
+        // `:ty` no início da linha completa os comandos do REPL
+        if let Some(command) = before.strip_prefix(':').filter(|command| !command.contains(char::is_whitespace)) {
+            let candidates = META_COMMANDS
+                .iter()
+                .filter(|meta| meta[1..].starts_with(command))
+                .map(|meta| Pair { display: meta.to_string(), replacement: meta.to_string() })
+                .collect();
+            return Ok((0, candidates));
+        }
+
+        let (offset, prefix, names) = match word.rsplit_once('.') {
+            Some((object, member)) => match self.members.get(object) {
+                Some(names) => (start + object.len() + 1, member, names),
+                None => return Ok((pos, Vec::new())),
+            },
+            None => (start, word, &self.globals),
+        };
+        let candidates = names
+            .iter()
+            .filter(|name| name.starts_with(prefix))
+            .map(|name| Pair { display: name.clone(), replacement: name.clone() })
+            .collect();
+        Ok((offset, candidates))
+    }
+}
+
+impl Hinter for ReplHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ReplHelper {}
+
+impl Validator for ReplHelper {}
+
+impl Helper for ReplHelper {}
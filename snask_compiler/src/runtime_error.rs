@@ -0,0 +1,126 @@
+use crate::diagnostics::{Annotation, Diagnostic};
+use crate::span::Span;
+use std::fmt;
+
+/// Maior sequência de frames considerada ao agrupar recursão (ex: `a -> b -> a -> b`)
+const MAX_REPEAT_PERIOD: usize = 8;
+/// Quantas repetições seguidas são necessárias para agrupar os frames
+const MIN_REPEATS: usize = 3;
+
+/// Uma chamada ativa no momento do erro
+#[derive(Debug, Clone, PartialEq)]
+pub struct StackFrame {
+    pub function: String,
+    pub file: String,
+    /// Trecho em execução no frame (a chamada, nos frames externos)
+    pub span: Option<Span>,
+}
+
+impl fmt::Display for StackFrame {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.span {
+            Some(span) => write!(f, "em {} ({}:{}:{})", self.function, self.file, span.start.line, span.start.column),
+            None => write!(f, "em {} ({})", self.function, self.file),
+        }
+    }
+}
+
+/// Erro de execução com a pilha de chamadas. `frames[0]` é o frame mais interno.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuntimeError {
+    pub message: String,
+    pub frames: Vec<StackFrame>,
+    /// Posição do erro dentro do frame que ainda não foi fechado
+    location: Option<Span>,
+}
+
+impl RuntimeError {
+    pub fn new(message: String) -> Self {
+        RuntimeError { message, frames: Vec::new(), location: None }
+    }
+
+    /// Registra onde o erro aconteceu, se ainda não houver uma posição mais precisa
+    pub fn at(mut self, span: Span) -> Self {
+        if self.location.is_none() {
+            self.location = Some(span);
+        }
+        self
+    }
+
+    /// Fecha o frame atual ao sair de uma função (ou do programa/módulo)
+    pub fn with_frame(mut self, function: &str, file: &str) -> Self {
+        self.frames.push(StackFrame {
+            function: function.to_string(),
+            file: file.to_string(),
+            span: self.location.take(),
+        });
+        self
+    }
+
+    /// Frame onde o erro foi gerado
+    pub fn innermost(&self) -> Option<&StackFrame> {
+        self.frames.first()
+    }
+
+    /// Converte o erro para um diagnóstico, apontando para o frame mais interno.
+    /// Os demais frames viram notas, do mais interno para o mais externo.
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        let mut diagnostic = Diagnostic::error(self.message.clone());
+        if let Some(frame) = self.innermost() {
+            if let Some(span) = frame.span {
+                diagnostic = diagnostic.with_annotation(Annotation::primary(span, format!("erro em {}", frame.function)));
+            }
+        }
+        for line in self.trace_lines() {
+            diagnostic = diagnostic.with_note(line);
+        }
+        diagnostic
+    }
+
+    /// Linhas da pilha de chamadas, agrupando frames que se repetem (recursão)
+    pub fn trace_lines(&self) -> Vec<String> {
+        let frames = &self.frames;
+        let mut lines = Vec::new();
+        let mut i = 0;
+        'frames: while i < frames.len() {
+            for period in 1..=MAX_REPEAT_PERIOD {
+                let block = &frames[i..(i + period).min(frames.len())];
+                let mut repeats = 1;
+                while i + (repeats + 1) * period <= frames.len()
+                    && frames[i + repeats * period..i + (repeats + 1) * period] == *block
+                {
+                    repeats += 1;
+                }
+                if repeats >= MIN_REPEATS {
+                    lines.extend(block.iter().map(|frame| frame.to_string()));
+                    lines.push(if period == 1 {
+                        format!("... o frame acima se repete mais {} vezes", repeats - 1)
+                    } else {
+                        format!("... os {} frames acima se repetem mais {} vezes", period, repeats - 1)
+                    });
+                    i += repeats * period;
+                    continue 'frames;
+                }
+            }
+            lines.push(frames[i].to_string());
+            i += 1;
+        }
+        lines
+    }
+}
+
+impl From<String> for RuntimeError {
+    fn from(message: String) -> Self {
+        RuntimeError::new(message)
+    }
+}
+
+impl fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)?;
+        for line in self.trace_lines() {
+            write!(f, "\n  {}", line)?;
+        }
+        Ok(())
+    }
+}
@@ -1,6 +1,9 @@
 ﻿use crate::ast::{Program, Stmt, StmtKind, Expr, ExprKind, VarDecl, BinaryOp, UnaryOp, LiteralValue, ConditionalStmt, LoopStmt, ListDecl, DictDecl, ListPush, DictSet};
+use crate::declarations::{stdlib_module, FunctionDecl, MemberDecl, ModuleDecl};
+use crate::diagnostics::{Annotation, Diagnostic, Severity};
+use crate::span::Span;
 use crate::types::Type;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum SemanticSymbolKind {
@@ -17,6 +20,23 @@ pub struct SemanticSymbol {
     pub symbol_type: Type,
     pub kind: SemanticSymbolKind,
     pub is_variadic: bool, // Novo campo
+    /// Onde o símbolo foi declarado (`None` para a biblioteca padrão)
+    pub declared_at: Option<Span>,
+    /// Tipo declarado de uma variável opcional estreitada por `x != nil`;
+    /// nesse caso `symbol_type` é o tipo sem `nil`
+    pub narrowed_from: Option<Type>,
+}
+
+/// Um nome no código e o símbolo a que ele se refere, para o servidor de linguagem
+#[derive(Debug, Clone)]
+pub struct NameInfo {
+    pub name: String,
+    /// Trecho do uso; nas declarações, o trecho do statement que declara
+    pub span: Span,
+    pub symbol_type: Type,
+    pub kind: SemanticSymbolKind,
+    /// Onde o símbolo foi declarado (`None` para a biblioteca padrão)
+    pub declared_at: Option<Span>,
 }
 
 #[derive(Debug, Clone)]
@@ -50,6 +70,10 @@ impl SemanticSymbolTable {
         true
     }
 
+    pub fn lookup_mut(&mut self, name: &str) -> Option<&mut SemanticSymbol> {
+        self.scopes.iter_mut().rev().find_map(|scope| scope.get_mut(name))
+    }
+
     pub fn lookup(&self, name: &str) -> Option<&SemanticSymbol> {
         for scope in self.scopes.iter().rev() {
             if let Some(symbol) = scope.get(name) {
@@ -58,30 +82,384 @@ impl SemanticSymbolTable {
         }
         None
     }
+
+    /// Substitui (ou sombreia) um símbolo no escopo atual
+    pub fn redefine(&mut self, symbol: SemanticSymbol) {
+        self.scopes.last_mut().unwrap().insert(symbol.name.clone(), symbol);
+    }
+
+    /// Nomes visíveis no escopo atual
+    pub fn visible_names(&self) -> impl Iterator<Item = &str> {
+        self.scopes.iter().flat_map(|scope| scope.keys().map(|name| name.as_str()))
+    }
 }
 
 
+/// Erro semântico. Todo erro aponta para o trecho de código que o causou;
+/// `previous`/`declared` apontam para declarações relacionadas, quando conhecidas.
 #[derive(Debug)]
 pub enum SemanticError {
-    VariableAlreadyDeclared(String),
-    VariableNotFound(String),
-    FunctionAlreadyDeclared(String),
-    FunctionNotFound(String),
-    TypeMismatch { expected: Type, found: Type },
-    InvalidOperation { op: String, type1: Type, type2: Option<Type> },
-    ImmutableAssignment(String),
-    ReturnOutsideFunction,
-    WrongNumberOfArguments { expected: usize, found: usize },
-    IndexAccessOnNonIndexable(Type),
-    InvalidIndexType(Type),
-    PropertyNotFound(String),
-    NotCallable(Type),
+    VariableAlreadyDeclared { name: String, span: Span, previous: Option<Span> },
+    VariableNotFound { name: String, span: Span, suggestion: Option<String> },
+    FunctionAlreadyDeclared { name: String, span: Span, previous: Option<Span> },
+    FunctionNotFound { name: String, span: Span },
+    TypeMismatch { expected: Type, found: Type, span: Span, expected_from: Option<Span> },
+    InvalidOperation { op: String, type1: Type, type2: Option<Type>, span: Span },
+    ImmutableAssignment { name: String, span: Span, declared: Option<Span>, constant: bool },
+    ReturnOutsideFunction { span: Span },
+    /// `max` é `None` quando a função aceita argumentos variádicos; `signature`
+    /// descreve as funções da biblioteca padrão, que não têm declaração no código
+    WrongNumberOfArguments { min: usize, max: Option<usize>, found: usize, span: Span, declared: Option<Span>, signature: Option<String> },
+    IndexAccessOnNonIndexable { found: Type, span: Span },
+    InvalidIndexType { target: Type, found: Type, span: Span },
+    PropertyNotFound { property: String, target: Type, span: Span },
+    NotCallable { found: Type, span: Span },
+    PossiblyNil { found: Type, span: Span },
+    UnknownType { name: String, span: Span },
+    TypeAlreadyDeclared { name: String, span: Span, previous: Option<Span> },
+    MissingField { field: String, target: Type, span: Span },
+    MemberNotFound { module: String, member: String, span: Span, suggestion: Option<String> },
+    MissingReturn { name: String, return_type: Type, span: Span, declared: Option<Span> },
+    TestOutsideTopLevel { span: Span },
+}
+
+impl SemanticError {
+    /// Código estável do erro, usado em `erro[E0xxx]`
+    pub fn code(&self) -> &'static str {
+        match self {
+            SemanticError::VariableAlreadyDeclared { .. } => "E0001",
+            SemanticError::VariableNotFound { .. } => "E0002",
+            SemanticError::FunctionAlreadyDeclared { .. } => "E0003",
+            SemanticError::FunctionNotFound { .. } => "E0004",
+            SemanticError::TypeMismatch { .. } => "E0005",
+            SemanticError::InvalidOperation { .. } => "E0006",
+            SemanticError::ImmutableAssignment { .. } => "E0007",
+            SemanticError::ReturnOutsideFunction { .. } => "E0008",
+            SemanticError::WrongNumberOfArguments { .. } => "E0009",
+            SemanticError::IndexAccessOnNonIndexable { .. } => "E0010",
+            SemanticError::InvalidIndexType { .. } => "E0011",
+            SemanticError::PropertyNotFound { .. } => "E0012",
+            SemanticError::NotCallable { .. } => "E0013",
+            SemanticError::PossiblyNil { .. } => "E0014",
+            SemanticError::UnknownType { .. } => "E0015",
+            SemanticError::TypeAlreadyDeclared { .. } => "E0016",
+            SemanticError::MissingField { .. } => "E0017",
+            SemanticError::MemberNotFound { .. } => "E0018",
+            SemanticError::MissingReturn { .. } => "E0019",
+            SemanticError::TestOutsideTopLevel { .. } => "E0020",
+        }
+    }
+
+    pub fn span(&self) -> Span {
+        match self {
+            SemanticError::VariableAlreadyDeclared { span, .. }
+            | SemanticError::VariableNotFound { span, .. }
+            | SemanticError::FunctionAlreadyDeclared { span, .. }
+            | SemanticError::FunctionNotFound { span, .. }
+            | SemanticError::TypeMismatch { span, .. }
+            | SemanticError::InvalidOperation { span, .. }
+            | SemanticError::ImmutableAssignment { span, .. }
+            | SemanticError::ReturnOutsideFunction { span }
+            | SemanticError::WrongNumberOfArguments { span, .. }
+            | SemanticError::IndexAccessOnNonIndexable { span, .. }
+            | SemanticError::InvalidIndexType { span, .. }
+            | SemanticError::PropertyNotFound { span, .. }
+            | SemanticError::NotCallable { span, .. }
+            | SemanticError::PossiblyNil { span, .. }
+            | SemanticError::UnknownType { span, .. }
+            | SemanticError::TypeAlreadyDeclared { span, .. }
+            | SemanticError::MissingField { span, .. }
+            | SemanticError::MemberNotFound { span, .. }
+            | SemanticError::MissingReturn { span, .. }
+            | SemanticError::TestOutsideTopLevel { span } => *span,
+        }
+    }
+
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        let span = self.span();
+        let diagnostic = match self {
+            SemanticError::VariableAlreadyDeclared { name, previous, .. } => {
+                let diagnostic = Diagnostic::error(format!("a variável '{}' já foi declarada neste escopo", name))
+                    .with_annotation(Annotation::primary(span, "declarada novamente aqui".to_string()))
+                    .with_help(format!("use outro nome, ou declare '{}' com `mut` e reatribua com `{} = ...`", name, name));
+                with_previous(diagnostic, *previous, "primeira declaração aqui")
+            }
+            SemanticError::VariableNotFound { name, suggestion, .. } => {
+                let diagnostic = Diagnostic::error(format!("a variável '{}' não foi encontrada", name))
+                    .with_annotation(Annotation::primary(span, "não declarada neste escopo".to_string()));
+                match suggestion {
+                    Some(similar) => diagnostic.with_help(format!("você quis dizer '{}'?", similar)),
+                    None => diagnostic.with_help(format!("declare '{}' com `let` antes de usá-la", name)),
+                }
+            }
+            SemanticError::FunctionAlreadyDeclared { name, previous, .. } => {
+                let diagnostic = Diagnostic::error(format!("a função '{}' já foi declarada", name))
+                    .with_annotation(Annotation::primary(span, "declarada novamente aqui".to_string()))
+                    .with_help("funções não podem ser sobrecarregadas; renomeie uma das declarações".to_string());
+                with_previous(diagnostic, *previous, "primeira declaração aqui")
+            }
+            SemanticError::FunctionNotFound { name, .. } => {
+                Diagnostic::error(format!("a função '{}' não foi encontrada", name))
+                    .with_annotation(Annotation::primary(span, "não declarada".to_string()))
+            }
+            SemanticError::TypeMismatch { expected, found, expected_from, .. } => {
+                let diagnostic = Diagnostic::error("tipos incompatíveis".to_string())
+                    .with_annotation(Annotation::primary(span, format!("esperado '{}', encontrado '{}'", expected, found)));
+                let diagnostic = with_previous(diagnostic, *expected_from, &format!("tipo '{}' definido aqui", expected));
+                if *expected == Type::Int && *found == Type::Float {
+                    diagnostic.with_help("use `round`, `floor` ou `ceil` para converter para inteiro".to_string())
+                } else if *found == Type::Nil {
+                    diagnostic.with_help(format!("declare o tipo como '{}' para aceitar `nil`", Type::optional(expected.clone())))
+                } else {
+                    diagnostic
+                }
+            }
+            SemanticError::InvalidOperation { op, type1, type2, .. } => {
+                let (message, label) = match type2 {
+                    Some(type2) => (
+                        format!("operação '{}' inválida entre '{}' e '{}'", op, type1, type2),
+                        format!("não é possível aplicar '{}' a '{}' e '{}'", op, type1, type2),
+                    ),
+                    None => (
+                        format!("operação '{}' inválida para '{}'", op, type1),
+                        format!("não é possível aplicar '{}' a '{}'", op, type1),
+                    ),
+                };
+                let diagnostic = Diagnostic::error(message).with_annotation(Annotation::primary(span, label));
+                if op == "+" && (*type1 == Type::String || type2.as_ref() == Some(&Type::String)) {
+                    diagnostic.with_help("para juntar textos e números use `format`".to_string())
+                } else {
+                    diagnostic
+                }
+            }
+            SemanticError::ImmutableAssignment { name, declared, constant, .. } => {
+                let diagnostic = Diagnostic::error(format!("não é possível reatribuir '{}'", name))
+                    .with_annotation(Annotation::primary(span, "reatribuição aqui".to_string()));
+                let diagnostic = with_previous(diagnostic, *declared, "declarada como imutável aqui");
+                if *constant {
+                    diagnostic.with_help("constantes nunca podem ser reatribuídas".to_string())
+                } else {
+                    diagnostic.with_help(format!("declare '{}' com `mut` para permitir reatribuição", name))
+                }
+            }
+            SemanticError::ReturnOutsideFunction { .. } => {
+                Diagnostic::error("`return` fora de uma função".to_string())
+                    .with_annotation(Annotation::primary(span, "não há função para retornar".to_string()))
+                    .with_help("`return` só pode ser usado dentro do corpo de uma `fun`".to_string())
+            }
+            SemanticError::WrongNumberOfArguments { min, max, found, declared, signature, .. } => {
+                let expected = match max {
+                    Some(max) if max == min => min.to_string(),
+                    Some(max) => format!("de {} a {}", min, max),
+                    None => format!("pelo menos {}", min),
+                };
+                let diagnostic = Diagnostic::error(format!(
+                    "número incorreto de argumentos: esperado {}, encontrado {}",
+                    expected, found
+                ))
+                .with_annotation(Annotation::primary(span, format!("{} argumento(s) passado(s)", found)));
+                let diagnostic = with_previous(diagnostic, *declared, &format!("função declarada aqui com {} parâmetro(s)", min));
+                match signature {
+                    Some(signature) => diagnostic.with_note(format!("assinatura: {}", signature)),
+                    None => diagnostic,
+                }
+            }
+            SemanticError::IndexAccessOnNonIndexable { found, .. } => {
+                Diagnostic::error(format!("o tipo '{}' não pode ser indexado", found))
+                    .with_annotation(Annotation::primary(span, format!("valor do tipo '{}'", found)))
+                    .with_help("apenas `list`, `dict` e `str` suportam acesso por índice ou propriedade".to_string())
+            }
+            SemanticError::InvalidIndexType { target, found, .. } => {
+                let help = if matches!(target, Type::Dict(..) | Type::Record(_)) {
+                    "chaves de `dict` devem ser `str`, números ou `bool`"
+                } else {
+                    "`list` e `str` são indexadas por inteiros"
+                };
+                Diagnostic::error(format!("índice do tipo '{}' inválido para '{}'", found, target))
+                    .with_annotation(Annotation::primary(span, format!("índice do tipo '{}'", found)))
+                    .with_help(help.to_string())
+            }
+            SemanticError::PropertyNotFound { property, target, .. } => {
+                let help = match target {
+                    Type::List(_) => "listas não têm propriedades; use `collections.push(lista, valor)` para adicionar itens".to_string(),
+                    Type::Dict(..) => "use `d[\"chave\"]` para ler valores e `collections.set(d, chave, valor)` para alterá-los".to_string(),
+                    Type::Record(fields) => {
+                        let names: Vec<&str> = fields.iter().map(|(name, _)| name.as_str()).collect();
+                        format!("os campos disponíveis são: {}", names.join(", "))
+                    }
+                    _ => "verifique o nome da propriedade".to_string(),
+                };
+                Diagnostic::error(format!("'{}' não possui a propriedade '{}'", target, property))
+                    .with_annotation(Annotation::primary(span, "propriedade desconhecida".to_string()))
+                    .with_help(help)
+            }
+            SemanticError::NotCallable { found, .. } => {
+                Diagnostic::error(format!("um valor do tipo '{}' não pode ser chamado", found))
+                    .with_annotation(Annotation::primary(span, "não é uma função".to_string()))
+            }
+            SemanticError::PossiblyNil { found, .. } => {
+                let label = match found {
+                    Type::Nil => "este valor é `nil`".to_string(),
+                    _ => format!("do tipo '{}', pode ser `nil`", found),
+                };
+                Diagnostic::error("valor possivelmente `nil` usado onde `nil` não é permitido".to_string())
+                    .with_annotation(Annotation::primary(span, label))
+                    .with_help("verifique antes com `if valor != nil { ... }`".to_string())
+            }
+            SemanticError::UnknownType { name, .. } => {
+                Diagnostic::error(format!("o tipo '{}' não existe", name))
+                    .with_annotation(Annotation::primary(span, "tipo desconhecido".to_string()))
+                    .with_help("os tipos disponíveis são int, float, str, bool, list<T>, dict<K, V>, any e void, além dos declarados com `type Nome = ...`".to_string())
+            }
+            SemanticError::TypeAlreadyDeclared { name, previous, .. } => {
+                let diagnostic = Diagnostic::error(format!("o tipo '{}' já foi declarado", name))
+                    .with_annotation(Annotation::primary(span, "declarado novamente aqui".to_string()));
+                with_previous(diagnostic, *previous, "primeira declaração aqui")
+            }
+            SemanticError::MissingField { field, target, .. } => {
+                Diagnostic::error(format!("falta o campo '{}'", field))
+                    .with_annotation(Annotation::primary(span, format!("esperado '{}'", target)))
+                    .with_help(format!("adicione \"{}\": ... (um campo opcional também precisa estar no literal, com `nil` se não houver valor)", field))
+            }
+            SemanticError::MemberNotFound { module, member, suggestion, .. } => {
+                let diagnostic = Diagnostic::error(format!("o módulo '{}' não possui o membro '{}'", module, member))
+                    .with_annotation(Annotation::primary(span, "membro desconhecido".to_string()));
+                match suggestion {
+                    Some(similar) => diagnostic.with_help(format!("você quis dizer '{}.{}'?", module, similar)),
+                    None => diagnostic,
+                }
+            }
+            SemanticError::MissingReturn { name, return_type, declared, .. } => {
+                let diagnostic = Diagnostic::error(format!("a função '{}' nem sempre retorna um valor", name))
+                    .with_annotation(Annotation::primary(span, format!("nem todos os caminhos retornam '{}'", return_type)));
+                with_previous(diagnostic, *declared, "tipo de retorno declarado aqui")
+                    .with_help("adicione um `return` ao final da função, ou um `else` que também retorne".to_string())
+            }
+            SemanticError::TestOutsideTopLevel { .. } => {
+                Diagnostic::error("`test` fora do nível superior do arquivo".to_string())
+                    .with_annotation(Annotation::primary(span, "teste declarado dentro de um bloco".to_string()))
+                    .with_help("declare os blocos `test` no nível superior, fora de funções e blocos".to_string())
+            }
+        };
+        diagnostic.with_code(self.code().to_string())
+    }
+}
+
+/// Aviso semântico: o programa roda, mas provavelmente não faz o que se espera
+#[derive(Debug)]
+pub enum SemanticWarning {
+    /// Código depois de um `return` (ou de um `while true`) no mesmo bloco
+    UnreachableCode { span: Span, cause: Span },
+}
+
+impl SemanticWarning {
+    pub fn code(&self) -> &'static str {
+        match self {
+            SemanticWarning::UnreachableCode { .. } => "W0001",
+        }
+    }
+
+    pub fn span(&self) -> Span {
+        match self {
+            SemanticWarning::UnreachableCode { span, .. } => *span,
+        }
+    }
+
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        let diagnostic = match self {
+            SemanticWarning::UnreachableCode { span, cause } => Diagnostic::warning("código inalcançável".to_string())
+                .with_annotation(Annotation::new(*span, Some("este código nunca é executado".to_string()), Severity::Warning))
+                .with_annotation(Annotation::secondary(*cause, "a execução não passa deste ponto".to_string()))
+                .with_help("remova o código ou mova-o para antes do `return`".to_string()),
+        };
+        diagnostic.with_code(self.code().to_string())
+    }
+}
+
+/// Adiciona uma anotação secundária apontando para uma declaração relacionada
+fn with_previous(diagnostic: Diagnostic, previous: Option<Span>, label: &str) -> Diagnostic {
+    match previous {
+        Some(span) => diagnostic.with_annotation(Annotation::secondary(span, label.to_string())),
+        None => diagnostic,
+    }
+}
+
+/// Símbolo textual de um operador binário, como escrito no código
+fn binary_op_symbol(op: &BinaryOp) -> &'static str {
+    match op {
+        BinaryOp::Add => "+",
+        BinaryOp::Subtract => "-",
+        BinaryOp::Multiply => "*",
+        BinaryOp::Divide => "/",
+        BinaryOp::Equals => "==",
+        BinaryOp::NotEquals => "!=",
+        BinaryOp::GreaterThan => ">",
+        BinaryOp::LessThan => "<",
+        BinaryOp::GreaterThanOrEquals => ">=",
+        BinaryOp::LessThanOrEquals => "<=",
+    }
+}
+
+/// Distância de edição entre dois nomes, usada para sugerir nomes parecidos
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = if ca == *cb { 0 } else { 1 };
+            current.push((previous[j] + cost).min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Nome mais parecido com `name` entre `candidates`, se algum for parecido o bastante
+fn closest_name<'a>(name: &str, candidates: impl Iterator<Item = &'a str>) -> Option<String> {
+    candidates
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, candidate)| *distance > 0 && *distance <= (name.len().max(candidate.len()) / 3).max(1))
+        .min()
+        .map(|(_, candidate)| candidate.to_string())
+}
+
+type SemanticResult<T> = Result<T, Box<SemanticError>>;
+
+/// Função sendo chamada, com o necessário para verificar os argumentos
+struct CallTarget {
+    signature: FunctionDecl,
+    /// Onde a função foi declarada, para funções do usuário
+    declared: Option<Span>,
+    /// Onde o tipo de cada parâmetro foi anotado, para funções do usuário
+    param_spans: Vec<Option<Span>>,
+    /// `modulo.funcao`, para funções da biblioteca padrão
+    name: Option<String>,
 }
 
 pub struct SemanticAnalyzer {
     pub symbol_table: SemanticSymbolTable,
     current_function_return_type: Option<Type>,
+    /// Onde está o tipo de retorno declarado da função sendo analisada
+    current_function_span: Option<Span>,
+    /// Aliases declarados com `type`: tipo já resolvido e onde o nome foi declarado
+    type_aliases: HashMap<String, (Type, Span)>,
+    /// Onde o tipo de cada parâmetro foi anotado, por declaração de função
+    function_params: HashMap<Span, Vec<Option<Span>>>,
+    /// Tipos dos `return` da função sendo analisada, para inferir o retorno
+    /// das funções sem anotação
+    function_returns: Vec<Type>,
     pub errors: Vec<SemanticError>,
+    pub warnings: Vec<SemanticWarning>,
+    /// Declarações e usos de nomes, com o tipo visto em cada ponto
+    pub names: Vec<NameInfo>,
+    /// Tipos dos argumentos de cada chamada direta a uma função do programa,
+    /// pelo trecho do nome na declaração da função
+    pub calls: HashMap<Span, Vec<Vec<Type>>>,
+    /// Módulos `.snask` já importados, direta ou indiretamente
+    imported_modules: HashSet<String>,
+    /// Nomes definidos por imports, que um módulo repassa a quem o importa
+    imported_names: Vec<String>,
 }
 
 impl SemanticAnalyzer {
@@ -89,21 +467,31 @@ impl SemanticAnalyzer {
         let mut analyzer = SemanticAnalyzer {
             symbol_table: SemanticSymbolTable::new(),
             current_function_return_type: None,
+            current_function_span: None,
+            type_aliases: HashMap::new(),
+            function_params: HashMap::new(),
+            function_returns: Vec::new(),
             errors: Vec::new(),
+            warnings: Vec::new(),
+            names: Vec::new(),
+            calls: HashMap::new(),
+            imported_modules: HashSet::new(),
+            imported_names: Vec::new(),
         };
         analyzer.register_stdlib();
         analyzer
     }
 
     fn register_stdlib(&mut self) {
-        // Define o módulo 'math' como um objeto de tipo 'Any'.
-        // Isso é um atalho para evitar a necessidade de definir um tipo de dicionário complexo.
-        // O verificador de tipos permitirá qualquer acesso a propriedades em 'math'.
+        // Os módulos nativos são 'Any'; os acessos `modulo.membro` são verificados
+        // pelas assinaturas declaradas em `stdlib/stdlib.snaskd`.
         let math_symbol = SemanticSymbol {
             name: "math".to_string(),
             symbol_type: Type::Any,
             kind: SemanticSymbolKind::Immutable,
             is_variadic: false,
+            declared_at: None,
+            narrowed_from: None,
         };
         self.symbol_table.define(math_symbol);
 
@@ -113,6 +501,8 @@ impl SemanticAnalyzer {
             symbol_type: Type::Any,
             kind: SemanticSymbolKind::Immutable,
             is_variadic: false,
+            declared_at: None,
+            narrowed_from: None,
         };
         self.symbol_table.define(string_symbol);
 
@@ -121,6 +511,8 @@ impl SemanticAnalyzer {
             symbol_type: Type::Any,
             kind: SemanticSymbolKind::Immutable,
             is_variadic: false,
+            declared_at: None,
+            narrowed_from: None,
         };
         self.symbol_table.define(collections_symbol);
 
@@ -129,6 +521,8 @@ impl SemanticAnalyzer {
             symbol_type: Type::Any,
             kind: SemanticSymbolKind::Immutable,
             is_variadic: false,
+            declared_at: None,
+            narrowed_from: None,
         };
         self.symbol_table.define(blaze_symbol);
 
@@ -137,6 +531,8 @@ impl SemanticAnalyzer {
             symbol_type: Type::Any,
             kind: SemanticSymbolKind::Immutable,
             is_variadic: false,
+            declared_at: None,
+            narrowed_from: None,
         };
         self.symbol_table.define(blaze_auth_symbol);
 
@@ -145,6 +541,8 @@ impl SemanticAnalyzer {
             symbol_type: Type::Any,
             kind: SemanticSymbolKind::Immutable,
             is_variadic: false,
+            declared_at: None,
+            narrowed_from: None,
         };
         self.symbol_table.define(blaze_db_symbol);
 
@@ -170,22 +568,22 @@ impl SemanticAnalyzer {
         self.define_builtin("upper", vec![Type::String], Type::String, false);
         self.define_builtin("lower", vec![Type::String], Type::String, false);
         self.define_builtin("trim", vec![Type::String], Type::String, false);
-        self.define_builtin("split", vec![Type::String, Type::String], Type::List, false);
-        self.define_builtin("join", vec![Type::List, Type::String], Type::String, false);
+        self.define_builtin("split", vec![Type::String, Type::String], Type::list(Type::String), false);
+        self.define_builtin("join", vec![Type::list(Type::Any), Type::String], Type::String, false);
         self.define_builtin("replace", vec![Type::String, Type::String, Type::String], Type::String, false);
         self.define_builtin("contains", vec![Type::String, Type::String], Type::Bool, false);
         self.define_builtin("starts_with", vec![Type::String, Type::String], Type::Bool, false);
         self.define_builtin("ends_with", vec![Type::String, Type::String], Type::Bool, false);
-        self.define_builtin("chars", vec![Type::String], Type::List, false);
+        self.define_builtin("chars", vec![Type::String], Type::list(Type::String), false);
         self.define_builtin("substring", vec![Type::String, Type::Float, Type::Float], Type::String, false);
         self.define_builtin("format", vec![Type::String, Type::Any, Type::Any], Type::String, true); // Variadic support // Basic support
 
         // Collections
-        self.define_builtin("range", vec![Type::Float], Type::List, false); // Basic support
-        self.define_builtin("sort", vec![Type::List], Type::List, false);
-        self.define_builtin("reverse", vec![Type::List], Type::List, false);
-        self.define_builtin("unique", vec![Type::List], Type::List, false);
-        self.define_builtin("flatten", vec![Type::List], Type::List, false);
+        self.define_builtin("range", vec![Type::Float], Type::list(Type::Int), false); // Basic support
+        self.define_builtin("sort", vec![Type::list(Type::Any)], Type::list(Type::Any), false);
+        self.define_builtin("reverse", vec![Type::list(Type::Any)], Type::list(Type::Any), false);
+        self.define_builtin("unique", vec![Type::list(Type::Any)], Type::list(Type::Any), false);
+        self.define_builtin("flatten", vec![Type::list(Type::Any)], Type::list(Type::Any), false);
         // TODO: map, filter, reduce (need function type support in args)
 
         // IO
@@ -194,13 +592,13 @@ impl SemanticAnalyzer {
         self.define_builtin("append_file", vec![Type::String, Type::String], Type::Void, false);
         self.define_builtin("exists", vec![Type::String], Type::Bool, false);
         self.define_builtin("delete", vec![Type::String], Type::Void, false);
-        self.define_builtin("read_dir", vec![Type::String], Type::List, false);
+        self.define_builtin("read_dir", vec![Type::String], Type::list(Type::String), false);
         self.define_builtin("is_file", vec![Type::String], Type::Bool, false);
         self.define_builtin("is_dir", vec![Type::String], Type::Bool, false);
         self.define_builtin("create_dir", vec![Type::String], Type::Void, false);
 
         // HTTP
-        self.define_builtin("http_get", vec![Type::String], Type::Dict, false);
+        self.define_builtin("http_get", vec![Type::String], Type::dict(Type::String, Type::Any), false);
         self.define_builtin("http_post", vec![Type::String, Type::String], Type::Void, false);
 
         // JSON
@@ -212,13 +610,18 @@ impl SemanticAnalyzer {
         self.define_builtin("time", vec![], Type::Float, false);
         self.define_builtin("sleep", vec![Type::Float], Type::Void, false);
         self.define_builtin("exit", vec![Type::Float], Type::Void, false);
-        self.define_builtin("args", vec![], Type::List, false);
+        self.define_builtin("args", vec![], Type::list(Type::String), false);
         self.define_builtin("env", vec![Type::String], Type::String, false);
         self.define_builtin("set_env", vec![Type::String, Type::String], Type::Void, false);
         self.define_builtin("cwd", vec![], Type::String, false);
         self.define_builtin("platform", vec![], Type::String, false);
         self.define_builtin("arch", vec![], Type::String, false);
 
+        // Testes (a mensagem é opcional, por isso são variádicas)
+        self.define_builtin("assert", vec![Type::Any, Type::String], Type::Void, true);
+        self.define_builtin("assert_eq", vec![Type::Any, Type::Any, Type::String], Type::Void, true);
+        self.define_builtin("assert_raises", vec![Type::Function(vec![], Box::new(Type::Any)), Type::String], Type::String, true);
+
         // Math - Novas funÃ§Ãµes
         self.define_builtin("mod", vec![Type::Float, Type::Float], Type::Float, false);
         self.define_builtin("random", vec![], Type::Float, false);
@@ -229,12 +632,35 @@ impl SemanticAnalyzer {
         self.define_builtin("rad_to_deg", vec![Type::Float], Type::Float, false);
     }
 
+    /// Declaração do módulo nativo que `target` nomeia, como `math` em `math.sqrt`
+    fn stdlib_module(&self, target: &Expr) -> Option<&'static ModuleDecl> {
+        let ExprKind::Variable(module) = &target.kind else {
+            return None;
+        };
+        // Uma variável do usuário com o mesmo nome esconde o módulo
+        if self.symbol_table.lookup(module)?.declared_at.is_some() {
+            return None;
+        }
+        stdlib_module(module)
+    }
+
+    fn member_not_found(module: &ModuleDecl, member: &str, span: Span) -> SemanticError {
+        SemanticError::MemberNotFound {
+            module: module.name.clone(),
+            member: member.to_string(),
+            span,
+            suggestion: closest_name(member, module.member_names()),
+        }
+    }
+
     fn define_builtin(&mut self, name: &str, params: Vec<Type>, return_type: Type, is_variadic: bool) {
         let symbol = SemanticSymbol {
             name: name.to_string(),
             symbol_type: Type::Function(params, Box::new(return_type)),
             kind: SemanticSymbolKind::Function,
             is_variadic,
+            declared_at: None,
+            narrowed_from: None,
         };
         self.symbol_table.define(symbol);
     }
@@ -245,193 +671,363 @@ impl SemanticAnalyzer {
             symbol_type: const_type,
             kind: SemanticSymbolKind::Constant,
             is_variadic: false,
+            declared_at: None,
+            narrowed_from: None,
         };
         self.symbol_table.define(symbol);
     }
 
+    fn record_name(&mut self, span: Span, symbol: &SemanticSymbol) {
+        self.names.push(NameInfo {
+            name: symbol.name.clone(),
+            span,
+            symbol_type: symbol.symbol_type.clone(),
+            kind: symbol.kind.clone(),
+            declared_at: symbol.declared_at,
+        });
+    }
+
+    /// Erro de variável não encontrada, sugerindo um nome visível parecido
+    fn variable_not_found(&self, name: &str, span: Span) -> SemanticError {
+        let suggestion = closest_name(name, self.symbol_table.visible_names());
+        SemanticError::VariableNotFound { name: name.to_string(), span, suggestion }
+    }
+
     pub fn analyze(&mut self, program: &Program) {
+        // Aliases do nível superior valem no arquivo inteiro, inclusive antes da declaração
         for statement in program {
-            self.analyze_statement(statement);
+            if let StmtKind::TypeAlias { name, name_span, target, type_span } = &statement.kind {
+                self.define_type_alias(name, *name_span, target, *type_span);
+            }
+        }
+        self.analyze_body(program);
+    }
+
+    /// Tipo de uma expressão, com os nomes já declarados nas análises anteriores
+    /// (usado pelo `:type` do REPL)
+    pub fn expression_type(&mut self, expression: &Expr) -> Result<Type, Box<SemanticError>> {
+        self.type_check_expression(expression)
+    }
+
+    fn define_type_alias(&mut self, name: &str, name_span: Span, target: &Type, type_span: Span) {
+        if let Some((_, previous)) = self.type_aliases.get(name) {
+            self.errors.push(SemanticError::TypeAlreadyDeclared { name: name.to_string(), span: name_span, previous: Some(*previous) });
+            return;
         }
+        // O alvo é resolvido com os aliases já declarados, o que impede aliases recursivos
+        let resolved = self.resolve_type(target, type_span);
+        self.type_aliases.insert(name.to_string(), (resolved, name_span));
+    }
+
+    /// Substitui os aliases em um tipo anotado no código pelos tipos que eles nomeiam
+    fn resolve_type(&mut self, annotated: &Type, span: Span) -> Type {
+        let (aliases, errors) = (&self.type_aliases, &mut self.errors);
+        annotated.resolve_named(&mut |name| match aliases.get(name) {
+            Some((resolved, _)) => resolved.clone(),
+            None => {
+                errors.push(SemanticError::UnknownType { name: name.to_string(), span });
+                Type::Any
+            }
+        })
     }
 
     fn analyze_statement(&mut self, statement: &Stmt) {
         match &statement.kind {
-            StmtKind::VarDeclaration(decl) => self.analyze_var_decl(decl, SemanticSymbolKind::Immutable),
-            StmtKind::MutDeclaration(decl) => self.analyze_var_decl(&decl.to_var_decl(), SemanticSymbolKind::Mutable),
-            StmtKind::ConstDeclaration(decl) => self.analyze_var_decl(&decl.to_var_decl(), SemanticSymbolKind::Constant),
-            StmtKind::Input { name, var_type } => {
+            StmtKind::VarDeclaration(decl) => self.analyze_var_decl(decl, SemanticSymbolKind::Immutable, statement.span),
+            StmtKind::MutDeclaration(decl) => self.analyze_var_decl(&decl.to_var_decl(), SemanticSymbolKind::Mutable, statement.span),
+            StmtKind::ConstDeclaration(decl) => self.analyze_var_decl(&decl.to_var_decl(), SemanticSymbolKind::Constant, statement.span),
+            StmtKind::Input { name, var_type, type_span } => {
+                let span = statement.span;
                 let symbol = SemanticSymbol {
                     name: name.clone(),
-                    symbol_type: var_type.clone(),
+                    symbol_type: self.resolve_type(var_type, *type_span),
                     kind: SemanticSymbolKind::Mutable,
                     is_variadic: false,
+                    declared_at: Some(span),
+                    narrowed_from: None,
                 };
-                if !self.symbol_table.define(symbol) {
-                    self.errors.push(SemanticError::VariableAlreadyDeclared(name.clone()));
-                }
+                self.define_variable(symbol, span);
             }
             StmtKind::VarAssignment(var_set) => {
-                let expr_type = match self.type_check_expression(&var_set.value) {
+                let declared_type = self
+                    .symbol_table
+                    .lookup(&var_set.name)
+                    .map(|symbol| symbol.narrowed_from.clone().unwrap_or_else(|| symbol.symbol_type.clone()))
+                    .unwrap_or(Type::Any);
+                let expr_type = match self.type_check_against(&var_set.value, &declared_type) {
                     Ok(t) => t,
                     Err(e) => {
-                        self.errors.push(e);
+                        self.errors.push(*e);
                         return;
                     }
                 };
 
-                if let Some(symbol) = self.symbol_table.lookup(&var_set.name) {
+                let span = statement.span;
+                if let Some(symbol) = self.symbol_table.lookup(&var_set.name).cloned() {
+                    self.record_name(span, &symbol);
                     if symbol.kind == SemanticSymbolKind::Constant || symbol.kind == SemanticSymbolKind::Immutable {
-                        self.errors.push(SemanticError::ImmutableAssignment(var_set.name.clone()));
-                    }
-                    if !self.is_compatible(&symbol.symbol_type, &expr_type) && expr_type != Type::Any {
-                        self.errors.push(SemanticError::TypeMismatch {
-                            expected: symbol.symbol_type.clone(),
-                            found: expr_type,
+                        self.errors.push(SemanticError::ImmutableAssignment {
+                            name: var_set.name.clone(),
+                            span,
+                            declared: symbol.declared_at,
+                            constant: symbol.kind == SemanticSymbolKind::Constant,
                         });
                     }
+                    if !self.is_compatible(&declared_type, &expr_type) {
+                        let error = self.mismatch(&declared_type, expr_type, var_set.value.span, symbol.declared_at);
+                        self.errors.push(error);
+                    } else if symbol.narrowed_from.is_some() && expr_type.is_nullable() {
+                        // A variável pode voltar a ser `nil`: desfaz o estreitamento
+                        if let Some(symbol) = self.symbol_table.lookup_mut(&var_set.name) {
+                            symbol.symbol_type = declared_type;
+                            symbol.narrowed_from = None;
+                        }
+                    }
                 } else {
-                    self.errors.push(SemanticError::VariableNotFound(var_set.name.clone()));
+                    let error = self.variable_not_found(&var_set.name, span);
+                    self.errors.push(error);
                 }
             }
             StmtKind::FuncDeclaration(func_decl) => {
-                let params_types: Vec<Type> = func_decl.params.iter().map(|p| p.1.clone()).collect();
+                let params_types: Vec<Type> = func_decl
+                    .params
+                    .iter()
+                    .map(|p| self.resolve_type(&p.param_type, p.type_span.unwrap_or(p.span)))
+                    .collect();
+                let span = func_decl.name_span;
+                let return_type = match &func_decl.return_type {
+                    Some(t) => self.resolve_type(t, func_decl.return_type_span.unwrap_or(span)),
+                    None => Type::Any,
+                };
                 let func_symbol = SemanticSymbol {
                     name: func_decl.name.clone(),
-                    symbol_type: Type::Function(params_types, Box::new(func_decl.return_type.clone().unwrap_or(Type::Any))),
+                    symbol_type: Type::Function(params_types.clone(), Box::new(return_type.clone())),
                     kind: SemanticSymbolKind::Function,
                     is_variadic: false,
+                    declared_at: Some(span),
+                    narrowed_from: None,
                 };
+                let previous = self.symbol_table.lookup(&func_decl.name).and_then(|s| s.declared_at);
                 if !self.symbol_table.define(func_symbol) {
-                    self.errors.push(SemanticError::FunctionAlreadyDeclared(func_decl.name.clone()));
+                    self.errors.push(SemanticError::FunctionAlreadyDeclared { name: func_decl.name.clone(), span, previous });
                     return;
                 }
+                self.function_params.insert(span, func_decl.params.iter().map(|p| p.type_span).collect());
 
                 self.symbol_table.enter_scope();
-                let prev_return_type = self.current_function_return_type.clone();
-                self.current_function_return_type = Some(func_decl.return_type.clone().unwrap_or(Type::Any));
+                let prev_return_type = self.current_function_return_type.replace(return_type.clone());
+                let prev_function_span = self.current_function_span.replace(func_decl.return_type_span.unwrap_or(span));
+                let prev_returns = std::mem::take(&mut self.function_returns);
 
-                for (param_name, param_type) in &func_decl.params {
+                for (param, param_type) in func_decl.params.iter().zip(params_types.clone()) {
                     let param_symbol = SemanticSymbol {
-                        name: param_name.clone(),
-                        symbol_type: param_type.clone(),
+                        name: param.name.clone(),
+                        symbol_type: param_type,
                         kind: SemanticSymbolKind::Parameter,
                         is_variadic: false,
+                        declared_at: Some(param.span),
+                        narrowed_from: None,
                     };
+                    self.record_name(param.span, &param_symbol);
                     self.symbol_table.define(param_symbol);
                 }
 
-                for stmt in &func_decl.body {
-                    self.analyze_statement(stmt);
-                }
+                self.analyze_body(&func_decl.body);
 
+                let returns = std::mem::replace(&mut self.function_returns, prev_returns);
                 self.current_function_return_type = prev_return_type;
+                self.current_function_span = prev_function_span;
                 self.symbol_table.exit_scope();
+
+                let falls_through = !Self::always_returns(&func_decl.body);
+                if func_decl.return_type.is_none() {
+                    // Quem chama a função passa a ver o tipo inferido em vez de `any`
+                    let inferred = Self::infer_return_type(returns, falls_through);
+                    if let Some(symbol) = self.symbol_table.lookup_mut(&func_decl.name) {
+                        symbol.symbol_type = Type::Function(params_types, Box::new(inferred));
+                    }
+                } else if falls_through && return_type != Type::Void {
+                    self.errors.push(SemanticError::MissingReturn {
+                        name: func_decl.name.clone(),
+                        return_type,
+                        span,
+                        declared: func_decl.return_type_span,
+                    });
+                }
+                if let Some(symbol) = self.symbol_table.lookup(&func_decl.name).cloned() {
+                    self.record_name(span, &symbol);
+                }
             }
             StmtKind::Return(expr) => {
-                let return_type = match self.type_check_expression(expr) {
+                let expected = self.current_function_return_type.clone().unwrap_or(Type::Any);
+                let return_type = match self.type_check_against(expr, &expected) {
                     Ok(t) => t,
                     Err(e) => {
-                        self.errors.push(e);
+                        self.errors.push(*e);
                         return;
                     }
                 };
+                self.function_returns.push(return_type.clone());
 
                 match &self.current_function_return_type {
                     Some(expected_type) => {
                         if !self.is_compatible(expected_type, &return_type) {
-                            self.errors.push(SemanticError::TypeMismatch {
-                                expected: expected_type.clone(),
-                                found: return_type,
-                            });
+                            let error = self.mismatch(expected_type, return_type, expr.span, self.current_function_span);
+                            self.errors.push(error);
                         }
                     }
-                    None => self.errors.push(SemanticError::ReturnOutsideFunction),
+                    None => self.errors.push(SemanticError::ReturnOutsideFunction {
+                        span: statement.span,
+                    }),
                 }
             }
             StmtKind::Conditional(cond) => self.analyze_conditional(cond),
-            StmtKind::Loop(loop_stmt) => self.analyze_loop(loop_stmt),
+            StmtKind::Loop(loop_stmt) => self.analyze_loop(loop_stmt, statement.span),
             StmtKind::ListDeclaration(decl) => {
                 let var_decl = VarDecl {
                     name: decl.name.clone(),
                     var_type: decl.var_type.clone(),
+                    type_span: decl.type_span,
                     value: decl.value.clone(),
                 };
-                self.analyze_var_decl(&var_decl, SemanticSymbolKind::Immutable);
+                self.analyze_var_decl(&var_decl, SemanticSymbolKind::Immutable, statement.span);
             }
             StmtKind::DictDeclaration(decl) => {
                 let var_decl = VarDecl {
                     name: decl.name.clone(),
                     var_type: decl.var_type.clone(),
+                    type_span: decl.type_span,
                     value: decl.value.clone(),
                 };
-                self.analyze_var_decl(&var_decl, SemanticSymbolKind::Immutable);
+                self.analyze_var_decl(&var_decl, SemanticSymbolKind::Immutable, statement.span);
             }
             StmtKind::ListPush(push) => {
+                let span = statement.span;
                 if let Some(symbol) = self.symbol_table.lookup(&push.name) {
-                    if symbol.symbol_type != Type::List {
-                        self.errors.push(SemanticError::InvalidOperation {
-                            op: "list_push".to_string(),
-                            type1: symbol.symbol_type.clone(),
-                            type2: None,
-                        });
-                    }
-                    let _ = self.type_check_expression(&push.value);
+                    let element_type = match &symbol.symbol_type {
+                        Type::List(element) => (**element).clone(),
+                        Type::Any => Type::Any,
+                        nullable if nullable.is_nullable() => {
+                            self.errors.push(SemanticError::PossiblyNil { found: nullable.clone(), span });
+                            Type::Any
+                        }
+                        other => {
+                            self.errors.push(SemanticError::InvalidOperation {
+                                op: "list_push".to_string(),
+                                type1: other.clone(),
+                                type2: None,
+                                span,
+                            });
+                            Type::Any
+                        }
+                    };
+                    self.check_value(&push.value, &element_type);
                 } else {
-                    self.errors.push(SemanticError::VariableNotFound(push.name.clone()));
+                    let error = self.variable_not_found(&push.name, span);
+                    self.errors.push(error);
                 }
             }
             StmtKind::DictSet(set) => {
+                let span = statement.span;
                 if let Some(symbol) = self.symbol_table.lookup(&set.name) {
-                    if symbol.symbol_type != Type::Dict {
-                        self.errors.push(SemanticError::InvalidOperation {
-                            op: "dict_set".to_string(),
-                            type1: symbol.symbol_type.clone(),
-                            type2: None,
-                        });
-                    }
-                    let _ = self.type_check_expression(&set.key);
-                    let _ = self.type_check_expression(&set.value);
+                    let (key_type, value_type) = match &symbol.symbol_type {
+                        Type::Dict(key, value) => ((**key).clone(), (**value).clone()),
+                        Type::Record(_) => (Type::String, Type::Any),
+                        Type::Any => (Type::Any, Type::Any),
+                        nullable if nullable.is_nullable() => {
+                            self.errors.push(SemanticError::PossiblyNil { found: nullable.clone(), span });
+                            (Type::Any, Type::Any)
+                        }
+                        other => {
+                            self.errors.push(SemanticError::InvalidOperation {
+                                op: "dict_set".to_string(),
+                                type1: other.clone(),
+                                type2: None,
+                                span,
+                            });
+                            (Type::Any, Type::Any)
+                        }
+                    };
+                    self.check_value(&set.key, &key_type);
+                    self.check_value(&set.value, &value_type);
                 } else {
-                    self.errors.push(SemanticError::VariableNotFound(set.name.clone()));
+                    let error = self.variable_not_found(&set.name, span);
+                    self.errors.push(error);
                 }
             }
             StmtKind::Expression(expr) | StmtKind::FuncCall(expr) => {
                 if let Err(e) = self.type_check_expression(expr) {
-                    self.errors.push(e);
+                    self.errors.push(*e);
                 }
             }
             StmtKind::Print(expressions) => {
                 for expr in expressions {
                     if let Err(e) = self.type_check_expression(expr) {
-                        self.errors.push(e);
+                        self.errors.push(*e);
                     }
                 }
             }
-            StmtKind::Import(_path) => {
-                // Import statements are handled at runtime
-                // No semantic analysis needed here
+            // Os do nível superior já foram registrados em `analyze`
+            StmtKind::TypeAlias { name, name_span, target, type_span } if self.symbol_table.scopes.len() > 1 => {
+                self.define_type_alias(name, *name_span, target, *type_span);
             }
+            StmtKind::TypeAlias { .. } => {}
+            StmtKind::Test { body, .. } => {
+                if self.symbol_table.scopes.len() > 1 {
+                    self.errors.push(SemanticError::TestOutsideTopLevel { span: statement.span });
+                }
+                self.analyze_block(body);
+            }
+            StmtKind::Import(path) => self.import_module(path),
+        }
+    }
+
+    /// Os nomes do nível de cima de um módulo `.snask`, e dos módulos que ele
+    /// importa, passam a existir aqui com os tipos que a análise do módulo
+    /// encontrou. Os erros do módulo são relatados quando ele é analisado sozinho.
+    fn import_module(&mut self, path: &str) {
+        if crate::modules::is_native_module(path) || !self.imported_modules.insert(path.to_string()) {
+            return;
+        }
+        let Ok(module) = crate::modules::load_module(path) else { return };
+        let mut analyzer = SemanticAnalyzer::new();
+        // Evita analisar de novo (ou em ciclo) os módulos já importados
+        analyzer.imported_modules = self.imported_modules.clone();
+        analyzer.analyze(&module);
+        self.imported_modules.extend(analyzer.imported_modules);
+
+        let exported = crate::lint::declared_names(&module).into_iter().chain(analyzer.imported_names);
+        for name in exported {
+            let Some(symbol) = analyzer.symbol_table.lookup(&name) else { continue };
+            self.symbol_table.define(SemanticSymbol {
+                // Declarados em outro arquivo: os spans não valem aqui
+                declared_at: None,
+                narrowed_from: None,
+                ..symbol.clone()
+            });
+            self.imported_names.push(name);
         }
     }
 
-    fn analyze_var_decl(&mut self, decl: &VarDecl, kind: SemanticSymbolKind) {
-        let expr_type = match self.type_check_expression(&decl.value) {
+    fn analyze_var_decl(&mut self, decl: &VarDecl, kind: SemanticSymbolKind, span: Span) {
+        let declared_type = decl.var_type.as_ref().map(|t| self.resolve_type(t, decl.type_span.unwrap_or(span)));
+        let expr_type = match self.type_check_against(&decl.value, declared_type.as_ref().unwrap_or(&Type::Any)) {
             Ok(t) => t,
             Err(e) => {
-                self.errors.push(e);
+                self.errors.push(*e);
                 return;
             }
         };
 
-        let final_type = if let Some(ref expected_type) = decl.var_type {
-            if !self.is_compatible(expected_type, &expr_type) {
-                self.errors.push(SemanticError::TypeMismatch {
-                    expected: expected_type.clone(),
-                    found: expr_type,
-                });
+        let final_type = if let Some(expected_type) = declared_type {
+            if !self.is_compatible(&expected_type, &expr_type) {
+                let error = self.mismatch(&expected_type, expr_type, decl.value.span, decl.type_span);
+                self.errors.push(error);
             }
-            expected_type.clone()
+            expected_type
+        } else if expr_type == Type::Nil {
+            // `let x = nil` sem anotação: a variável pode receber qualquer valor depois
+            Type::optional(Type::Any)
         } else {
             expr_type
         };
@@ -441,58 +1037,178 @@ impl SemanticAnalyzer {
             symbol_type: final_type,
             kind,
             is_variadic: false,
+            declared_at: Some(span),
+            narrowed_from: None,
         };
+        self.define_variable(symbol, span);
+    }
 
+    /// Define uma variável no escopo atual, registrando a redeclaração como erro
+    fn define_variable(&mut self, symbol: SemanticSymbol, span: Span) {
+        let name = symbol.name.clone();
+        let previous = self.symbol_table.scopes.last().and_then(|scope| scope.get(&name)).and_then(|s| s.declared_at);
+        self.record_name(span, &symbol);
         if !self.symbol_table.define(symbol) {
-            self.errors.push(SemanticError::VariableAlreadyDeclared(decl.name.clone()));
+            self.errors.push(SemanticError::VariableAlreadyDeclared { name, span, previous });
         }
     }
 
     fn analyze_conditional(&mut self, cond: &ConditionalStmt) {
-        if let Err(e) = self.check_condition(&cond.if_block.condition) { self.errors.push(e); }
+        // Escopos com as variáveis estreitadas pelas condições falsas dos ramos
+        // anteriores: em `if x == nil { } else { }`, `x` não é nil no `else`
+        let mut fallthrough_scopes = 0;
+        for block in std::iter::once(&cond.if_block).chain(&cond.elif_blocks) {
+            if let Err(e) = self.check_condition(&block.condition) { self.errors.push(*e); }
+            let check = Self::nil_check(&block.condition);
+            let narrowed = matches!(&check, Some((name, true)) if self.enter_narrowed_scope(name));
+            self.analyze_block(&block.body);
+            if narrowed { self.symbol_table.exit_scope(); }
+            if matches!(&check, Some((name, false)) if self.enter_narrowed_scope(name)) {
+                fallthrough_scopes += 1;
+            }
+        }
+
+        if let Some(else_body) = &cond.else_block {
+            self.analyze_block(else_body);
+        }
+        for _ in 0..fallthrough_scopes {
+            self.symbol_table.exit_scope();
+        }
+
+        // `if x == nil { return ... }`: depois do `if`, `x` não é nil
+        if Self::always_returns(&cond.if_block.body) && cond.elif_blocks.is_empty() && cond.else_block.is_none() {
+            if let Some((name, false)) = Self::nil_check(&cond.if_block.condition) {
+                if let Some(narrowed) = self.narrowed(&name) {
+                    self.symbol_table.redefine(narrowed);
+                }
+            }
+        }
+    }
+
+    fn analyze_block(&mut self, body: &[Stmt]) {
         self.symbol_table.enter_scope();
-        for stmt in &cond.if_block.body { self.analyze_statement(stmt); }
+        self.analyze_body(body);
         self.symbol_table.exit_scope();
+    }
 
-        for elif in &cond.elif_blocks {
-            if let Err(e) = self.check_condition(&elif.condition) { self.errors.push(e); }
-            self.symbol_table.enter_scope();
-            for stmt in &elif.body { self.analyze_statement(stmt); }
-            self.symbol_table.exit_scope();
+    /// Analisa os statements de um bloco no escopo atual, avisando sobre o
+    /// código que vem depois de um ponto de onde a execução não passa
+    fn analyze_body(&mut self, body: &[Stmt]) {
+        for stmt in body { self.analyze_statement(stmt); }
+        if let Some(index) = body.iter().position(Self::always_returns_stmt) {
+            if let (Some(first), Some(last)) = (body.get(index + 1), body.last()) {
+                self.warnings.push(SemanticWarning::UnreachableCode {
+                    span: Span::new(first.span.start, last.span.end),
+                    cause: body[index].span,
+                });
+            }
         }
+    }
 
-        if let Some(else_body) = &cond.else_block {
-            self.symbol_table.enter_scope();
-            for stmt in else_body { self.analyze_statement(stmt); }
-            self.symbol_table.exit_scope();
+    /// Se a execução nunca chega ao fim de `body`: todo caminho termina em `return`
+    fn always_returns(body: &[Stmt]) -> bool {
+        body.iter().any(Self::always_returns_stmt)
+    }
+
+    fn always_returns_stmt(stmt: &Stmt) -> bool {
+        match &stmt.kind {
+            StmtKind::Return(_) => true,
+            StmtKind::Conditional(cond) => {
+                cond.else_block.as_ref().is_some_and(|body| Self::always_returns(body))
+                    && Self::always_returns(&cond.if_block.body)
+                    && cond.elif_blocks.iter().all(|block| Self::always_returns(&block.body))
+            }
+            // Não há `break`: um `while true` só termina com `return`
+            StmtKind::Loop(LoopStmt::While { condition, .. }) => {
+                matches!(condition.kind, ExprKind::Literal(LiteralValue::Boolean(true)))
+            }
+            _ => false,
+        }
+    }
+
+    /// Tipo de retorno de uma função sem anotação, a partir dos seus `return`.
+    /// Se a execução pode chegar ao fim do corpo, a função também pode retornar `nil`.
+    fn infer_return_type(returns: Vec<Type>, falls_through: bool) -> Type {
+        let Some(joined) = returns.into_iter().reduce(|a, b| Type::join(&a, &b)) else {
+            return Type::Void;
+        };
+        if falls_through && joined != Type::Any { Type::optional(joined) } else { joined }
+    }
+
+    /// Variável que uma condição compara com `nil`, e se ela é diferente de
+    /// `nil` quando a condição é verdadeira (`x != nil`) ou falsa (`x == nil`)
+    fn nil_check(condition: &Expr) -> Option<(String, bool)> {
+        let variable = |expr: &Expr| match &expr.kind {
+            ExprKind::Variable(name) => Some(name.clone()),
+            _ => None,
+        };
+        let is_nil = |expr: &Expr| matches!(expr.kind, ExprKind::Literal(LiteralValue::Nil));
+        match &condition.kind {
+            ExprKind::Binary { left, op: op @ (BinaryOp::Equals | BinaryOp::NotEquals), right } => {
+                let name = if is_nil(right) { variable(left) } else if is_nil(left) { variable(right) } else { None }?;
+                Some((name, *op == BinaryOp::NotEquals))
+            }
+            _ => None,
         }
     }
 
-    fn analyze_loop(&mut self, loop_stmt: &LoopStmt) {
+    /// Cópia do símbolo `name` sem a possibilidade de `nil`, se ele for opcional
+    fn narrowed(&self, name: &str) -> Option<SemanticSymbol> {
+        let symbol = self.symbol_table.lookup(name)?;
+        if !symbol.symbol_type.is_nullable() {
+            return None;
+        }
+        Some(SemanticSymbol {
+            symbol_type: symbol.symbol_type.without_nil(),
+            narrowed_from: Some(symbol.symbol_type.clone()),
+            ..symbol.clone()
+        })
+    }
+
+    /// Abre um escopo em que `name` não é nil. Não abre nada (e retorna `false`)
+    /// se a variável não é opcional.
+    fn enter_narrowed_scope(&mut self, name: &str) -> bool {
+        let Some(narrowed) = self.narrowed(name) else {
+            return false;
+        };
+        self.symbol_table.enter_scope();
+        self.symbol_table.define(narrowed);
+        true
+    }
+
+    fn analyze_loop(&mut self, loop_stmt: &LoopStmt, span: Span) {
         self.symbol_table.enter_scope();
         match loop_stmt {
             LoopStmt::While { condition, body } => {
-                if let Err(e) = self.check_condition(condition) { self.errors.push(e); }
-                for stmt in body { self.analyze_statement(stmt); }
+                if let Err(e) = self.check_condition(condition) { self.errors.push(*e); }
+                let narrowed = matches!(Self::nil_check(condition), Some((name, true)) if self.enter_narrowed_scope(&name));
+                self.analyze_block(body);
+                if narrowed { self.symbol_table.exit_scope(); }
             }
             LoopStmt::For { iterator, iterable, body } => {
                 let iterable_type = match self.type_check_expression(iterable) {
                     Ok(t) => t,
                     Err(e) => {
-                        self.errors.push(e);
+                        self.errors.push(*e);
                         self.symbol_table.exit_scope();
                         return;
                     }
                 };
 
                 let iterator_type = match iterable_type {
-                    Type::List => Type::Any,
+                    Type::List(element) => *element,
                     Type::String => Type::String,
+                    Type::Any => Type::Any,
+                    Type::Optional(_) | Type::Nil => {
+                        self.errors.push(SemanticError::PossiblyNil { found: iterable_type, span: iterable.span });
+                        Type::Any
+                    }
                     _ => {
                         self.errors.push(SemanticError::InvalidOperation {
                             op: "for-in".to_string(),
                             type1: iterable_type,
                             type2: None,
+                            span: iterable.span,
                         });
                         Type::Void
                     }
@@ -503,52 +1219,271 @@ impl SemanticAnalyzer {
                     symbol_type: iterator_type,
                     kind: SemanticSymbolKind::Immutable,
                     is_variadic: false,
+                    declared_at: Some(span),
+                    narrowed_from: None,
                 };
+                self.record_name(span, &symbol);
                 self.symbol_table.define(symbol);
 
-                for stmt in body { self.analyze_statement(stmt); }
+                self.analyze_body(body);
             }
         }
         self.symbol_table.exit_scope();
     }
 
-    fn check_condition(&mut self, expr: &Expr) -> Result<(), SemanticError> {
+    fn check_condition(&mut self, expr: &Expr) -> SemanticResult<()> {
         let expr_type = self.type_check_expression(expr)?;
         if expr_type != Type::Bool && expr_type != Type::Any {
-            return Err(SemanticError::TypeMismatch {
+            return Err(Box::new(SemanticError::TypeMismatch {
                 expected: Type::Bool,
                 found: expr_type,
-            });
+                span: expr.span,
+                expected_from: None,
+            }));
         }
         Ok(())
     }
 
+    /// Verifica o tipo de `value` contra `expected`, registrando o erro se houver
+    fn check_value(&mut self, value: &Expr, expected: &Type) {
+        match self.type_check_against(value, expected) {
+            Ok(found) => {
+                if !self.is_compatible(expected, &found) {
+                    let error = self.mismatch(expected, found, value.span, None);
+                    self.errors.push(error);
+                }
+            }
+            Err(e) => self.errors.push(*e),
+        }
+    }
+
     fn is_compatible(&self, expected: &Type, found: &Type) -> bool {
         if expected == found { return true; }
         if *expected == Type::Float && *found == Type::Int { return true; }
-        matches!((expected, found), (Type::Any, _))
+        match (expected, found) {
+            // Valores `any` (ex: elementos de `[]` ou retornos de `json_parse`) só são verificados em execução
+            (Type::Any, _) | (_, Type::Any) => true,
+            (Type::Optional(_), Type::Nil) => true,
+            (Type::Optional(expected), Type::Optional(found)) => self.is_compatible(expected, found),
+            (Type::Optional(expected), found) => self.is_compatible(expected, found),
+            (_, Type::Union(found)) => found.iter().all(|member| self.is_compatible(expected, member)),
+            (Type::Union(expected), found) => expected.iter().any(|member| self.is_compatible(member, found)),
+            // Todos os campos precisam existir (os opcionais podem valer `nil`); campos a mais são permitidos
+            (Type::Record(expected), Type::Record(found)) => expected.iter().all(|(name, expected)| {
+                found.iter().any(|(found_name, found)| found_name == name && self.is_compatible(expected, found))
+            }),
+            // Um `dict` comum não garante os campos: só os tipos de chave e valor são verificados
+            (Type::Record(fields), Type::Dict(key, value)) => {
+                self.is_compatible(key, &Type::String) && fields.iter().all(|(_, field)| self.is_compatible(field, value))
+            }
+            (Type::Dict(key, value), Type::Record(fields)) => {
+                self.is_compatible(key, &Type::String) && fields.iter().all(|(_, field)| self.is_compatible(value, field))
+            }
+            (Type::List(expected), Type::List(found)) => self.is_compatible(expected, found),
+            (Type::Dict(expected_key, expected_value), Type::Dict(found_key, found_value)) => {
+                self.is_compatible(expected_key, found_key) && self.is_compatible(expected_value, found_value)
+            }
+            // A função recebida precisa aceitar os argumentos que serão passados a ela
+            (Type::Function(expected_params, expected_return), Type::Function(found_params, found_return)) => {
+                expected_params.len() == found_params.len()
+                    && expected_params.iter().zip(found_params).all(|(expected, found)| self.is_compatible(found, expected))
+                    && self.is_compatible(expected_return, found_return)
+            }
+            _ => false,
+        }
+    }
+
+    /// Tipo de `expr` onde se espera `expected`. Um literal `{"campo": valor}`
+    /// esperado como tipo estrutural é verificado campo a campo, e os itens de
+    /// um literal de lista ou dict, contra os tipos de elemento esperados.
+    fn type_check_against(&mut self, expr: &Expr, expected: &Type) -> SemanticResult<Type> {
+        let record = expected.without_nil();
+        match (&record, &expr.kind) {
+            (Type::List(element), ExprKind::Literal(LiteralValue::List(items))) if **element != Type::Any => {
+                for item in items {
+                    self.check_value(item, element);
+                }
+                return Ok(record);
+            }
+            (Type::Dict(key, value), ExprKind::Literal(LiteralValue::Dict(pairs))) if **key != Type::Any || **value != Type::Any => {
+                for (key_expr, value_expr) in pairs {
+                    self.check_value(key_expr, key);
+                    self.check_value(value_expr, value);
+                }
+                return Ok(record);
+            }
+            _ => {}
+        }
+        if let (Type::Record(fields), ExprKind::Literal(LiteralValue::Dict(pairs))) = (&record, &expr.kind) {
+            let keys: Option<Vec<&String>> = pairs
+                .iter()
+                .map(|(key, _)| match &key.kind {
+                    ExprKind::Literal(LiteralValue::String(name)) => Some(name),
+                    _ => None,
+                })
+                .collect();
+            if let Some(keys) = keys {
+                for (key, (key_expr, value)) in keys.iter().zip(pairs) {
+                    match fields.iter().find(|(name, _)| name == *key) {
+                        Some((_, field_type)) => self.check_value(value, field_type),
+                        None => self.errors.push(SemanticError::PropertyNotFound {
+                            property: (*key).clone(),
+                            target: record.clone(),
+                            span: key_expr.span,
+                        }),
+                    }
+                }
+                for (name, _) in fields {
+                    if !keys.contains(&name) {
+                        self.errors.push(SemanticError::MissingField { field: name.clone(), target: record.clone(), span: expr.span });
+                    }
+                }
+                return Ok(record);
+            }
+        }
+        self.type_check_expression(expr)
+    }
+
+    /// Erro para `found` usado onde se espera `expected`. Quando a única diferença
+    /// é a possibilidade de `nil`, o erro diz isso em vez de "tipos incompatíveis".
+    fn mismatch(&self, expected: &Type, found: Type, span: Span, expected_from: Option<Span>) -> SemanticError {
+        if matches!(found, Type::Optional(_)) && self.is_compatible(expected, &found.without_nil()) {
+            SemanticError::PossiblyNil { found, span }
+        } else {
+            SemanticError::TypeMismatch { expected: expected.clone(), found, span, expected_from }
+        }
+    }
+
+    /// Verifica o número e os tipos dos argumentos de uma chamada, registrando
+    /// todos os erros encontrados, e retorna o tipo de retorno da função
+    fn check_call(&mut self, call: &CallTarget, args: &[Expr], span: Span) -> Type {
+        self.check_arguments(call, args, span);
+        call.signature.return_type.clone()
+    }
+
+    /// Verifica os argumentos de uma chamada contra a assinatura e retorna o
+    /// tipo encontrado em cada um (`any` nos que tiveram erro)
+    fn check_arguments(&mut self, call: &CallTarget, args: &[Expr], span: Span) -> Vec<Type> {
+        let signature = &call.signature;
+        let (min, max) = (signature.min_args(), signature.max_args());
+        if args.len() < min || max.is_some_and(|max| args.len() > max) {
+            self.errors.push(SemanticError::WrongNumberOfArguments {
+                min,
+                max,
+                found: args.len(),
+                span,
+                declared: call.declared,
+                signature: call.name.as_ref().map(|name| format!("{}{}", name, signature)),
+            });
+        }
+
+        let mut found_types = Vec::new();
+        for (i, arg) in args.iter().enumerate() {
+            let expected = match signature.param_for(i) {
+                Some(param) => param.param_type.clone(),
+                None => Type::Any, // Argumento a mais, já registrado acima
+            };
+            match self.type_check_against(arg, &expected) {
+                Ok(found) if !self.is_compatible(&expected, &found) => {
+                    let expected_from = call.param_spans.get(i).copied().flatten();
+                    let error = self.mismatch(&expected, found, arg.span, expected_from);
+                    self.errors.push(error);
+                    found_types.push(Type::Any);
+                }
+                Ok(found) => found_types.push(found),
+                Err(e) => {
+                    self.errors.push(*e);
+                    found_types.push(Type::Any);
+                }
+            }
+        }
+        if let Some(declared) = call.declared {
+            self.calls.entry(declared).or_default().push(found_types.clone());
+        }
+        found_types
     }
 
-    fn type_check_expression(&mut self, expression: &Expr) -> Result<Type, SemanticError> {
+    /// `collections.push(lista, valor)` e `collections.set(d, chave, valor)`: o
+    /// valor (e a chave) precisam combinar com os tipos da coleção, que é o tipo
+    /// do resultado. `None` quando a coleção não tem tipos de elemento conhecidos.
+    fn check_collection_update(&mut self, function: &str, args: &[Expr], found: &[Type]) -> Option<Type> {
+        let (collection, expected): (Type, Vec<Type>) = match (function, found.first()?) {
+            ("push", list @ Type::List(element)) => (list.clone(), vec![(**element).clone()]),
+            ("set", dict @ Type::Dict(key, value)) => (dict.clone(), vec![(**key).clone(), (**value).clone()]),
+            _ => return None,
+        };
+        for ((expected, found), arg) in expected.iter().zip(&found[1..]).zip(&args[1..]) {
+            if !self.is_compatible(expected, found) {
+                let error = self.mismatch(expected, found.clone(), arg.span, None);
+                self.errors.push(error);
+            }
+        }
+        Some(collection)
+    }
+
+    /// Exige que um operando, alvo de acesso ou função chamada não possa ser `nil`
+    fn require_non_nil(found: &Type, span: Span) -> SemanticResult<()> {
+        if found.is_nullable() {
+            return Err(Box::new(SemanticError::PossiblyNil { found: found.clone(), span }));
+        }
+        Ok(())
+    }
+
+    fn type_check_expression(&mut self, expression: &Expr) -> SemanticResult<Type> {
         match &expression.kind {
             ExprKind::Variable(name) => {
-                if let Some(symbol) = self.symbol_table.lookup(name) {
-                    Ok(symbol.symbol_type.clone())
+                if let Some(symbol) = self.symbol_table.lookup(name).cloned() {
+                    self.record_name(expression.span, &symbol);
+                    Ok(symbol.symbol_type)
                 } else {
-                    Err(SemanticError::VariableNotFound(name.clone()))
+                    Err(Box::new(self.variable_not_found(name, expression.span)))
                 }
             }
             ExprKind::Literal(value) => match value {
                 LiteralValue::Number(n) => if n.fract() == 0.0 { Ok(Type::Int) } else { Ok(Type::Float) },
                 LiteralValue::String(_) => Ok(Type::String),
                 LiteralValue::Boolean(_) => Ok(Type::Bool),
-                LiteralValue::List(_) => Ok(Type::List),
-                LiteralValue::Dict(_) => Ok(Type::Dict),
-                LiteralValue::Nil => Ok(Type::Any),
+                LiteralValue::List(items) => {
+                    let mut element_type: Option<Type> = None;
+                    for item in items {
+                        let item_type = self.type_check_expression(item)?;
+                        element_type = Some(match element_type {
+                            Some(current) => Type::join(&current, &item_type),
+                            None => item_type,
+                        });
+                    }
+                    Ok(Type::list(element_type.unwrap_or(Type::Any)))
+                }
+                LiteralValue::Dict(pairs) => {
+                    let mut entry_types: Option<(Type, Type)> = None;
+                    for (key, value) in pairs {
+                        let key_type = self.type_check_expression(key)?;
+                        let value_type = self.type_check_expression(value)?;
+                        entry_types = Some(match entry_types {
+                            Some((k, v)) => (Type::join(&k, &key_type), Type::join(&v, &value_type)),
+                            None => (key_type, value_type),
+                        });
+                    }
+                    let (key_type, value_type) = entry_types.unwrap_or((Type::Any, Type::Any));
+                    Ok(Type::dict(key_type, value_type))
+                }
+                LiteralValue::Nil => Ok(Type::Nil),
             },
             ExprKind::Binary { left, op, right } => {
                 let left_type = self.type_check_expression(left)?;
                 let right_type = self.type_check_expression(right)?;
+                let symbol = binary_op_symbol(op);
+                let invalid = |type1: Type, type2: Type| SemanticError::InvalidOperation {
+                    op: symbol.to_string(),
+                    type1,
+                    type2: Some(type2),
+                    span: expression.span,
+                };
+
+                if !matches!(op, BinaryOp::Equals | BinaryOp::NotEquals) {
+                    Self::require_non_nil(&left_type, left.span)?;
+                    Self::require_non_nil(&right_type, right.span)?;
+                }
 
                 match op {
                     BinaryOp::Add => {
@@ -563,7 +1498,7 @@ impl SemanticAnalyzer {
                         } else if left_type == Type::String && right_type == Type::String {
                             Ok(Type::String)
                         } else {
-                            Err(SemanticError::InvalidOperation { op: format!("{:?}", op), type1: left_type, type2: Some(right_type) })
+                            Err(Box::new(invalid(left_type, right_type)))
                         }
                     }
                     BinaryOp::Subtract | BinaryOp::Multiply | BinaryOp::Divide => {
@@ -572,28 +1507,62 @@ impl SemanticAnalyzer {
                         } else if left_type.is_numeric() && right_type.is_numeric() {
                             if left_type == Type::Float || right_type == Type::Float { Ok(Type::Float) } else { Ok(Type::Int) }
                         } else {
-                            Err(SemanticError::InvalidOperation { op: format!("{:?}", op), type1: left_type, type2: Some(right_type) })
+                            Err(Box::new(invalid(left_type, right_type)))
                         }
                     }
+                    // Comparar com `nil` é sempre permitido: é assim que opcionais são verificados
+                    BinaryOp::Equals | BinaryOp::NotEquals if left_type == Type::Nil || right_type == Type::Nil => Ok(Type::Bool),
                     BinaryOp::Equals | BinaryOp::NotEquals | BinaryOp::GreaterThan | BinaryOp::LessThan | BinaryOp::GreaterThanOrEquals | BinaryOp::LessThanOrEquals => {
                         if self.is_compatible(&left_type, &right_type) || self.is_compatible(&right_type, &left_type) { Ok(Type::Bool) } else {
-                             Err(SemanticError::InvalidOperation { op: format!("{:?}", op), type1: left_type, type2: Some(right_type) })
+                             Err(Box::new(invalid(left_type, right_type)))
                         }
                     }
                 }
             }
             ExprKind::Unary { op, expr } => {
                 let expr_type = self.type_check_expression(expr)?;
+                Self::require_non_nil(&expr_type, expr.span)?;
                 match op {
                     UnaryOp::Negative => {
                         if expr_type.is_numeric() { Ok(expr_type) } else {
-                            Err(SemanticError::InvalidOperation { op: "Negative".to_string(), type1: expr_type, type2: None })
+                            Err(Box::new(SemanticError::InvalidOperation {
+                                op: "-".to_string(),
+                                type1: expr_type,
+                                type2: None,
+                                span: expression.span,
+                            }))
                         }
                     }
                 }
             }
             ExprKind::FunctionCall { callee, args } => {
+                // `modulo.funcao(...)`: verificada pela assinatura de `stdlib.snaskd`
+                if let ExprKind::PropertyAccess { target, property } = &callee.kind {
+                    if let Some(module) = self.stdlib_module(target) {
+                        return match module.member(property) {
+                            Some(MemberDecl::Function(signature)) => {
+                                let call = CallTarget {
+                                    signature: signature.clone(),
+                                    declared: None,
+                                    param_spans: Vec::new(),
+                                    name: Some(format!("{}.{}", module.name, property)),
+                                };
+                                if module.name == "collections" && matches!(property.as_str(), "push" | "set") {
+                                    let found = self.check_arguments(&call, args, expression.span);
+                                    return Ok(self.check_collection_update(property, args, &found).unwrap_or(signature.return_type.clone()));
+                                }
+                                Ok(self.check_call(&call, args, expression.span))
+                            }
+                            Some(MemberDecl::Constant(found)) => {
+                                Err(Box::new(SemanticError::NotCallable { found: found.clone(), span: callee.span }))
+                            }
+                            None => Err(Box::new(Self::member_not_found(module, property, callee.span))),
+                        };
+                    }
+                }
+
                 let callee_type = self.type_check_expression(callee)?;
+                Self::require_non_nil(&callee_type, callee.span)?;
 
                 if let Some(callee_name) = match &callee.kind {
                     ExprKind::Variable(name) => Some(name),
@@ -610,18 +1579,18 @@ impl SemanticAnalyzer {
                     }
                 }
 
-                if let Type::Function(param_types, return_type) = callee_type {
-                    if args.len() != param_types.len() {
-                        return Err(SemanticError::WrongNumberOfArguments { expected: param_types.len(), found: args.len() });
-                    }
-
-                    for (i, arg) in args.iter().enumerate() {
-                        let arg_type = self.type_check_expression(arg)?;
-                        if !self.is_compatible(&param_types[i], &arg_type) {
-                            return Err(SemanticError::TypeMismatch { expected: param_types[i].clone(), found: arg_type });
-                        }
-                    }
-                    Ok(*return_type.clone())
+                if let Type::Function(param_types, return_type) = &callee_type {
+                    let declared = match &callee.kind {
+                        ExprKind::Variable(name) => self.symbol_table.lookup(name).and_then(|s| s.declared_at),
+                        _ => None,
+                    };
+                    let call = CallTarget {
+                        signature: FunctionDecl::from_types(param_types, return_type),
+                        declared,
+                        param_spans: declared.and_then(|span| self.function_params.get(&span)).cloned().unwrap_or_default(),
+                        name: None,
+                    };
+                    Ok(self.check_call(&call, args, expression.span))
                 } else if callee_type == Type::Any {
                     // Se o tipo do callee é 'Any', não podemos verificar os argumentos.
                     // Apenas assumimos que a chamada é válida e retorna 'Any'.
@@ -630,57 +1599,175 @@ impl SemanticAnalyzer {
                     }
                     Ok(Type::Any)
                 } else {
-                    Err(SemanticError::NotCallable(callee_type))
+                    Err(Box::new(SemanticError::NotCallable { found: callee_type, span: callee.span }))
                 }
             }
             ExprKind::PropertyAccess { target, property } => {
                 let target_type = self.type_check_expression(target)?;
+                Self::require_non_nil(&target_type, target.span)?;
 
-                match target_type {
-                    Type::Any => Ok(Type::Any), // Permite acesso a propriedades em 'Any'
-                    Type::List => {
-                        if property == "push" {
-                            Ok(Type::Function(vec![Type::Any], Box::new(Type::Void)))
-                        } else {
-                            Err(SemanticError::PropertyNotFound(property.clone()))
-                        }
+                let span = expression.span;
+                match &target_type {
+                    Type::Any => match self.stdlib_module(target) {
+                        Some(module) => match module.member(property) {
+                            Some(member) => Ok(member.member_type()),
+                            None => Err(Box::new(Self::member_not_found(module, property, span))),
+                        },
+                        None => Ok(Type::Any), // Permite acesso a propriedades em 'Any'
+                    },
+                    // Listas e dicts não têm métodos: `collections.push` e `collections.set` os alteram
+                    Type::List(_) | Type::Dict(..) => {
+                        Err(Box::new(SemanticError::PropertyNotFound { property: property.clone(), target: target_type, span }))
                     }
-                    Type::Dict => {
-                        if property == "set" {
-                            Ok(Type::Function(vec![Type::Any, Type::Any], Box::new(Type::Void)))
-                        } else {
-                            Err(SemanticError::PropertyNotFound(property.clone()))
-                        }
-                    }
-                    _ => Err(SemanticError::IndexAccessOnNonIndexable(target_type)),
+                    Type::Record(fields) => match fields.iter().find(|(name, _)| name == property) {
+                        Some((_, field_type)) => Ok(field_type.clone()),
+                        None => Err(Box::new(SemanticError::PropertyNotFound { property: property.clone(), target: target_type.clone(), span })),
+                    },
+                    _ => Err(Box::new(SemanticError::IndexAccessOnNonIndexable { found: target_type, span })),
                 }
             }
             ExprKind::IndexAccess { target, index } => {
                 let target_type = self.type_check_expression(target)?;
+                Self::require_non_nil(&target_type, target.span)?;
                 let index_type = self.type_check_expression(index)?;
+                let index_span = index.span;
+                let integer_index = matches!(index_type, Type::Int | Type::Any);
 
-                match target_type {
-                    Type::List => {
-                        if index_type != Type::Int {
-                            self.errors.push(SemanticError::InvalidIndexType(index_type));
+                match &target_type {
+                    Type::Any => Ok(Type::Any),
+                    Type::List(element) => {
+                        if !integer_index {
+                            self.errors.push(SemanticError::InvalidIndexType { target: target_type.clone(), found: index_type, span: index_span });
                         }
-                        Ok(Type::Any)
+                        Ok((**element).clone())
                     }
-                    Type::Dict => {
-                        if !matches!(index_type, Type::String | Type::Int | Type::Float | Type::Bool) {
-                            self.errors.push(SemanticError::InvalidIndexType(index_type));
+                    Type::Dict(key, value) => {
+                        let valid_key = match **key {
+                            Type::Any => matches!(index_type, Type::String | Type::Int | Type::Float | Type::Bool | Type::Any),
+                            _ => self.is_compatible(key, &index_type),
+                        };
+                        if !valid_key {
+                            self.errors.push(SemanticError::InvalidIndexType { target: target_type.clone(), found: index_type, span: index_span });
                         }
-                        Ok(Type::Any)
+                        Ok((**value).clone())
                     }
                     Type::String => {
-                        if index_type != Type::Int {
-                            self.errors.push(SemanticError::InvalidIndexType(index_type));
+                        if !integer_index {
+                            self.errors.push(SemanticError::InvalidIndexType { target: target_type.clone(), found: index_type, span: index_span });
                         }
                         Ok(Type::String)
                     }
-                    _ => Err(SemanticError::IndexAccessOnNonIndexable(target_type)),
+                    Type::Record(fields) => match &index.kind {
+                        ExprKind::Literal(LiteralValue::String(key)) => match fields.iter().find(|(name, _)| name == key) {
+                            Some((_, field_type)) => Ok(field_type.clone()),
+                            None => Err(Box::new(SemanticError::PropertyNotFound { property: key.clone(), target: target_type.clone(), span: index_span })),
+                        },
+                        _ => {
+                            if !matches!(index_type, Type::String | Type::Any) {
+                                self.errors.push(SemanticError::InvalidIndexType { target: target_type.clone(), found: index_type, span: index_span });
+                            }
+                            Ok(Type::Any)
+                        }
+                    },
+                    _ => Err(Box::new(SemanticError::IndexAccessOnNonIndexable { found: target_type, span: expression.span })),
                 }
             }
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SemanticAnalyzer;
+    use crate::parser::parse_program;
+
+    /// Códigos dos erros semânticos de `source`, na ordem em que aparecem
+    fn errors(source: &str) -> Vec<&'static str> {
+        let program = parse_program(source).expect("o programa de teste não compila");
+        let mut analyzer = SemanticAnalyzer::new();
+        analyzer.analyze(&program);
+        analyzer.errors.iter().map(|error| error.code()).collect()
+    }
+
+    #[test]
+    fn checks_list_literal_items_against_annotation() {
+        assert_eq!(errors("let xs: list<int> = [1, 2];"), Vec::<&str>::new());
+        assert_eq!(errors("let xs: list<int> = [1, \"a\"];"), ["E0005"]);
+        assert_eq!(errors("let xs: list<any> = [1, \"a\"];"), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn checks_dict_literal_values_against_annotation() {
+        assert_eq!(errors("let d: dict<str, int> = {\"a\": 1};"), Vec::<&str>::new());
+        assert_eq!(errors("let d: dict<str, int> = {\"a\": \"b\"};"), ["E0005"]);
+    }
+
+    #[test]
+    fn checks_collection_updates_against_element_type() {
+        let prelude = "import \"collections\";\nlet xs: list<int> = [];\nlet d: dict<str, int> = {};\n";
+        assert_eq!(errors(&format!("{}collections.push(xs, 1);\ncollections.set(d, \"a\", 2);", prelude)), Vec::<&str>::new());
+        assert_eq!(errors(&format!("{}collections.push(xs, \"a\");", prelude)), ["E0005"]);
+        assert_eq!(errors(&format!("{}collections.set(d, \"a\", true);", prelude)), ["E0005"]);
+    }
+
+    #[test]
+    fn rejects_methods_on_lists_and_dicts() {
+        assert_eq!(errors("let xs: list<int> = [];\nxs.push(1);"), ["E0012"]);
+        assert_eq!(errors("let d: dict<str, int> = {};\nd.set(\"a\", 1);"), ["E0012"]);
+    }
+
+    #[test]
+    fn rejects_optional_used_without_check() {
+        assert_eq!(errors("fun f(nome: str?): str {\n    return \"oi \" + nome;\n}"), ["E0014"]);
+        assert_eq!(errors("fun f(nome: str?): str {\n    return nome;\n}"), ["E0014"]);
+    }
+
+    #[test]
+    fn narrows_optional_inside_not_nil_branch() {
+        assert_eq!(errors("fun f(nome: str?): str {\n    if nome != nil {\n        return \"oi \" + nome;\n    }\n    return \"\";\n}"), Vec::<&str>::new());
+        assert_eq!(errors("fun f(nome: str?): str {\n    if nome != nil {\n        print(1);\n    }\n    return \"oi \" + nome;\n}"), ["E0014"]);
+    }
+
+    #[test]
+    fn narrows_optional_after_early_return() {
+        assert_eq!(errors("fun f(nome: str?): str {\n    if nome == nil {\n        return \"\";\n    }\n    return \"oi \" + nome;\n}"), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn requires_optional_record_fields_to_be_present() {
+        let alias = "type Usuario = { id: int, email: str? }\n";
+        assert_eq!(errors(&format!("{}let u: Usuario = {{\"id\": 1, \"email\": nil}};", alias)), Vec::<&str>::new());
+        assert_eq!(errors(&format!("{}let u: Usuario = {{\"id\": 1, \"email\": \"a@b\"}};", alias)), Vec::<&str>::new());
+        assert_eq!(errors(&format!("{}let u: Usuario = {{\"id\": 1}};", alias)), ["E0017"]);
+        assert_eq!(errors(&format!("{}let u: Usuario = {{\"id\": \"1\", \"email\": nil}};", alias)), ["E0005"]);
+    }
+
+    #[test]
+    fn checks_record_field_access() {
+        let alias = "type Resposta = { status: int, body: str }\nlet r: Resposta = {\"status\": 200, \"body\": \"ok\"};\n";
+        assert_eq!(errors(&format!("{}let s: int = r.status;", alias)), Vec::<&str>::new());
+        assert_eq!(errors(&format!("{}let s: str = r.status;", alias)), ["E0005"]);
+        assert_eq!(errors(&format!("{}print(r.headers);", alias)), ["E0012"]);
+    }
+
+    #[test]
+    fn checks_union_aliases() {
+        let alias = "type Id = int | str\n";
+        assert_eq!(errors(&format!("{}let a: Id = 1;\nlet b: Id = \"x\";", alias)), Vec::<&str>::new());
+        assert_eq!(errors(&format!("{}let c: Id = true;", alias)), ["E0005"]);
+        assert_eq!(errors("let d: Desconhecido = 1;"), ["E0015"]);
+    }
+
+    #[test]
+    fn reports_missing_return_in_annotated_function() {
+        assert_eq!(errors("fun f(x: int): int {\n    if x > 0 {\n        return 1;\n    }\n}"), ["E0019"]);
+        assert_eq!(errors("fun f(x: int): int {\n    if x > 0 {\n        return 1;\n    } else {\n        return 2;\n    }\n}"), Vec::<&str>::new());
+        assert_eq!(errors("fun f(x: int) {\n    if x > 0 {\n        return 1;\n    }\n}"), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn uses_inferred_return_type_at_call_sites() {
+        assert_eq!(errors("fun nome() {\n    return \"Ana\";\n}\nlet n: int = nome();"), ["E0005"]);
+        assert_eq!(errors("fun nome() {\n    return \"Ana\";\n}\nlet n: str = nome();"), Vec::<&str>::new());
+    }
+}
@@ -7,6 +7,7 @@ pub mod blaze;
 pub mod blaze_auth;
 pub mod collections;
 pub mod blaze_db; // Adicione esta linha
+pub mod testing;
 
 /// Registra todas as funções da biblioteca padrão
 pub fn register_stdlib(globals: &mut SymbolTable) {
@@ -22,4 +23,9 @@ pub fn register_stdlib(globals: &mut SymbolTable) {
     globals.define("blaze_auth".to_string(), blaze_auth::create_module(), false, false);
     globals.define("collections".to_string(), collections::create_module(), false, false);
     globals.define("blaze_db".to_string(), blaze_db::create_module(), false, false); // Adicione esta linha
+
+    // Asserções usadas nos blocos `test`
+    for (name, function) in testing::create_functions() {
+        globals.define(name.to_string(), function, false, false);
+    }
 }
\ No newline at end of file
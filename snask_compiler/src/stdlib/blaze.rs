@@ -1,9 +1,9 @@
 use crate::value::Value;
+use crate::assets;
 use rouille::{Request, Response};
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex, RwLock};
 use chrono::Local;
-use std::fs;
 use lazy_static::lazy_static;
 
 use bcrypt::{hash, verify, DEFAULT_COST};
@@ -534,7 +534,7 @@ pub fn create_module() -> Value {
             
             let template_path = format!("{}/{}", template_dir, template_name);
 
-            let mut template_content = fs::read_to_string(&template_path)
+            let mut template_content = assets::read_to_string(&template_path)
                 .map_err(|e| format!("Erro ao ler arquivo de template '{}': {}", template_path, e))?;
 
             // Processar dados para substituição (aceita Dict ou Nil)
@@ -570,7 +570,7 @@ pub fn create_module() -> Value {
                                 // Se o diretório estático está configurado, usar caminho relativo
                                 if let Some(ref static_path) = static_dir {
                                     let css_path = format!("{}/{}", static_path, css_file);
-                                    if std::path::Path::new(&css_path).exists() {
+                                    if assets::is_file(&css_path) {
                                         css_links.push_str(&format!("<link rel=\"stylesheet\" href=\"/static/{}\">
 ", css_file));
                                     }
@@ -663,7 +663,7 @@ pub fn create_module() -> Value {
                 // Incluir CSS inline
                 if let Some(ref static_path) = static_dir {
                     let css_path = format!("{}/{}", static_path, css_file);
-                    match fs::read_to_string(&css_path) {
+                    match assets::read_to_string(&css_path) {
                         Ok(css_content) => {
                             Ok(Value::String(format!("<style>\n{}\n</style>", css_content)))
                         },
@@ -820,10 +820,11 @@ pub fn create_module() -> Value {
                                                     asset_path.push(part);
                                                 }
                                             }
-                                            if asset_path.is_file() {
-                                                if let Ok(file) = std::fs::File::open(&asset_path) {
+                                            let asset_path_str = asset_path.to_string_lossy().into_owned();
+                                            if assets::is_file(&asset_path_str) {
+                                                if let Ok(bytes) = assets::read(&asset_path_str) {
                                                     let mime = rouille::extension_to_mime(asset_path.extension().and_then(|s| s.to_str()).unwrap_or(""));
-                                                    response_to_return = Response::from_file(mime, file);
+                                                    response_to_return = Response::from_data(mime, bytes);
                                                     route_found = true;
                                                     break;
                                                 }
@@ -872,7 +873,7 @@ pub fn create_module() -> Value {
                                             );
                                             if let Value::Function(_) = handler {
                                                 let res_obj = Value::Dict(HashMap::new()); // Default response object
-                                                let mut handler_res_val = match interp.call_function_by_value(handler.clone(), vec![req_obj, res_obj]) {
+                                                let mut handler_res_val = match call_handler(&mut interp, handler.clone(), vec![req_obj, res_obj]) {
                                                     Ok(res_val) => res_val,
                                                     Err(e) => {
                                                         eprintln!("[Erro no Handler da Rota] {}", e);
@@ -949,6 +950,15 @@ pub fn create_module() -> Value {
     Value::Dict(dict_map)
 }
 
+/// Chama o handler de uma rota. As threads do rouille têm a pilha padrão, então
+/// a chamada roda numa thread com a pilha que `max_call_depth` pede, como o programa principal.
+fn call_handler(interpreter: &mut crate::interpreter::Interpreter, handler: Value, args: Vec<Value>) -> Result<Value, String> {
+    interpreter
+        .with_call_stack(|interpreter| interpreter.call_function_by_value(handler, args))
+        .map_err(|e| e.message)
+        .and_then(|result| result)
+}
+
 fn is_authenticated(session_id: &str) -> Option<String> {
     let mut sessions = SESSIONS.write().unwrap();
     if let Some(session) = sessions.get(session_id) {
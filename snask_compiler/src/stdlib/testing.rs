@@ -0,0 +1,142 @@
+use crate::interpreter::Interpreter;
+use crate::value::Value;
+
+/// Início das mensagens de erro das asserções, usado por `snask test` para
+/// separar falhas de teste de outros erros de execução
+const FAILURE_PREFIXES: [&str; 3] = ["assert falhou", "assert_eq falhou", "assert_raises falhou"];
+
+/// Indica se um erro de execução veio de uma asserção
+pub fn is_assertion_failure(message: &str) -> bool {
+    FAILURE_PREFIXES.iter().any(|prefix| message.starts_with(prefix))
+}
+
+/// Funções globais de asserção: `assert`, `assert_eq` e `assert_raises`
+pub fn create_functions() -> Vec<(&'static str, Value)> {
+    vec![
+        ("assert", Value::NativeFunction(assert)),
+        ("assert_eq", Value::NativeFunction(assert_eq)),
+        ("assert_raises", Value::NativeFunction(assert_raises)),
+    ]
+}
+
+/// `assert(condição, mensagem?)`
+fn assert(args: Vec<Value>, _interpreter: &mut Interpreter) -> Result<Value, String> {
+    if args.is_empty() || args.len() > 2 {
+        return Err("assert espera 1 ou 2 argumentos".to_string());
+    }
+    if args[0].is_truthy() {
+        return Ok(Value::Nil);
+    }
+    match args.get(1) {
+        Some(message) => Err(format!("assert falhou: {}", message)),
+        None => Err(format!("assert falhou: a condição resultou em {}", literal(&args[0]))),
+    }
+}
+
+/// `assert_eq(obtido, esperado, mensagem?)`
+fn assert_eq(args: Vec<Value>, _interpreter: &mut Interpreter) -> Result<Value, String> {
+    if args.len() < 2 || args.len() > 3 {
+        return Err("assert_eq espera 2 ou 3 argumentos".to_string());
+    }
+    let (actual, expected) = (&args[0], &args[1]);
+    if same_value(actual, expected) {
+        return Ok(Value::Nil);
+    }
+    let mut message = match args.get(2) {
+        Some(text) => format!("assert_eq falhou: {}", text),
+        None => "assert_eq falhou: os valores são diferentes".to_string(),
+    };
+    message.push_str(&format!("\n  obtido:   {}\n  esperado: {}", literal(actual), literal(expected)));
+    let mut differences = Vec::new();
+    diff("", actual, expected, &mut differences);
+    // Quando a diferença é o valor inteiro, as duas linhas acima já dizem tudo
+    if !differences.iter().all(|line| line.starts_with(':')) {
+        message.push_str("\n  diferenças:");
+        for line in differences {
+            message.push_str(&format!("\n    {}", line));
+        }
+    }
+    Err(message)
+}
+
+/// `assert_raises(função, trecho?)`: chama a função sem argumentos e exige que
+/// ela termine com erro (contendo `trecho`, se dado). Retorna a mensagem do erro.
+fn assert_raises(args: Vec<Value>, interpreter: &mut Interpreter) -> Result<Value, String> {
+    if args.is_empty() || args.len() > 2 {
+        return Err("assert_raises espera 1 ou 2 argumentos".to_string());
+    }
+    let expected = match args.get(1) {
+        Some(Value::String(text)) => Some(text.clone()),
+        Some(other) => return Err(format!("assert_raises espera uma string como segundo argumento, encontrado {}", literal(other))),
+        None => None,
+    };
+    match interpreter.call_function_by_value(args[0].clone(), vec![]) {
+        Ok(value) => Err(format!("assert_raises falhou: a função retornou {} sem erro", literal(&value))),
+        Err(message) => match expected {
+            Some(text) if !message.contains(&text) => Err(format!(
+                "assert_raises falhou: o erro não contém \"{}\"\n  erro: {}",
+                text, message
+            )),
+            _ => Ok(Value::String(message)),
+        },
+    }
+}
+
+/// Igualdade das asserções: como `==`, mas listas e dicionários são comparados
+/// elemento a elemento com a mesma regra
+fn same_value(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::List(a), Value::List(b)) => a.len() == b.len() && a.iter().zip(b).all(|(x, y)| same_value(x, y)),
+        (Value::Dict(a), Value::Dict(b)) => {
+            a.len() == b.len() && a.iter().all(|(key, x)| b.get(key).is_some_and(|y| same_value(x, y)))
+        }
+        _ => a == b,
+    }
+}
+
+/// Uma linha `caminho: ...` para cada ponto onde `actual` e `expected` diferem
+fn diff(path: &str, actual: &Value, expected: &Value, out: &mut Vec<String>) {
+    match (actual, expected) {
+        (Value::List(a), Value::List(b)) => {
+            for index in 0..a.len().max(b.len()) {
+                let item = format!("{}[{}]", path, index);
+                match (a.get(index), b.get(index)) {
+                    (Some(x), Some(y)) => diff(&item, x, y, out),
+                    (Some(x), None) => out.push(format!("{}: sobrando {}", item, literal(x))),
+                    (None, Some(y)) => out.push(format!("{}: faltando {}", item, literal(y))),
+                    (None, None) => {}
+                }
+            }
+        }
+        (Value::Dict(a), Value::Dict(b)) => {
+            let mut keys: Vec<&Value> = a.keys().chain(b.keys().filter(|key| !a.contains_key(*key))).collect();
+            keys.sort_by_key(|key| literal(key));
+            for key in keys {
+                let item = format!("{}[{}]", path, literal(key));
+                match (a.get(key), b.get(key)) {
+                    (Some(x), Some(y)) => diff(&item, x, y, out),
+                    (Some(x), None) => out.push(format!("{}: sobrando {}", item, literal(x))),
+                    (None, Some(y)) => out.push(format!("{}: faltando {}", item, literal(y))),
+                    (None, None) => {}
+                }
+            }
+        }
+        _ if same_value(actual, expected) => {}
+        _ => out.push(format!("{}: obtido {}, esperado {}", path, literal(actual), literal(expected))),
+    }
+}
+
+/// Valor como seria escrito no código: strings entre aspas, dicionários com as
+/// chaves em ordem (para a saída não mudar de uma execução para outra)
+pub fn literal(value: &Value) -> String {
+    match value {
+        Value::String(s) => format!("{:?}", s),
+        Value::List(items) => format!("[{}]", items.iter().map(literal).collect::<Vec<_>>().join(", ")),
+        Value::Dict(pairs) => {
+            let mut entries: Vec<String> = pairs.iter().map(|(key, value)| format!("{}: {}", literal(key), literal(value))).collect();
+            entries.sort();
+            format!("{{{}}}", entries.join(", "))
+        }
+        other => other.to_string(),
+    }
+}
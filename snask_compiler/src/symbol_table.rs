@@ -66,6 +66,21 @@ impl SymbolTable {
         None
     }
 
+    // All scopes, from the global scope to the current one
+    pub fn scopes(&self) -> &[HashMap<String, Symbol>] {
+        &self.scopes
+    }
+
+    // Remove the scopes from `at` on (never the global scope); `restore_scopes` puts them back
+    pub fn take_scopes_from(&mut self, at: usize) -> Vec<HashMap<String, Symbol>> {
+        let at = at.clamp(1, self.scopes.len());
+        self.scopes.split_off(at)
+    }
+
+    pub fn restore_scopes(&mut self, scopes: Vec<HashMap<String, Symbol>>) {
+        self.scopes.extend(scopes);
+    }
+
     pub fn define_native_function(&mut self, name: &str, func: fn(Vec<Value>, &mut crate::interpreter::Interpreter) -> Result<Value, String>) {
         self.define(name.to_string(), Value::NativeFunction(func), false, false);
     }
@@ -0,0 +1,379 @@
+//! `snask test`: roda os blocos `test "descrição" { ... }` dos arquivos
+//! `*_test.snask`.
+//!
+//! Cada teste roda em um `Interpreter` novo, que primeiro executa o nível
+//! superior do arquivo (imports, funções, constantes) e depois o corpo do
+//! teste. A saída de `print` fica guardada e só aparece se o teste falhar.
+//! Com `--jobs`, os testes rodam em paralelo, mas o resultado sai sempre na
+//! ordem dos arquivos.
+
+use crate::ast::{Program, Stmt, StmtKind};
+use crate::check::collect_files;
+use crate::coverage::{Coverage, CoverageOptions};
+use crate::interpreter::{InterpretResult, Interpreter, Tracer};
+use crate::stdlib::testing::is_assertion_failure;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Sufixo dos arquivos procurados nos diretórios
+const TEST_SUFFIX: &str = "_test.snask";
+
+#[derive(Debug, Clone, Default)]
+pub struct TestOptions {
+    /// Só roda os testes cuja descrição ou arquivo contém este texto
+    pub filter: Option<String>,
+    /// Quantos testes rodam ao mesmo tempo
+    pub jobs: usize,
+    /// Arquivo onde gravar o relatório JUnit XML
+    pub junit: Option<String>,
+    pub max_call_depth: usize,
+    pub coverage: Option<CoverageOptions>,
+}
+
+/// Um bloco `test` e o nível superior do arquivo onde ele está
+struct Case {
+    file: String,
+    name: String,
+    line: usize,
+    setup: Arc<Program>,
+    body: Vec<Stmt>,
+}
+
+enum Outcome {
+    Passed,
+    /// Uma asserção falhou; `location` é `arquivo:linha`
+    Failed { message: String, location: String },
+    /// Outro erro de execução, já renderizado
+    Error(String),
+}
+
+struct TestResult {
+    outcome: Outcome,
+    /// Saída dos `print` durante o teste
+    output: String,
+    time: Duration,
+}
+
+/// Guarda a saída dos `print` em vez de escrevê-la no terminal
+#[derive(Default)]
+struct Capture {
+    output: String,
+}
+
+impl Tracer for Capture {
+    fn output(&mut self, text: &str) -> bool {
+        self.output.push_str(text);
+        self.output.push('\n');
+        true
+    }
+}
+
+/// Descobre e roda os testes; retorna o código de saída (1 se algum falhou)
+pub fn run(paths: &[String], options: TestOptions) -> Result<i32, String> {
+    let started = Instant::now();
+    let paths = if paths.is_empty() { vec![".".to_string()] } else { paths.to_vec() };
+    let files = test_files(&paths)?;
+
+    let coverage = options.coverage.clone().map(|options| Arc::new(Mutex::new(Coverage::with_options(options))));
+    let mut cases = Vec::new();
+    let mut load_errors = Vec::new();
+    for file in &files {
+        let file = file.strip_prefix(".").unwrap_or(file).to_string_lossy().into_owned();
+        let program = match crate::load_program(&file) {
+            Ok(program) => program,
+            Err(e) => {
+                eprint!("{}", e);
+                load_errors.push((file, e));
+                continue;
+            }
+        };
+        if let Some(coverage) = &coverage {
+            coverage.lock().unwrap_or_else(|e| e.into_inner()).add_imports(&program);
+        }
+        cases.extend(split_tests(&file, program).into_iter().filter(|case| matches_filter(case, options.filter.as_deref())));
+    }
+
+    if cases.is_empty() && load_errors.is_empty() {
+        println!("Nenhum teste encontrado");
+        return Ok(0);
+    }
+
+    let results = run_cases(&cases, &options, coverage.clone());
+    let elapsed = started.elapsed();
+
+    let failed = results.iter().filter(|result| !matches!(result.outcome, Outcome::Passed)).count();
+    let passed = results.len() - failed;
+    println!(
+        "\n{} teste{}: {} {}, {} {} ({})",
+        results.len(),
+        if results.len() == 1 { "" } else { "s" },
+        passed,
+        if passed == 1 { "passou" } else { "passaram" },
+        failed,
+        if failed == 1 { "falhou" } else { "falharam" },
+        milliseconds(elapsed)
+    );
+    if !load_errors.is_empty() {
+        let plural = load_errors.len() != 1;
+        println!(
+            "{} arquivo{} não {} ser carregado{}",
+            load_errors.len(),
+            if plural { "s" } else { "" },
+            if plural { "puderam" } else { "pôde" },
+            if plural { "s" } else { "" }
+        );
+    }
+
+    if let Some(path) = &options.junit {
+        let xml = junit_xml(&cases, &results, &load_errors, elapsed);
+        fs::write(path, xml).map_err(|e| format!("Não foi possível gravar {}: {}", path, e))?;
+        println!("Relatório JUnit gravado em {}", path);
+    }
+    let mut code = if failed > 0 || !load_errors.is_empty() { 1 } else { 0 };
+    if let Some(coverage) = &coverage {
+        if let Err(e) = coverage.lock().unwrap_or_else(|e| e.into_inner()).write_report() {
+            eprintln!("Erro: {}", e);
+            code = 1;
+        }
+    }
+    Ok(code)
+}
+
+/// Arquivos dados explicitamente e os `*_test.snask` dos diretórios
+fn test_files(paths: &[String]) -> Result<Vec<PathBuf>, String> {
+    let mut files = Vec::new();
+    for path in paths {
+        if Path::new(path).is_dir() {
+            let found = collect_files(std::slice::from_ref(path))?;
+            files.extend(found.into_iter().filter(|file| file.to_string_lossy().ends_with(TEST_SUFFIX)));
+        } else {
+            files.extend(collect_files(std::slice::from_ref(path))?);
+        }
+    }
+    Ok(files)
+}
+
+/// Separa os blocos `test` do resto do nível superior do arquivo
+fn split_tests(file: &str, program: Program) -> Vec<Case> {
+    let (tests, setup): (Vec<Stmt>, Vec<Stmt>) = program.into_iter().partition(|stmt| matches!(stmt.kind, StmtKind::Test { .. }));
+    let setup = Arc::new(setup);
+    tests
+        .into_iter()
+        .filter_map(|stmt| match stmt.kind {
+            StmtKind::Test { name, body, .. } => Some(Case {
+                file: file.to_string(),
+                name,
+                line: stmt.span.start.line,
+                setup: setup.clone(),
+                body,
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
+fn matches_filter(case: &Case, filter: Option<&str>) -> bool {
+    filter.is_none_or(|filter| case.name.contains(filter) || case.file.contains(filter))
+}
+
+/// Roda os testes em `options.jobs` threads, escrevendo cada resultado assim
+/// que todos os anteriores já foram escritos
+fn run_cases(cases: &[Case], options: &TestOptions, coverage: Option<Arc<Mutex<Coverage>>>) -> Vec<TestResult> {
+    let next = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel();
+    let mut results = Vec::with_capacity(cases.len());
+    std::thread::scope(|scope| {
+        for _ in 0..options.jobs.clamp(1, cases.len().max(1)) {
+            let sender = sender.clone();
+            let (next, coverage) = (&next, coverage.clone());
+            scope.spawn(move || loop {
+                let index = next.fetch_add(1, Ordering::SeqCst);
+                let Some(case) = cases.get(index) else { break };
+                let result = run_case(case, options.max_call_depth, coverage.as_ref());
+                if sender.send((index, result)).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(sender);
+
+        let mut pending = HashMap::new();
+        for (index, result) in receiver {
+            pending.insert(index, result);
+            while let Some(result) = pending.remove(&results.len()) {
+                let case = &cases[results.len()];
+                if results.is_empty() || cases[results.len() - 1].file != case.file {
+                    println!("{}", case.file);
+                }
+                print_result(case, &result);
+                results.push(result);
+            }
+        }
+    });
+    results
+}
+
+fn run_case(case: &Case, max_call_depth: usize, coverage: Option<&Arc<Mutex<Coverage>>>) -> TestResult {
+    let capture = Arc::new(Mutex::new(Capture::default()));
+    let mut interpreter = Interpreter::new();
+    interpreter.set_file(&case.file);
+    interpreter.set_max_call_depth(max_call_depth);
+    interpreter.add_tracer(capture.clone());
+    if let Some(coverage) = coverage {
+        interpreter.add_tracer(coverage.clone());
+    }
+
+    let program: Program = case.setup.iter().cloned().chain(case.body.iter().cloned()).collect();
+    let started = Instant::now();
+    let outcome = match interpreter.interpret(program) {
+        InterpretResult::Ok => Outcome::Passed,
+        InterpretResult::RuntimeError(e) if is_assertion_failure(&e.message) => {
+            let location = match e.innermost() {
+                Some(frame) => match frame.span {
+                    Some(span) => format!("{}:{}", frame.file, span.start.line),
+                    None => frame.file.clone(),
+                },
+                None => case.file.clone(),
+            };
+            Outcome::Failed { message: e.message, location }
+        }
+        InterpretResult::RuntimeError(e) => Outcome::Error(crate::render_runtime_error(&e)),
+    };
+    let time = started.elapsed();
+    let output = std::mem::take(&mut capture.lock().unwrap_or_else(|e| e.into_inner()).output);
+    TestResult { outcome, output, time }
+}
+
+fn print_result(case: &Case, result: &TestResult) {
+    let details = match &result.outcome {
+        Outcome::Passed => {
+            println!("  ✓ {} ({})", case.name, milliseconds(result.time));
+            return;
+        }
+        Outcome::Failed { message, location } => format!("{}\nem {}", message, location),
+        Outcome::Error(rendered) => rendered.trim_end().to_string(),
+    };
+    println!("  ✗ {} ({})", case.name, milliseconds(result.time));
+    for line in details.lines() {
+        println!("      {}", line);
+    }
+    if !result.output.is_empty() {
+        println!("      saída:");
+        for line in result.output.lines() {
+            println!("        {}", line);
+        }
+    }
+}
+
+fn milliseconds(duration: Duration) -> String {
+    format!("{:.1} ms", duration.as_secs_f64() * 1000.0)
+}
+
+/// Relatório JUnit XML: um `<testsuite>` por arquivo
+fn junit_xml(cases: &[Case], results: &[TestResult], load_errors: &[(String, String)], elapsed: Duration) -> String {
+    let failures = results.iter().filter(|result| matches!(result.outcome, Outcome::Failed { .. })).count();
+    let errors = results.iter().filter(|result| matches!(result.outcome, Outcome::Error(_))).count() + load_errors.len();
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str(&format!(
+        "<testsuites name=\"snask\" tests=\"{}\" failures=\"{}\" errors=\"{}\" time=\"{:.3}\">\n",
+        results.len() + load_errors.len(),
+        failures,
+        errors,
+        elapsed.as_secs_f64()
+    ));
+
+    let mut suites: Vec<(&str, Vec<(&Case, &TestResult)>)> = Vec::new();
+    for (case, result) in cases.iter().zip(results) {
+        match suites.last_mut() {
+            Some((file, suite)) if *file == case.file => suite.push((case, result)),
+            _ => suites.push((&case.file, vec![(case, result)])),
+        }
+    }
+    for (file, suite) in suites {
+        let failed = suite.iter().filter(|(_, result)| matches!(result.outcome, Outcome::Failed { .. })).count();
+        let errored = suite.iter().filter(|(_, result)| matches!(result.outcome, Outcome::Error(_))).count();
+        let time: Duration = suite.iter().map(|(_, result)| result.time).sum();
+        xml.push_str(&format!(
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"{}\" time=\"{:.3}\">\n",
+            escape(file),
+            suite.len(),
+            failed,
+            errored,
+            time.as_secs_f64()
+        ));
+        for (case, result) in suite {
+            xml.push_str(&format!(
+                "    <testcase name=\"{}\" classname=\"{}\" file=\"{}\" line=\"{}\" time=\"{:.3}\"",
+                escape(&case.name),
+                escape(file),
+                escape(file),
+                case.line,
+                result.time.as_secs_f64()
+            ));
+            let body = match &result.outcome {
+                Outcome::Passed => String::new(),
+                Outcome::Failed { message, location } => format!(
+                    "      <failure message=\"{}\" type=\"assertion\">{}\nem {}</failure>\n",
+                    escape(message.lines().next().unwrap_or("")),
+                    escape(message),
+                    escape(location)
+                ),
+                Outcome::Error(rendered) => format!(
+                    "      <error message=\"{}\" type=\"runtime\">{}</error>\n",
+                    escape(rendered.lines().next().unwrap_or("")),
+                    escape(rendered)
+                ),
+            };
+            let output = if result.output.is_empty() {
+                String::new()
+            } else {
+                format!("      <system-out>{}</system-out>\n", escape(&result.output))
+            };
+            if body.is_empty() && output.is_empty() {
+                xml.push_str("/>\n");
+            } else {
+                xml.push_str(&format!(">\n{}{}    </testcase>\n", body, output));
+            }
+        }
+        xml.push_str("  </testsuite>\n");
+    }
+    for (file, message) in load_errors {
+        xml.push_str(&format!(
+            "  <testsuite name=\"{0}\" tests=\"1\" failures=\"0\" errors=\"1\" time=\"0.000\">\n    <testcase name=\"(carregamento)\" classname=\"{0}\" file=\"{0}\" time=\"0.000\">\n      <error message=\"o arquivo não pôde ser carregado\" type=\"load\">{1}</error>\n    </testcase>\n  </testsuite>\n",
+            escape(file),
+            escape(message)
+        ));
+    }
+    xml.push_str("</testsuites>\n");
+    xml
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            // Cores ANSI dos diagnósticos (`\x1b[1;31m`): não têm lugar no XML
+            '\x1b' => {
+                for c in chars.by_ref() {
+                    if c.is_ascii_alphabetic() {
+                        break;
+                    }
+                }
+            }
+            // Outros caracteres de controle não são permitidos em XML 1.0
+            c if c.is_control() && !matches!(c, '\n' | '\t' | '\r') => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
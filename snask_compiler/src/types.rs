@@ -1,20 +1,166 @@
-#[derive(Debug, Clone, PartialEq, Eq)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Type {
     Int,
     Float,
     String,
     Bool,
-    List,
-    Dict,
+    List(Box<Type>),            // element_type
+    Dict(Box<Type>, Box<Type>), // key_type, value_type
+    Optional(Box<Type>),        // `T?`: um valor de `T` ou `nil`
+    Nil,                        // tipo do literal `nil`
+    Union(Vec<Type>),           // `int | str`
+    Record(Vec<(String, Type)>), // `{ status: int, body: str }`: dict com campos conhecidos
+    Named(String),              // alias declarado com `type`, resolvido na análise semântica
     Void,
     Any,
     Function(Vec<Type>, Box<Type>), // param_types, return_type
 }
 
 impl Type {
+    pub fn list(element: Type) -> Type {
+        Type::List(Box::new(element))
+    }
+
+    pub fn dict(key: Type, value: Type) -> Type {
+        Type::Dict(Box::new(key), Box::new(value))
+    }
+
+    /// `T?`. Tipos que já aceitam `nil` não são envolvidos de novo, e `nil?` vira `any?`.
+    pub fn optional(inner: Type) -> Type {
+        match inner {
+            Type::Optional(_) => inner,
+            Type::Nil => Type::Optional(Box::new(Type::Any)),
+            _ => Type::Optional(Box::new(inner)),
+        }
+    }
+
+    /// `a | b | ...`, achatando uniões aninhadas e removendo membros repetidos.
+    /// `nil` entre os membros torna a união opcional: `int | nil` é `int?`.
+    pub fn union(members: Vec<Type>) -> Type {
+        let mut flat: Vec<Type> = Vec::new();
+        let mut nullable = false;
+        for member in members {
+            let member = match member {
+                Type::Nil => {
+                    nullable = true;
+                    continue;
+                }
+                Type::Optional(inner) => {
+                    nullable = true;
+                    *inner
+                }
+                other => other,
+            };
+            let members = match member {
+                Type::Union(inner) => inner,
+                other => vec![other],
+            };
+            for member in members {
+                if !flat.contains(&member) {
+                    flat.push(member);
+                }
+            }
+        }
+
+        let base = if flat.contains(&Type::Any) {
+            Type::Any
+        } else if flat.is_empty() {
+            return Type::Nil;
+        } else if flat.len() == 1 {
+            flat.pop().unwrap()
+        } else {
+            Type::Union(flat)
+        };
+        if nullable { Type::optional(base) } else { base }
+    }
+
+    /// Se um valor deste tipo pode ser `nil` (`any` não conta: é verificado em execução)
+    pub fn is_nullable(&self) -> bool {
+        matches!(self, Type::Optional(_) | Type::Nil)
+    }
+
+    /// O tipo sem a possibilidade de `nil`: `T?` vira `T`
+    pub fn without_nil(&self) -> Type {
+        match self {
+            Type::Optional(inner) => (**inner).clone(),
+            Type::Nil => Type::Any,
+            other => other.clone(),
+        }
+    }
+
+    /// Substitui cada alias (`Named`) pelo tipo que `resolve` retorna para ele
+    pub fn resolve_named(&self, resolve: &mut impl FnMut(&str) -> Type) -> Type {
+        match self {
+            Type::Named(name) => resolve(name),
+            Type::List(element) => Type::list(element.resolve_named(resolve)),
+            Type::Dict(key, value) => Type::dict(key.resolve_named(resolve), value.resolve_named(resolve)),
+            Type::Optional(inner) => Type::optional(inner.resolve_named(resolve)),
+            Type::Union(members) => Type::union(members.iter().map(|m| m.resolve_named(resolve)).collect()),
+            Type::Record(fields) => {
+                Type::Record(fields.iter().map(|(name, t)| (name.clone(), t.resolve_named(resolve))).collect())
+            }
+            Type::Function(params, return_type) => Type::Function(
+                params.iter().map(|p| p.resolve_named(resolve)).collect(),
+                Box::new(return_type.resolve_named(resolve)),
+            ),
+            other => other.clone(),
+        }
+    }
+
     pub fn is_numeric(&self) -> bool {
         matches!(self, Type::Int | Type::Float)
     }
+
+    /// Tipo que cobre `a` e `b`, usado para inferir o tipo dos elementos de
+    /// literais: `int` e `float` viram `float`, `nil` torna o tipo opcional e
+    /// tipos diferentes viram `any`.
+    pub fn join(a: &Type, b: &Type) -> Type {
+        match (a, b) {
+            _ if a == b => a.clone(),
+            (Type::Int, Type::Float) | (Type::Float, Type::Int) => Type::Float,
+            (Type::Nil, other) | (other, Type::Nil) => Type::optional(other.clone()),
+            (Type::Optional(x), y) | (y, Type::Optional(x)) => Type::optional(Type::join(x, &y.without_nil())),
+            (Type::List(x), Type::List(y)) => Type::list(Type::join(x, y)),
+            (Type::Dict(k1, v1), Type::Dict(k2, v2)) => Type::dict(Type::join(k1, k2), Type::join(v1, v2)),
+            _ => Type::Any,
+        }
+    }
+}
+
+impl std::fmt::Display for Type {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Type::Int => write!(f, "int"),
+            Type::Float => write!(f, "float"),
+            Type::String => write!(f, "str"),
+            Type::Bool => write!(f, "bool"),
+            Type::List(element) => write!(f, "list<{}>", element),
+            Type::Dict(key, value) => write!(f, "dict<{}, {}>", key, value),
+            Type::Optional(inner) => match **inner {
+                Type::Union(_) | Type::Function(..) => write!(f, "({})?", inner),
+                _ => write!(f, "{}?", inner),
+            },
+            Type::Union(members) => {
+                let members: Vec<String> = members.iter().map(|m| m.to_string()).collect();
+                write!(f, "{}", members.join(" | "))
+            }
+            Type::Record(fields) if fields.is_empty() => write!(f, "{{}}"),
+            Type::Record(fields) => {
+                let fields: Vec<String> = fields.iter().map(|(name, t)| format!("{}: {}", name, t)).collect();
+                write!(f, "{{ {} }}", fields.join(", "))
+            }
+            Type::Named(name) => write!(f, "{}", name),
+            Type::Nil => write!(f, "nil"),
+            Type::Void => write!(f, "void"),
+            Type::Any => write!(f, "any"),
+            Type::Function(params, return_type) => {
+                let params: Vec<String> = params.iter().map(|p| p.to_string()).collect();
+                write!(f, "fun({}): {}", params.join(", "), return_type)
+            }
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -29,8 +175,8 @@ impl std::str::FromStr for Type {
             "float" => Ok(Type::Float),
             "str" => Ok(Type::String),
             "bool" => Ok(Type::Bool),
-            "list" => Ok(Type::List),
-            "dict" => Ok(Type::Dict),
+            "list" => Ok(Type::list(Type::Any)),
+            "dict" => Ok(Type::dict(Type::Any, Type::Any)),
             "void" => Ok(Type::Void),
             "any" => Ok(Type::Any),
             _ => Err(TypeParseError),
@@ -0,0 +1,56 @@
+//! `snask build` com um programa dividido em módulos
+
+mod common;
+
+use common::project_dir;
+use std::fs;
+use std::process::Command;
+
+#[test]
+fn builds_and_runs_program_calling_imported_modules() {
+    let dir = project_dir("build-imports");
+    fs::write(
+        dir.join("main.snask"),
+        "import \"lib/util.snask\";\n\nprint(dobro(21));\nprint(triplo(2));\n",
+    )
+    .unwrap();
+    // `triplo` vem de um módulo importado pelo módulo importado
+    fs::write(
+        dir.join("lib/util.snask"),
+        "import \"lib/base.snask\";\n\nfun dobro(x: float): float {\n    return x * FATOR;\n}\n",
+    )
+    .unwrap();
+    fs::write(dir.join("lib/base.snask"), "const FATOR = 2;\n\nfun triplo(x) {\n    return x * 3;\n}\n").unwrap();
+
+    let build = Command::new(env!("CARGO_BIN_EXE_snask"))
+        .args(["build", "main.snask", "-o", "app"])
+        .current_dir(&dir)
+        .output()
+        .unwrap();
+    assert!(build.status.success(), "snask build falhou:\n{}", String::from_utf8_lossy(&build.stderr));
+
+    // O executável leva os módulos consigo: roda de outro diretório
+    let elsewhere = std::env::temp_dir();
+    let run = Command::new(dir.join("app")).current_dir(&elsewhere).output().unwrap();
+    assert!(run.status.success(), "o executável falhou:\n{}", String::from_utf8_lossy(&run.stderr));
+    assert_eq!(String::from_utf8_lossy(&run.stdout), "42\n6\n");
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn rejects_wrong_call_into_imported_module() {
+    let dir = project_dir("build-imports-typed");
+    fs::write(dir.join("main.snask"), "import \"lib/util.snask\";\n\nprint(metade(\"a\"));\n").unwrap();
+    fs::write(dir.join("lib/util.snask"), "fun metade(x: float): float {\n    return x / 2;\n}\n").unwrap();
+
+    let build = Command::new(env!("CARGO_BIN_EXE_snask"))
+        .args(["build", "main.snask", "-o", "app"])
+        .current_dir(&dir)
+        .output()
+        .unwrap();
+    assert!(!build.status.success());
+    assert!(String::from_utf8_lossy(&build.stderr).contains("E0005"));
+
+    let _ = fs::remove_dir_all(&dir);
+}
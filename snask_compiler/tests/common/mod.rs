@@ -0,0 +1,19 @@
+//! Utilitários compartilhados pelos testes de integração
+
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+/// Diretório temporário novo (com uma pasta `lib`) para um teste
+pub fn project_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("snask-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("lib")).unwrap();
+    dir
+}
+
+/// Indica se há um compilador C (`cc`) no PATH
+#[allow(dead_code)]
+pub fn has_c_compiler() -> bool {
+    Command::new("cc").arg("--version").output().is_ok_and(|output| output.status.success())
+}
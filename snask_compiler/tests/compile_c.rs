@@ -0,0 +1,62 @@
+//! `snask compile` (backend C) com tipos inferidos e módulos importados
+
+mod common;
+
+use common::{has_c_compiler, project_dir};
+use std::fs;
+use std::process::Command;
+
+#[test]
+fn unboxes_types_inferred_by_the_analyzer() {
+    let dir = project_dir("compile-inferred");
+    fs::write(
+        dir.join("main.snask"),
+        "fun sq(x) {\n    return x * x;\n}\n\nfun dobro(n) {\n    return n * 2;\n}\n\nfun aplica(f, v) {\n    return f(v);\n}\n\nlet total = sq(3) + 1;\nprint(total);\nprint(aplica(dobro, 4));\n",
+    )
+    .unwrap();
+
+    let compile = Command::new(env!("CARGO_BIN_EXE_snask"))
+        .args(["compile", "main.snask", "-o", "main.c"])
+        .current_dir(&dir)
+        .output()
+        .unwrap();
+    assert!(compile.status.success(), "snask compile falhou:\n{}", String::from_utf8_lossy(&compile.stderr));
+
+    let c = fs::read_to_string(dir.join("main.c")).unwrap();
+    assert!(c.contains("static double fn_sq(double v_x) {"), "{}", c);
+    assert!(c.contains("static double g_total = 0;"), "{}", c);
+    // `dobro` também é passada como valor: continua com `SnValue`
+    assert!(c.contains("static SnValue fn_dobro(SnValue v_n) {"), "{}", c);
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+/// Precisa de um compilador C (`cc`) no PATH; sem ele, o teste é pulado
+#[test]
+fn builds_program_calling_imported_modules() {
+    if !has_c_compiler() {
+        eprintln!("pulado: nenhum `cc` encontrado no PATH");
+        return;
+    }
+    let dir = project_dir("compile-imports");
+    fs::write(dir.join("main.snask"), "import \"lib/util.snask\";\n\nprint(dobro(21));\nprint(triplo(2));\n").unwrap();
+    fs::write(
+        dir.join("lib/util.snask"),
+        "import \"lib/base.snask\";\n\nfun dobro(x: float): float {\n    return x * FATOR;\n}\n",
+    )
+    .unwrap();
+    fs::write(dir.join("lib/base.snask"), "const FATOR = 2;\n\nfun triplo(x) {\n    return x * 3;\n}\n").unwrap();
+
+    let build = Command::new(env!("CARGO_BIN_EXE_snask"))
+        .args(["compile", "main.snask", "--build", "-o", "main.c"])
+        .current_dir(&dir)
+        .output()
+        .unwrap();
+    assert!(build.status.success(), "snask compile --build falhou:\n{}", String::from_utf8_lossy(&build.stderr));
+
+    let run = Command::new(dir.join("main")).output().unwrap();
+    assert!(run.status.success(), "o executável falhou:\n{}", String::from_utf8_lossy(&run.stderr));
+    assert_eq!(String::from_utf8_lossy(&run.stdout), "42\n6\n");
+
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,62 @@
+//! Os exemplos completos da documentação da stdlib passam em `snask check`
+
+mod common;
+
+use common::project_dir;
+use std::fs;
+use std::process::Command;
+
+const DECLARATIONS: &str = include_str!("../src/stdlib/stdlib.snaskd");
+
+/// Exemplos ```snask dos comentários `///` de cada `module`: programas
+/// completos, ao contrário dos trechos nos membros
+fn module_examples() -> Vec<(String, String)> {
+    let mut examples = Vec::new();
+    let mut doc: Vec<&str> = Vec::new();
+    for line in DECLARATIONS.lines() {
+        if let Some(text) = line.trim_start().strip_prefix("///") {
+            doc.push(text.strip_prefix(' ').unwrap_or(text));
+            continue;
+        }
+        if let Some(module) = line.strip_prefix("module ") {
+            let name = module.trim_end_matches(" {").to_string();
+            let mut code = None;
+            for text in &doc {
+                match (text.trim(), &mut code) {
+                    ("```snask", None) => code = Some(String::new()),
+                    ("```", Some(finished)) => {
+                        examples.push((name.clone(), std::mem::take(finished)));
+                        code = None;
+                    }
+                    (_, Some(code)) => {
+                        code.push_str(text);
+                        code.push('\n');
+                    }
+                    _ => {}
+                }
+            }
+        }
+        doc.clear();
+    }
+    examples
+}
+
+#[test]
+fn module_examples_pass_check() {
+    let examples = module_examples();
+    assert!(!examples.is_empty());
+    let dir = project_dir("stdlib-examples");
+    for (i, (module, code)) in examples.iter().enumerate() {
+        let file = dir.join(format!("{}_{}.snask", module, i));
+        fs::write(&file, code).unwrap();
+        let check = Command::new(env!("CARGO_BIN_EXE_snask")).arg("check").arg(&file).output().unwrap();
+        assert!(
+            check.status.success(),
+            "o exemplo do módulo `{}` não passa em `snask check`:\n{}\n{}",
+            module,
+            code,
+            String::from_utf8_lossy(&check.stderr)
+        );
+    }
+    let _ = fs::remove_dir_all(&dir);
+}